@@ -0,0 +1,317 @@
+//! gonhanh-convert: command-line text conversion
+//!
+//! Reads files (or stdin) and converts using the core engine, so results
+//! match what users see while typing:
+//! - Telex/VNI/VIQR keystrokes → Vietnamese Unicode (default)
+//! - Vietnamese Unicode → Telex/VNI/VIQR keystrokes
+//! - Unicode ↔ legacy charsets (TCVN3, VNI-Windows, VISCII)
+//! - Diacritic stripping (Tiếng Việt → Tieng Viet)
+
+use gonhanh_core::charset::{self, Charset};
+use gonhanh_core::convert::{self, ConvertOptions};
+use gonhanh_core::fold::{self, FoldOptions};
+use gonhanh_core::input::Family;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: gonhanh-convert [OPTIONS] [FILE]...
+
+Convert Vietnamese text. Reads FILEs (or stdin when none or '-') and
+writes to stdout.
+
+Modes:
+  -m, --method <NAME>       Typed text to Unicode: telex (default), vni, viqr
+  -t, --to <NAME>           Unicode to typed text: telex, vni, viqr
+  -e, --encode <CHARSET>    Unicode to legacy charset: tcvn3, vni-win, viscii
+  -d, --decode <CHARSET>    Legacy charset to Unicode
+  -s, --strip               Remove diacritics (Tiếng Việt → Tieng Viet)
+
+Engine options (with --method):
+      --modern              Modern tone placement (hoà instead of hòa)
+      --free-tone           Allow diacritics anywhere, skip spelling checks
+      --auto-restore-english
+                            Restore English words typed by accident
+
+  -o, --output <FILE>       Write to FILE instead of stdout
+  -h, --help                Print help
+  -V, --version             Print version
+";
+
+/// What to do with the input
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    /// Replay typed text through the engine (input method id)
+    Type(u8),
+    /// Vietnamese text to the keystrokes of an input method family
+    Keystrokes(Family),
+    Encode(Charset),
+    Decode(Charset),
+    Strip,
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    mode: Mode,
+    options: ConvertOptions,
+    files: Vec<String>,
+    output: Option<String>,
+}
+
+/// Parse command line (without program name)
+///
+/// Returns Ok(None) when help/version was printed.
+fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Args>, String> {
+    let mut mode = None;
+    // Match the app defaults rather than the engine's (traditional placement)
+    let mut options = ConvertOptions {
+        modern_tone: false,
+        ..Default::default()
+    };
+    let mut files = Vec::new();
+    let mut output = None;
+
+    let mut it = args.into_iter();
+    while let Some(arg) = it.next() {
+        let mut value = |name: &str| it.next().ok_or(format!("{} requires a value", name));
+        let new_mode = match arg.as_str() {
+            "-m" | "--method" => Some(Mode::Type(parse_method(&value(&arg)?)?)),
+            "-t" | "--to" => Some(Mode::Keystrokes(parse_family(&value(&arg)?)?)),
+            "-e" | "--encode" => Some(Mode::Encode(parse_charset(&value(&arg)?)?)),
+            "-d" | "--decode" => Some(Mode::Decode(parse_charset(&value(&arg)?)?)),
+            "-s" | "--strip" => Some(Mode::Strip),
+            "--modern" => {
+                options.modern_tone = true;
+                None
+            }
+            "--free-tone" => {
+                options.free_tone = true;
+                None
+            }
+            "--auto-restore-english" => {
+                options.english_auto_restore = true;
+                None
+            }
+            "-o" | "--output" => {
+                output = Some(value(&arg)?);
+                None
+            }
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                return Ok(None);
+            }
+            "-V" | "--version" => {
+                println!("gonhanh-convert {}", env!("CARGO_PKG_VERSION"));
+                return Ok(None);
+            }
+            "-" => {
+                files.push(arg);
+                None
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option '{}'", arg)),
+            _ => {
+                files.push(arg);
+                None
+            }
+        };
+        if let Some(m) = new_mode {
+            if mode.is_some_and(|old| old != m) {
+                return Err("only one of --method, --to, --encode, --decode, --strip".to_string());
+            }
+            mode = Some(m);
+        }
+    }
+
+    Ok(Some(Args {
+        mode: mode.unwrap_or(Mode::Type(0)),
+        options,
+        files,
+        output,
+    }))
+}
+
+fn parse_method(name: &str) -> Result<u8, String> {
+    match name.to_ascii_lowercase().as_str() {
+        "telex" => Ok(0),
+        "vni" => Ok(1),
+        "viqr" => Ok(2),
+        _ => Err(format!("unknown input method '{}'", name)),
+    }
+}
+
+fn parse_family(name: &str) -> Result<Family, String> {
+    match name.to_ascii_lowercase().as_str() {
+        "telex" => Ok(Family::Telex),
+        "vni" => Ok(Family::Vni),
+        "viqr" => Ok(Family::Viqr),
+        _ => Err(format!("unknown input method '{}'", name)),
+    }
+}
+
+fn parse_charset(name: &str) -> Result<Charset, String> {
+    match name.to_ascii_lowercase().as_str() {
+        "unicode" | "utf-8" | "utf8" => Ok(Charset::Unicode),
+        "tcvn3" | "abc" => Ok(Charset::Tcvn3),
+        "vni-win" | "vni-windows" | "vni" => Ok(Charset::VniWindows),
+        "viscii" => Ok(Charset::Viscii),
+        "vps" => Err("charset 'vps' is not supported yet".to_string()),
+        _ => Err(format!("unknown charset '{}'", name)),
+    }
+}
+
+/// Convert one input according to `mode`
+fn convert_bytes(input: &[u8], mode: Mode, options: &ConvertOptions) -> Result<Vec<u8>, String> {
+    if let Mode::Decode(cs) = mode {
+        return Ok(charset::decode(input, cs).into_bytes());
+    }
+    let text = std::str::from_utf8(input).map_err(|e| format!("input is not UTF-8: {}", e))?;
+    Ok(match mode {
+        Mode::Type(method) => convert::convert(text, method, options).into_bytes(),
+        Mode::Keystrokes(family) => convert::to_keystrokes(text, family).into_bytes(),
+        Mode::Encode(cs) => charset::encode(text, cs),
+        Mode::Strip => fold::fold(text, &FoldOptions::default()).into_bytes(),
+        Mode::Decode(_) => unreachable!(),
+    })
+}
+
+fn read_input(file: &str) -> Result<Vec<u8>, String> {
+    if file == "-" {
+        let mut buf = Vec::new();
+        io::stdin()
+            .read_to_end(&mut buf)
+            .map_err(|e| format!("stdin: {}", e))?;
+        return Ok(buf);
+    }
+    fs::read(file).map_err(|e| format!("{}: {}", file, e))
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let files = if args.files.is_empty() {
+        vec!["-".to_string()]
+    } else {
+        args.files
+    };
+    let mut out = Vec::new();
+    for file in &files {
+        let input = read_input(file)?;
+        let converted = convert_bytes(&input, args.mode, &args.options)
+            .map_err(|e| format!("{}: {}", file, e))?;
+        out.extend_from_slice(&converted);
+    }
+    match args.output {
+        Some(path) => fs::write(&path, out).map_err(|e| format!("{}: {}", path, e)),
+        None => io::stdout()
+            .write_all(&out)
+            .map_err(|e| format!("stdout: {}", e)),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => return ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("gonhanh-convert: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("gonhanh-convert: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Result<Option<Args>, String> {
+        parse_args(list.iter().map(|s| s.to_string()))
+    }
+
+    fn run_mode(input: &str, mode: Mode, options: ConvertOptions) -> Vec<u8> {
+        convert_bytes(input.as_bytes(), mode, &options).unwrap()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let a = args(&["-m", "vni", "--modern", "a.txt", "-"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(a.mode, Mode::Type(1));
+        assert!(a.options.modern_tone);
+        assert!(!a.options.free_tone);
+        assert_eq!(a.files, vec!["a.txt", "-"]);
+
+        let a = args(&["--encode", "TCVN3", "-o", "out.txt"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(a.mode, Mode::Encode(Charset::Tcvn3));
+        assert_eq!(a.output.as_deref(), Some("out.txt"));
+
+        let a = args(&["--to", "VIQR"]).unwrap().unwrap();
+        assert_eq!(a.mode, Mode::Keystrokes(Family::Viqr));
+
+        let a = args(&[]).unwrap().unwrap();
+        assert_eq!(a.mode, Mode::Type(0));
+        assert!(!a.options.modern_tone);
+    }
+
+    #[test]
+    fn test_parse_args_errors() {
+        assert!(args(&["--method"]).is_err());
+        assert!(args(&["--method", "foo"]).is_err());
+        assert!(args(&["--decode", "koi8"]).is_err());
+        assert!(args(&["--encode", "vps"]).is_err());
+        assert!(args(&["--strip", "--encode", "viscii"]).is_err());
+        assert!(args(&["--to", "unicode"]).is_err());
+        assert!(args(&["--to", "vni", "--method", "vni"]).is_err());
+        assert!(args(&["--bogus"]).is_err());
+    }
+
+    #[test]
+    fn test_convert_modes() {
+        let opts = ConvertOptions {
+            modern_tone: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            run_mode("Tieengs Vieetj, hoaf\n", Mode::Type(0), opts),
+            "Tiếng Việt, hòa\n".as_bytes()
+        );
+        assert_eq!(
+            run_mode("hoa2", Mode::Type(1), ConvertOptions::default()),
+            "hoà".as_bytes()
+        );
+        assert_eq!(
+            run_mode("Tiếng Việt\n", Mode::Keystrokes(Family::Telex), opts),
+            b"Tieengs Vieetj\n"
+        );
+        assert_eq!(
+            run_mode("Tiếng Việt", Mode::Keystrokes(Family::Vni), opts),
+            b"Tie6ng1 Vie6t5"
+        );
+        assert_eq!(
+            run_mode("Việt", Mode::Encode(Charset::VniWindows), opts),
+            b"Vie\xE4t"
+        );
+        assert_eq!(
+            convert_bytes(b"Vie\xE4t", Mode::Decode(Charset::VniWindows), &opts).unwrap(),
+            "Việt".as_bytes()
+        );
+        assert_eq!(
+            run_mode("Tiếng Việt, ĐƯỜNG!", Mode::Strip, opts),
+            b"Tieng Viet, DUONG!"
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let opts = ConvertOptions::default();
+        assert!(convert_bytes(b"\xFF", Mode::Strip, &opts).is_err());
+    }
+}
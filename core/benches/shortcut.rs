@@ -0,0 +1,119 @@
+//! Shortcut table benchmarks
+//!
+//! `lookup/linear` reproduces the previous table (HashMap plus a trigger list
+//! scanned on every lookup) as a baseline for `lookup/trie`.
+//!
+//! Run: cargo bench --bench shortcut
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use gonhanh_core::engine::shortcut::{Shortcut, ShortcutTable};
+use std::collections::HashMap;
+
+/// Previous implementation: exact match by scanning triggers longest first
+struct LinearTable {
+    shortcuts: HashMap<String, Shortcut>,
+    sorted_triggers: Vec<String>,
+}
+
+impl LinearTable {
+    fn new(shortcuts: &[Shortcut]) -> Self {
+        let shortcuts: HashMap<String, Shortcut> = shortcuts
+            .iter()
+            .map(|s| (s.trigger.clone(), s.clone()))
+            .collect();
+        let mut sorted_triggers: Vec<String> = shortcuts.keys().cloned().collect();
+        sorted_triggers.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        Self {
+            shortcuts,
+            sorted_triggers,
+        }
+    }
+
+    fn lookup(&self, buffer: &str) -> Option<&Shortcut> {
+        let buffer_lower = buffer.to_lowercase();
+        for trigger in &self.sorted_triggers {
+            if buffer_lower == *trigger {
+                if let Some(shortcut) = self.shortcuts.get(trigger) {
+                    if shortcut.enabled {
+                        return Some(shortcut);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Deterministic pseudo-random triggers of 2-8 letters
+fn shortcuts(n: usize) -> Vec<Shortcut> {
+    let mut seed: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut next = move || {
+        seed = seed
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (seed >> 33) as usize
+    };
+    let mut seen = std::collections::HashSet::new();
+    let mut list = Vec::with_capacity(n);
+    while list.len() < n {
+        let len = 2 + next() % 7;
+        let trigger: String = (0..len)
+            .map(|_| (b'a' + (next() % 26) as u8) as char)
+            .collect();
+        if seen.insert(trigger.clone()) {
+            list.push(Shortcut::new(&trigger, "thay thế"));
+        }
+    }
+    list
+}
+
+fn lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lookup");
+    for n in [100, 1_000, 10_000] {
+        let list = shortcuts(n);
+        let linear = LinearTable::new(&list);
+        let mut trie = ShortcutTable::new();
+        for s in &list {
+            trie.add(s.clone());
+        }
+        // Half hits, half misses (typed words that are not triggers)
+        let queries: Vec<String> = list
+            .iter()
+            .step_by((n / 50).max(1))
+            .flat_map(|s| [s.trigger.clone(), format!("{}q", s.trigger)])
+            .collect();
+
+        group.bench_with_input(BenchmarkId::new("linear", n), &queries, |b, queries| {
+            b.iter(|| {
+                for q in queries {
+                    black_box(linear.lookup(black_box(q)));
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("trie", n), &queries, |b, queries| {
+            b.iter(|| {
+                for q in queries {
+                    black_box(trie.lookup(black_box(q)));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn suggest(c: &mut Criterion) {
+    let mut table = ShortcutTable::new();
+    for s in shortcuts(10_000) {
+        table.add(s);
+    }
+    let mut group = c.benchmark_group("suggest");
+    for prefix in ["a", "ab", "abc", "abcd"] {
+        group.bench_with_input(BenchmarkId::from_parameter(prefix), prefix, |b, prefix| {
+            b.iter(|| black_box(table.suggest(black_box(prefix), 10)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, lookup, suggest);
+criterion_main!(benches);
@@ -0,0 +1,778 @@
+//! Output Charsets and Unicode Forms
+//!
+//! Encoders/decoders between Unicode and the 8-bit encodings still found in
+//! older documents and fonts:
+//! - TCVN3 (ABC): one byte per lowercase letter. Capitals come from separate
+//!   "H" fonts (.VnTimeH) that reuse the lowercase codes, so only Ă Â Ê Ô Ơ Ư Đ
+//!   have their own bytes and decoding yields lowercase for the rest.
+//! - VNI-Windows: ASCII base letter followed by a diacritic byte (á = "aù")
+//! - VISCII: one byte per letter, including 6 C0 control positions
+//!
+//! VPS is not implemented yet. glibc and libiconv, which the tables below
+//! were generated from, have no VPS converter, and byte values that no
+//! published mapping checks would only make tests agree with themselves. It
+//! can be added like VISCII once a reference table (e.g. Mozilla's `vps.uf`)
+//! is vendored for `test_reference_mappings`.
+//!
+//! Legacy fonts render bytes as Windows "ANSI" text, so the engine emits each
+//! byte as the Latin-1 codepoint of the same value (see `Engine::set_charset`).
+//!
+//! Unicode output can also be decomposed (see `Form`) for apps that expect
+//! combining diacritics instead of precomposed NFC letters.
+
+use crate::data::chars::{self, mark, tone};
+use crate::data::keys;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Output encoding
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Charset {
+    #[default]
+    Unicode = 0,
+    Tcvn3 = 1,
+    VniWindows = 2,
+    Viscii = 3,
+}
+
+impl Charset {
+    /// Charset from its FFI id (see `ime_charset`)
+    pub fn from_u8(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::Unicode),
+            1 => Some(Self::Tcvn3),
+            2 => Some(Self::VniWindows),
+            3 => Some(Self::Viscii),
+            _ => None,
+        }
+    }
+
+    /// Unicode → legacy bytes table (None for Unicode)
+    fn table(self) -> Option<&'static [(char, &'static [u8])]> {
+        match self {
+            Self::Unicode => None,
+            Self::Tcvn3 => Some(TCVN3),
+            Self::VniWindows => Some(VNI_WINDOWS),
+            Self::Viscii => Some(VISCII),
+        }
+    }
+}
+
+/// Unicode composition of output letters
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Form {
+    /// Precomposed NFC: ệ = U+1EC7
+    #[default]
+    Precomposed = 0,
+    /// Fully decomposed NFD: ệ = e + U+0323 + U+0302
+    Decomposed = 1,
+    /// Windows-1258 style: precomposed base, combining mark only (ệ = ê + U+0323)
+    Windows1258 = 2,
+}
+
+impl Form {
+    /// Form from its FFI id (see `ime_output_form`)
+    pub fn from_u8(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::Precomposed),
+            1 => Some(Self::Decomposed),
+            2 => Some(Self::Windows1258),
+            _ => None,
+        }
+    }
+}
+
+/// Combining character for a mark (sắc, huyền, hỏi, ngã, nặng)
+fn combining_mark(m: u8) -> Option<char> {
+    match m {
+        mark::SAC => Some('\u{301}'),
+        mark::HUYEN => Some('\u{300}'),
+        mark::HOI => Some('\u{309}'),
+        mark::NGA => Some('\u{303}'),
+        mark::NANG => Some('\u{323}'),
+        _ => None,
+    }
+}
+
+/// Combining character for a tone (breve is HORN on A)
+fn combining_tone(key: u16, t: u8) -> Option<char> {
+    match t {
+        tone::CIRCUMFLEX => Some('\u{302}'),
+        tone::HORN if key == keys::A => Some('\u{306}'),
+        tone::HORN => Some('\u{31B}'),
+        _ => None,
+    }
+}
+
+/// Decompose one character into `out`
+///
+/// Non-Vietnamese characters (and đ, which has no decomposition) are pushed
+/// unchanged. Decomposed output follows Unicode canonical order: horn, then
+/// dot below, then the other marks (ợ = o + U+031B + U+0323).
+pub fn decompose(c: char, form: Form, out: &mut Vec<char>) {
+    let parsed = match form {
+        Form::Precomposed => None,
+        _ => chars::parse_char(c).filter(|p| !p.stroke && (p.tone | p.mark) != 0),
+    };
+    let Some(p) = parsed else {
+        out.push(c);
+        return;
+    };
+    let mark = combining_mark(p.mark);
+    if form == Form::Windows1258 {
+        out.extend(chars::to_char(p.key, p.caps, p.tone, 0));
+        out.extend(mark);
+        return;
+    }
+    out.extend(chars::to_char(p.key, p.caps, 0, 0));
+    let tone = combining_tone(p.key, p.tone);
+    // Dot below (class 220) sorts before circumflex/breve (230), after horn (216)
+    let horn = p.tone == tone::HORN && p.key != keys::A;
+    if p.mark == mark::NANG && !horn {
+        out.extend(mark);
+        out.extend(tone);
+    } else {
+        out.extend(tone);
+        out.extend(mark);
+    }
+}
+
+/// Encode one character, or None if the charset has no bytes for it
+///
+/// ASCII maps to itself in every legacy charset. TCVN3 capitals without their
+/// own code use the lowercase bytes (rendered uppercase by the "H" fonts).
+pub fn encode_char(c: char, charset: Charset) -> Option<Vec<u8>> {
+    let Some(table) = charset.table() else {
+        return Some(c.to_string().into_bytes());
+    };
+    if c.is_ascii() && !(charset == Charset::Viscii && is_viscii_control(c as u8)) {
+        return Some(vec![c as u8]);
+    }
+    if let Some(bytes) = lookup(table, c) {
+        return Some(bytes.to_vec());
+    }
+    if charset == Charset::Tcvn3 {
+        let mut lower = c.to_lowercase();
+        if let (Some(l), None) = (lower.next(), lower.next()) {
+            return lookup(table, l).map(<[u8]>::to_vec);
+        }
+    }
+    None
+}
+
+/// Encode text, replacing characters the charset cannot represent with '?'
+pub fn encode(text: &str, charset: Charset) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        match encode_char(c, charset) {
+            Some(bytes) => out.extend_from_slice(&bytes),
+            None => out.push(b'?'),
+        }
+    }
+    out
+}
+
+/// Decode legacy bytes to Unicode
+///
+/// Bytes outside the Vietnamese table decode as Latin-1, matching how legacy
+/// fonts are typed through Windows. `Unicode` decodes UTF-8 (lossy).
+pub fn decode(bytes: &[u8], charset: Charset) -> String {
+    let Some(table) = charset.table() else {
+        return String::from_utf8_lossy(bytes).into_owned();
+    };
+    let mut out = String::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        // Longest match first: VNI pairs a base letter with a diacritic byte
+        let pair = bytes.get(i..i + 2).and_then(|p| reverse_lookup(table, p));
+        if let Some(c) = pair {
+            out.push(c);
+            i += 2;
+            continue;
+        }
+        let b = bytes[i];
+        match reverse_lookup(table, &bytes[i..i + 1]) {
+            Some(c) => out.push(c),
+            None => out.push(b as char),
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Engine output chars for a charset and form
+///
+/// Legacy charsets give one Latin-1 char per byte, as typed through a legacy
+/// font (unencodable characters are kept as is); `form` only applies to
+/// `Unicode`.
+pub(crate) fn to_output_chars(chars: &[char], charset: Charset, form: Form) -> Vec<char> {
+    let mut out = Vec::with_capacity(chars.len() * 2);
+    for &c in chars {
+        if charset == Charset::Unicode {
+            decompose(c, form, &mut out);
+            continue;
+        }
+        match encode_char(c, charset) {
+            Some(bytes) => out.extend(bytes.into_iter().map(char::from)),
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+/// Number of chars `c` occupies on screen in the given output
+pub(crate) fn output_width(c: char, charset: Charset, form: Form) -> usize {
+    to_output_chars(&[c], charset, form).len()
+}
+
+/// VISCII reuses these C0 positions for capital letters
+fn is_viscii_control(b: u8) -> bool {
+    matches!(b, 0x02 | 0x05 | 0x06 | 0x14 | 0x19 | 0x1E)
+}
+
+fn lookup(table: &[(char, &'static [u8])], c: char) -> Option<&'static [u8]> {
+    table.iter().find(|(t, _)| *t == c).map(|(_, b)| *b)
+}
+
+fn reverse_lookup(table: &[(char, &[u8])], bytes: &[u8]) -> Option<char> {
+    table.iter().find(|(_, b)| *b == bytes).map(|(c, _)| *c)
+}
+
+// ============================================================
+// Tables (generated from glibc iconv for VISCII/TCVN 5712, VNI from the
+// VNI-Windows font layout)
+// ============================================================
+
+/// VISCII (RFC 1456): one byte per letter
+const VISCII: &[(char, &[u8])] = &[
+    ('á', &[0xE1]),
+    ('Á', &[0xC1]),
+    ('à', &[0xE0]),
+    ('À', &[0xC0]),
+    ('ả', &[0xE4]),
+    ('Ả', &[0xC4]),
+    ('ã', &[0xE3]),
+    ('Ã', &[0xC3]),
+    ('ạ', &[0xD5]),
+    ('Ạ', &[0x80]),
+    ('ă', &[0xE5]),
+    ('Ă', &[0xC5]),
+    ('ắ', &[0xA1]),
+    ('Ắ', &[0x81]),
+    ('ằ', &[0xA2]),
+    ('Ằ', &[0x82]),
+    ('ẳ', &[0xC6]),
+    ('Ẳ', &[0x02]),
+    ('ẵ', &[0xC7]),
+    ('Ẵ', &[0x05]),
+    ('ặ', &[0xA3]),
+    ('Ặ', &[0x83]),
+    ('â', &[0xE2]),
+    ('Â', &[0xC2]),
+    ('ấ', &[0xA4]),
+    ('Ấ', &[0x84]),
+    ('ầ', &[0xA5]),
+    ('Ầ', &[0x85]),
+    ('ẩ', &[0xA6]),
+    ('Ẩ', &[0x86]),
+    ('ẫ', &[0xE7]),
+    ('Ẫ', &[0x06]),
+    ('ậ', &[0xA7]),
+    ('Ậ', &[0x87]),
+    ('é', &[0xE9]),
+    ('É', &[0xC9]),
+    ('è', &[0xE8]),
+    ('È', &[0xC8]),
+    ('ẻ', &[0xEB]),
+    ('Ẻ', &[0xCB]),
+    ('ẽ', &[0xA8]),
+    ('Ẽ', &[0x88]),
+    ('ẹ', &[0xA9]),
+    ('Ẹ', &[0x89]),
+    ('ê', &[0xEA]),
+    ('Ê', &[0xCA]),
+    ('ế', &[0xAA]),
+    ('Ế', &[0x8A]),
+    ('ề', &[0xAB]),
+    ('Ề', &[0x8B]),
+    ('ể', &[0xAC]),
+    ('Ể', &[0x8C]),
+    ('ễ', &[0xAD]),
+    ('Ễ', &[0x8D]),
+    ('ệ', &[0xAE]),
+    ('Ệ', &[0x8E]),
+    ('í', &[0xED]),
+    ('Í', &[0xCD]),
+    ('ì', &[0xEC]),
+    ('Ì', &[0xCC]),
+    ('ỉ', &[0xEF]),
+    ('Ỉ', &[0x9B]),
+    ('ĩ', &[0xEE]),
+    ('Ĩ', &[0xCE]),
+    ('ị', &[0xB8]),
+    ('Ị', &[0x98]),
+    ('ó', &[0xF3]),
+    ('Ó', &[0xD3]),
+    ('ò', &[0xF2]),
+    ('Ò', &[0xD2]),
+    ('ỏ', &[0xF6]),
+    ('Ỏ', &[0x99]),
+    ('õ', &[0xF5]),
+    ('Õ', &[0xA0]),
+    ('ọ', &[0xF7]),
+    ('Ọ', &[0x9A]),
+    ('ô', &[0xF4]),
+    ('Ô', &[0xD4]),
+    ('ố', &[0xAF]),
+    ('Ố', &[0x8F]),
+    ('ồ', &[0xB0]),
+    ('Ồ', &[0x90]),
+    ('ổ', &[0xB1]),
+    ('Ổ', &[0x91]),
+    ('ỗ', &[0xB2]),
+    ('Ỗ', &[0x92]),
+    ('ộ', &[0xB5]),
+    ('Ộ', &[0x93]),
+    ('ơ', &[0xBD]),
+    ('Ơ', &[0xB4]),
+    ('ớ', &[0xBE]),
+    ('Ớ', &[0x95]),
+    ('ờ', &[0xB6]),
+    ('Ờ', &[0x96]),
+    ('ở', &[0xB7]),
+    ('Ở', &[0x97]),
+    ('ỡ', &[0xDE]),
+    ('Ỡ', &[0xB3]),
+    ('ợ', &[0xFE]),
+    ('Ợ', &[0x94]),
+    ('ú', &[0xFA]),
+    ('Ú', &[0xDA]),
+    ('ù', &[0xF9]),
+    ('Ù', &[0xD9]),
+    ('ủ', &[0xFC]),
+    ('Ủ', &[0x9C]),
+    ('ũ', &[0xFB]),
+    ('Ũ', &[0x9D]),
+    ('ụ', &[0xF8]),
+    ('Ụ', &[0x9E]),
+    ('ư', &[0xDF]),
+    ('Ư', &[0xBF]),
+    ('ứ', &[0xD1]),
+    ('Ứ', &[0xBA]),
+    ('ừ', &[0xD7]),
+    ('Ừ', &[0xBB]),
+    ('ử', &[0xD8]),
+    ('Ử', &[0xBC]),
+    ('ữ', &[0xE6]),
+    ('Ữ', &[0xFF]),
+    ('ự', &[0xF1]),
+    ('Ự', &[0xB9]),
+    ('ý', &[0xFD]),
+    ('Ý', &[0xDD]),
+    ('ỳ', &[0xCF]),
+    ('Ỳ', &[0x9F]),
+    ('ỷ', &[0xD6]),
+    ('Ỷ', &[0x14]),
+    ('ỹ', &[0xDB]),
+    ('Ỹ', &[0x19]),
+    ('ỵ', &[0xDC]),
+    ('Ỵ', &[0x1E]),
+    ('đ', &[0xF0]),
+    ('Đ', &[0xD0]),
+];
+
+/// TCVN3 (ABC)
+const TCVN3: &[(char, &[u8])] = &[
+    ('á', &[0xB8]),
+    ('à', &[0xB5]),
+    ('ả', &[0xB6]),
+    ('ã', &[0xB7]),
+    ('ạ', &[0xB9]),
+    ('ă', &[0xA8]),
+    ('ắ', &[0xBE]),
+    ('ằ', &[0xBB]),
+    ('ẳ', &[0xBC]),
+    ('ẵ', &[0xBD]),
+    ('ặ', &[0xC6]),
+    ('â', &[0xA9]),
+    ('ấ', &[0xCA]),
+    ('ầ', &[0xC7]),
+    ('ẩ', &[0xC8]),
+    ('ẫ', &[0xC9]),
+    ('ậ', &[0xCB]),
+    ('é', &[0xD0]),
+    ('è', &[0xCC]),
+    ('ẻ', &[0xCE]),
+    ('ẽ', &[0xCF]),
+    ('ẹ', &[0xD1]),
+    ('ê', &[0xAA]),
+    ('ế', &[0xD5]),
+    ('ề', &[0xD2]),
+    ('ể', &[0xD3]),
+    ('ễ', &[0xD4]),
+    ('ệ', &[0xD6]),
+    ('í', &[0xDD]),
+    ('ì', &[0xD7]),
+    ('ỉ', &[0xD8]),
+    ('ĩ', &[0xDC]),
+    ('ị', &[0xDE]),
+    ('ó', &[0xE3]),
+    ('ò', &[0xDF]),
+    ('ỏ', &[0xE1]),
+    ('õ', &[0xE2]),
+    ('ọ', &[0xE4]),
+    ('ô', &[0xAB]),
+    ('ố', &[0xE8]),
+    ('ồ', &[0xE5]),
+    ('ổ', &[0xE6]),
+    ('ỗ', &[0xE7]),
+    ('ộ', &[0xE9]),
+    ('ơ', &[0xAC]),
+    ('ớ', &[0xED]),
+    ('ờ', &[0xEA]),
+    ('ở', &[0xEB]),
+    ('ỡ', &[0xEC]),
+    ('ợ', &[0xEE]),
+    ('ú', &[0xF3]),
+    ('ù', &[0xEF]),
+    ('ủ', &[0xF1]),
+    ('ũ', &[0xF2]),
+    ('ụ', &[0xF4]),
+    ('ư', &[0xAD]),
+    ('ứ', &[0xF8]),
+    ('ừ', &[0xF5]),
+    ('ử', &[0xF6]),
+    ('ữ', &[0xF7]),
+    ('ự', &[0xF9]),
+    ('ý', &[0xFD]),
+    ('ỳ', &[0xFA]),
+    ('ỷ', &[0xFB]),
+    ('ỹ', &[0xFC]),
+    ('ỵ', &[0xFE]),
+    ('đ', &[0xAE]),
+    ('Ă', &[0xA1]),
+    ('Â', &[0xA2]),
+    ('Ê', &[0xA3]),
+    ('Ô', &[0xA4]),
+    ('Ơ', &[0xA5]),
+    ('Ư', &[0xA6]),
+    ('Đ', &[0xA7]),
+];
+
+/// VNI-Windows: base letter plus diacritic byte(s)
+const VNI_WINDOWS: &[(char, &[u8])] = &[
+    ('á', &[0x61, 0xF9]),
+    ('Á', &[0x41, 0xD9]),
+    ('à', &[0x61, 0xF8]),
+    ('À', &[0x41, 0xD8]),
+    ('ả', &[0x61, 0xFB]),
+    ('Ả', &[0x41, 0xDB]),
+    ('ã', &[0x61, 0xF5]),
+    ('Ã', &[0x41, 0xD5]),
+    ('ạ', &[0x61, 0xEF]),
+    ('Ạ', &[0x41, 0xCF]),
+    ('ă', &[0x61, 0xEA]),
+    ('Ă', &[0x41, 0xCA]),
+    ('ắ', &[0x61, 0xE9]),
+    ('Ắ', &[0x41, 0xC9]),
+    ('ằ', &[0x61, 0xE8]),
+    ('Ằ', &[0x41, 0xC8]),
+    ('ẳ', &[0x61, 0xFA]),
+    ('Ẳ', &[0x41, 0xDA]),
+    ('ẵ', &[0x61, 0xFC]),
+    ('Ẵ', &[0x41, 0xDC]),
+    ('ặ', &[0x61, 0xEB]),
+    ('Ặ', &[0x41, 0xCB]),
+    ('â', &[0x61, 0xE2]),
+    ('Â', &[0x41, 0xC2]),
+    ('ấ', &[0x61, 0xE1]),
+    ('Ấ', &[0x41, 0xC1]),
+    ('ầ', &[0x61, 0xE0]),
+    ('Ầ', &[0x41, 0xC0]),
+    ('ẩ', &[0x61, 0xE5]),
+    ('Ẩ', &[0x41, 0xC5]),
+    ('ẫ', &[0x61, 0xE3]),
+    ('Ẫ', &[0x41, 0xC3]),
+    ('ậ', &[0x61, 0xE4]),
+    ('Ậ', &[0x41, 0xC4]),
+    ('é', &[0x65, 0xF9]),
+    ('É', &[0x45, 0xD9]),
+    ('è', &[0x65, 0xF8]),
+    ('È', &[0x45, 0xD8]),
+    ('ẻ', &[0x65, 0xFB]),
+    ('Ẻ', &[0x45, 0xDB]),
+    ('ẽ', &[0x65, 0xF5]),
+    ('Ẽ', &[0x45, 0xD5]),
+    ('ẹ', &[0x65, 0xEF]),
+    ('Ẹ', &[0x45, 0xCF]),
+    ('ê', &[0x65, 0xE2]),
+    ('Ê', &[0x45, 0xC2]),
+    ('ế', &[0x65, 0xE1]),
+    ('Ế', &[0x45, 0xC1]),
+    ('ề', &[0x65, 0xE0]),
+    ('Ề', &[0x45, 0xC0]),
+    ('ể', &[0x65, 0xE5]),
+    ('Ể', &[0x45, 0xC5]),
+    ('ễ', &[0x65, 0xE3]),
+    ('Ễ', &[0x45, 0xC3]),
+    ('ệ', &[0x65, 0xE4]),
+    ('Ệ', &[0x45, 0xC4]),
+    ('í', &[0xED]),
+    ('Í', &[0xCD]),
+    ('ì', &[0xEC]),
+    ('Ì', &[0xCC]),
+    ('ỉ', &[0xE6]),
+    ('Ỉ', &[0xC6]),
+    ('ĩ', &[0xF3]),
+    ('Ĩ', &[0xD3]),
+    ('ị', &[0xF2]),
+    ('Ị', &[0xD2]),
+    ('ó', &[0x6F, 0xF9]),
+    ('Ó', &[0x4F, 0xD9]),
+    ('ò', &[0x6F, 0xF8]),
+    ('Ò', &[0x4F, 0xD8]),
+    ('ỏ', &[0x6F, 0xFB]),
+    ('Ỏ', &[0x4F, 0xDB]),
+    ('õ', &[0x6F, 0xF5]),
+    ('Õ', &[0x4F, 0xD5]),
+    ('ọ', &[0x6F, 0xEF]),
+    ('Ọ', &[0x4F, 0xCF]),
+    ('ô', &[0x6F, 0xE2]),
+    ('Ô', &[0x4F, 0xC2]),
+    ('ố', &[0x6F, 0xE1]),
+    ('Ố', &[0x4F, 0xC1]),
+    ('ồ', &[0x6F, 0xE0]),
+    ('Ồ', &[0x4F, 0xC0]),
+    ('ổ', &[0x6F, 0xE5]),
+    ('Ổ', &[0x4F, 0xC5]),
+    ('ỗ', &[0x6F, 0xE3]),
+    ('Ỗ', &[0x4F, 0xC3]),
+    ('ộ', &[0x6F, 0xE4]),
+    ('Ộ', &[0x4F, 0xC4]),
+    ('ơ', &[0xF4]),
+    ('Ơ', &[0xD4]),
+    ('ớ', &[0xF4, 0xF9]),
+    ('Ớ', &[0xD4, 0xD9]),
+    ('ờ', &[0xF4, 0xF8]),
+    ('Ờ', &[0xD4, 0xD8]),
+    ('ở', &[0xF4, 0xFB]),
+    ('Ở', &[0xD4, 0xDB]),
+    ('ỡ', &[0xF4, 0xF5]),
+    ('Ỡ', &[0xD4, 0xD5]),
+    ('ợ', &[0xF4, 0xEF]),
+    ('Ợ', &[0xD4, 0xCF]),
+    ('ú', &[0x75, 0xF9]),
+    ('Ú', &[0x55, 0xD9]),
+    ('ù', &[0x75, 0xF8]),
+    ('Ù', &[0x55, 0xD8]),
+    ('ủ', &[0x75, 0xFB]),
+    ('Ủ', &[0x55, 0xDB]),
+    ('ũ', &[0x75, 0xF5]),
+    ('Ũ', &[0x55, 0xD5]),
+    ('ụ', &[0x75, 0xEF]),
+    ('Ụ', &[0x55, 0xCF]),
+    ('ư', &[0xF6]),
+    ('Ư', &[0xD6]),
+    ('ứ', &[0xF6, 0xF9]),
+    ('Ứ', &[0xD6, 0xD9]),
+    ('ừ', &[0xF6, 0xF8]),
+    ('Ừ', &[0xD6, 0xD8]),
+    ('ử', &[0xF6, 0xFB]),
+    ('Ử', &[0xD6, 0xDB]),
+    ('ữ', &[0xF6, 0xF5]),
+    ('Ữ', &[0xD6, 0xD5]),
+    ('ự', &[0xF6, 0xEF]),
+    ('Ự', &[0xD6, 0xCF]),
+    ('ý', &[0x79, 0xF9]),
+    ('Ý', &[0x59, 0xD9]),
+    ('ỳ', &[0x79, 0xF8]),
+    ('Ỳ', &[0x59, 0xD8]),
+    ('ỷ', &[0x79, 0xFB]),
+    ('Ỷ', &[0x59, 0xDB]),
+    ('ỹ', &[0x79, 0xF5]),
+    ('Ỹ', &[0x59, 0xD5]),
+    ('ỵ', &[0xEE]),
+    ('Ỵ', &[0xCE]),
+    ('đ', &[0xF1]),
+    ('Đ', &[0xD1]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::chars::to_char;
+
+    /// All 134 Vietnamese letters outside ASCII
+    fn vietnamese_letters() -> Vec<char> {
+        let mut out = vec!['đ', 'Đ'];
+        for key in [keys::A, keys::E, keys::I, keys::O, keys::U, keys::Y] {
+            for t in [tone::NONE, tone::CIRCUMFLEX, tone::HORN] {
+                for m in mark::NONE..=mark::NANG {
+                    for caps in [false, true] {
+                        if let Some(c) = to_char(key, caps, t, m) {
+                            if !c.is_ascii() && !out.contains(&c) {
+                                out.push(c);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    fn latin1(s: &str) -> Vec<u8> {
+        s.chars().map(|c| c as u8).collect()
+    }
+
+    #[test]
+    fn test_tables_complete_and_unique() {
+        let letters = vietnamese_letters();
+        assert_eq!(letters.len(), 134);
+        for charset in [Charset::VniWindows, Charset::Viscii] {
+            let table = charset.table().unwrap();
+            assert_eq!(table.len(), 134, "{:?}", charset);
+            for (i, (c, bytes)) in table.iter().enumerate() {
+                assert!(letters.contains(c), "{:?} {}", charset, c);
+                assert!(
+                    table[i + 1..].iter().all(|(_, b)| b != bytes),
+                    "{:?} duplicate bytes for {}",
+                    charset,
+                    c
+                );
+            }
+        }
+        // 67 lowercase letters + 7 capitals with their own code
+        assert_eq!(TCVN3.len(), 74);
+    }
+
+    #[test]
+    fn test_round_trip_all_letters() {
+        for c in vietnamese_letters() {
+            for charset in [Charset::Unicode, Charset::VniWindows, Charset::Viscii] {
+                let s = c.to_string();
+                assert_eq!(decode(&encode(&s, charset), charset), s, "{:?}", charset);
+            }
+            // TCVN3 capitals without a code come back lowercase
+            let tcvn3 = decode(&encode(&c.to_string(), Charset::Tcvn3), Charset::Tcvn3);
+            assert!(tcvn3 == c.to_string() || tcvn3 == c.to_lowercase().to_string());
+        }
+    }
+
+    #[test]
+    fn test_reference_mappings() {
+        // VNI-Windows as it appears in documents viewed without the font
+        assert_eq!(encode("Việt Nam", Charset::VniWindows), latin1("Vieät Nam"));
+        assert_eq!(encode("đường", Charset::VniWindows), latin1("ñöôøng"));
+        assert_eq!(encode("Đà Nẵng", Charset::VniWindows), latin1("Ñaø Naüng"));
+        assert_eq!(encode("năm", Charset::VniWindows), latin1("naêm"));
+        assert_eq!(encode("nghĩa", Charset::VniWindows), latin1("nghóa"));
+
+        // TCVN3 (.VnTime)
+        assert_eq!(encode("Việt Nam", Charset::Tcvn3), b"Vi\xD6t Nam");
+        assert_eq!(encode("đường", Charset::Tcvn3), b"\xAE\xAD\xEAng");
+        assert_eq!(encode("ĐƯỜNG", Charset::Tcvn3), b"\xA7\xA6\xEANG");
+
+        // VISCII (RFC 1456)
+        assert_eq!(encode("Việt Nam", Charset::Viscii), b"Vi\xAEt Nam");
+        assert_eq!(encode("Ẳ Ỵ", Charset::Viscii), b"\x02 \x1E");
+        assert_eq!(encode("đ Đ", Charset::Viscii), b"\xF0 \xD0");
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(
+            decode(&latin1("Tieáng Vieät"), Charset::VniWindows),
+            "Tiếng Việt"
+        );
+        assert_eq!(decode(b"Ti\xD5ng Vi\xD6t", Charset::Tcvn3), "Tiếng Việt");
+        assert_eq!(decode(b"Ti\xAAng Vi\xAEt", Charset::Viscii), "Tiếng Việt");
+        assert_eq!(decode("Tiếng".as_bytes(), Charset::Unicode), "Tiếng");
+    }
+
+    #[test]
+    fn test_unencodable() {
+        assert_eq!(encode("€ 日", Charset::Tcvn3), b"? ?");
+        assert_eq!(encode_char('\u{2}', Charset::Viscii), None);
+        assert_eq!(encode_char('€', Charset::Viscii), None);
+    }
+
+    fn decomposed(s: &str, form: Form) -> String {
+        let mut out = Vec::new();
+        for c in s.chars() {
+            decompose(c, form, &mut out);
+        }
+        out.into_iter().collect()
+    }
+
+    #[test]
+    fn test_decompose() {
+        // Expected NFD from Python's unicodedata.normalize
+        let nfd = [
+            ("á", "a\u{301}"),
+            ("ệ", "e\u{323}\u{302}"),
+            ("ậ", "a\u{323}\u{302}"),
+            ("ặ", "a\u{323}\u{306}"),
+            ("ấ", "a\u{302}\u{301}"),
+            ("ợ", "o\u{31B}\u{323}"),
+            ("Ờ", "O\u{31B}\u{300}"),
+            ("ư", "u\u{31B}"),
+            ("ỹ", "y\u{303}"),
+            ("đường", "đu\u{31B}o\u{31B}\u{300}ng"),
+            ("a日", "a日"),
+        ];
+        for (input, expected) in nfd {
+            assert_eq!(decomposed(input, Form::Decomposed), expected, "{}", input);
+        }
+        let cp1258 = [
+            ("ệ", "ê\u{323}"),
+            ("Ặ", "Ă\u{323}"),
+            ("ơ", "ơ"),
+            ("Việt", "Viê\u{323}t"),
+        ];
+        for (input, expected) in cp1258 {
+            assert_eq!(decomposed(input, Form::Windows1258), expected, "{}", input);
+        }
+        assert_eq!(decomposed("Việt", Form::Precomposed), "Việt");
+    }
+
+    #[test]
+    fn test_decompose_all_letters() {
+        for c in vietnamese_letters() {
+            let nfd = decomposed(&c.to_string(), Form::Decomposed);
+            let mut it = nfd.chars();
+            let base = it.next().unwrap();
+            assert!(
+                base.is_ascii_alphabetic() || base == 'đ' || base == 'Đ',
+                "{}",
+                c
+            );
+            assert!(it.all(|m| ('\u{300}'..='\u{36F}').contains(&m)), "{}", c);
+            let w = decomposed(&c.to_string(), Form::Windows1258);
+            assert!(w.chars().count() <= 2, "{}", c);
+        }
+    }
+
+    #[test]
+    fn test_font_chars() {
+        let chars: Vec<char> = "á".chars().collect();
+        let pre = Form::Precomposed;
+        assert_eq!(
+            to_output_chars(&chars, Charset::VniWindows, pre),
+            vec!['a', 'ù']
+        );
+        assert_eq!(to_output_chars(&chars, Charset::Unicode, pre), vec!['á']);
+        assert_eq!(output_width('á', Charset::VniWindows, pre), 2);
+        assert_eq!(output_width('á', Charset::Tcvn3, pre), 1);
+        assert_eq!(output_width('日', Charset::Tcvn3, pre), 1);
+        // Form only applies to Unicode output
+        assert_eq!(output_width('á', Charset::Tcvn3, Form::Decomposed), 1);
+    }
+}
@@ -0,0 +1,229 @@
+//! Batch Text Conversion
+//!
+//! Converts a whole string typed in Telex/VNI/VIQR into Vietnamese by replaying
+//! it through the engine, so the result matches what users see while typing.
+//!
+//! Characters that have a key on a US layout (letters, digits, punctuation,
+//! space, newline, tab) are fed as keystrokes. Anything else (already-accented
+//! letters, other scripts, emoji) ends the current word and is copied as is.
+//!
+//! The `reverse` submodule goes the other way: Vietnamese text to keystrokes.
+
+mod reverse;
+
+pub use reverse::{to_keystrokes, to_telex, to_viqr, to_vni};
+
+use crate::data::keys;
+use crate::engine::{Action, Engine};
+
+/// Engine settings used for conversion
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConvertOptions {
+    /// Modern tone placement (hoà) instead of traditional (hòa)
+    pub modern_tone: bool,
+    /// Allow diacritics anywhere without spelling validation
+    pub free_tone: bool,
+    /// Restore English words that were accidentally transformed
+    pub english_auto_restore: bool,
+}
+
+impl Default for ConvertOptions {
+    /// Same defaults as a new `Engine`
+    fn default() -> Self {
+        Self {
+            modern_tone: true,
+            free_tone: false,
+            english_auto_restore: false,
+        }
+    }
+}
+
+/// Key typing `c`, shared with `Engine::on_char` (`keys::from_char`)
+///
+/// Editing keys (Backspace, Esc) are not text: their chars are copied.
+fn text_key(c: char) -> Option<(u16, bool)> {
+    keys::from_char(c).filter(|&(key, _)| key != keys::DELETE && key != keys::ESC)
+}
+
+/// Reusable converter (keeps one engine for many conversions)
+pub struct Converter {
+    engine: Engine,
+}
+
+impl Converter {
+    /// Create a converter for an input method id (see `Engine::set_method`)
+    pub fn new(method: u8, options: &ConvertOptions) -> Self {
+        let mut engine = Engine::new();
+        engine.set_method(method);
+        engine.set_modern_tone(options.modern_tone);
+        engine.set_free_tone(options.free_tone);
+        engine.set_english_auto_restore(options.english_auto_restore);
+        Self { engine }
+    }
+
+    /// Convert text, returning the Vietnamese result
+    pub fn convert(&mut self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len() + text.len() / 4);
+        for c in text.chars() {
+            match text_key(c) {
+                Some((key, shift)) => self.type_key(&mut out, c, key, shift),
+                None => {
+                    self.end_word(&mut out);
+                    out.push(c);
+                }
+            }
+        }
+        self.end_word(&mut out);
+        self.engine.clear_all();
+        out
+    }
+
+    /// Feed one keystroke and apply the engine's edit to `out`
+    fn type_key(&mut self, out: &mut String, c: char, key: u16, shift: bool) {
+        let r = if keys::is_letter(key) {
+            self.engine.on_key_ext(key, shift, false, false)
+        } else {
+            self.engine.on_key_ext(key, false, false, shift)
+        };
+        if r.action != Action::Send as u8 {
+            out.push(c);
+            return;
+        }
+        apply(out, r.backspace, &r.chars[..r.count as usize]);
+        // Space output already includes the space; other break keys are
+        // typed by the app after the edit unless the engine consumed them
+        if key != keys::SPACE && keys::is_break_ext(key, shift) && !r.key_consumed() {
+            out.push(c);
+        }
+    }
+
+    /// Commit the current word (auto-restore applies) without typing anything
+    fn end_word(&mut self, out: &mut String) {
+        // Tab is a word break with no shortcut trigger char
+        let r = self.engine.on_key_ext(keys::TAB, false, false, false);
+        if r.action == Action::Send as u8 {
+            apply(out, r.backspace, &r.chars[..r.count as usize]);
+        }
+    }
+}
+
+/// Apply backspaces then insert codepoints
+fn apply(out: &mut String, backspace: u8, chars: &[u32]) {
+    for _ in 0..backspace {
+        out.pop();
+    }
+    out.extend(chars.iter().filter_map(|&c| char::from_u32(c)));
+}
+
+/// Convert text typed with an input method into Vietnamese
+///
+/// # Arguments
+/// * `text` - Text as typed, e.g. "Tieengs Vieetj" (Telex)
+/// * `method` - Input method id: 0=Telex, 1=VNI, 2=VIQR, 3+=registered
+/// * `options` - Engine settings to apply
+///
+/// # Example
+/// ```
+/// use gonhanh_core::convert::{convert, ConvertOptions};
+/// let s = convert("Tieengs Vieetj, xin chaof!", 0, &ConvertOptions::default());
+/// assert_eq!(s, "Tiếng Việt, xin chào!");
+/// ```
+pub fn convert(text: &str, method: u8, options: &ConvertOptions) -> String {
+    Converter::new(method, options).convert(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(method: u8, options: ConvertOptions, cases: &[(&str, &str)]) {
+        for (input, expected) in cases {
+            assert_eq!(convert(input, method, &options), *expected, "'{}'", input);
+        }
+    }
+
+    #[test]
+    fn test_telex_sentences() {
+        run(
+            0,
+            ConvertOptions::default(),
+            &[
+                ("Tieengs Vieetj", "Tiếng Việt"),
+                ("xin chaof, tooi teen laf Nam.", "xin chào, tôi tên là Nam."),
+                ("Ddaay laf dduwowngf!", "Đây là đường!"),
+                ("hoaf", "hoà"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_vni_and_viqr() {
+        run(
+            1,
+            ConvertOptions::default(),
+            &[("Tie6ng1 Vie65t", "Tiếng Việt"), ("d9i hoc5", "đi học")],
+        );
+        run(
+            2,
+            ConvertOptions::default(),
+            &[("Tie^'ng Vie^.t", "Tiếng Việt"), ("ddi ho.c\\.", "đi học.")],
+        );
+    }
+
+    #[test]
+    fn test_preserves_whitespace_and_symbols() {
+        run(
+            0,
+            ConvertOptions::default(),
+            &[
+                ("  vieetj\tnam\r\n(vn)  ", "  việt\tnam\r\n(vn)  "),
+                ("a+b=c; x<y>z", "a+b=c; x<y>z"),
+                // Editing keys are text here, not Backspace/Esc
+                ("aa\u{8}\u{7f}dd\u{1b}", "â\u{8}\u{7f}đ\u{1b}"),
+                (
+                    "email: nhatkha1407@gmail.com",
+                    "email: nhatkha1407@gmail.com",
+                ),
+                ("", ""),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_preserves_non_latin() {
+        run(
+            0,
+            ConvertOptions::default(),
+            &[
+                ("việt nam", "việt nam"),
+                ("日本 vaf 中国", "日本 và 中国"),
+                ("😀chaof😀", "😀chào😀"),
+                ("Ωmega", "Ωmega"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_options() {
+        let traditional = ConvertOptions {
+            modern_tone: false,
+            ..Default::default()
+        };
+        run(0, traditional, &[("hoaf thuys", "hòa thúy")]);
+
+        let auto_restore = ConvertOptions {
+            english_auto_restore: true,
+            ..Default::default()
+        };
+        run(0, auto_restore, &[("text vieetj", "text việt")]);
+        assert_eq!(convert("text", 0, &ConvertOptions::default()), "tẽt");
+    }
+
+    #[test]
+    fn test_converter_reuse() {
+        let mut c = Converter::new(0, &ConvertOptions::default());
+        assert_eq!(c.convert("chaof"), "chào");
+        // State from the previous call must not leak into the next one
+        assert_eq!(c.convert("s"), "s");
+    }
+}
@@ -0,0 +1,376 @@
+//! Reverse Conversion
+//!
+//! Turns Vietnamese text back into the canonical keystrokes of a built-in
+//! input method, e.g. "Tiếng Việt" → "Tieengs Vieetj" (Telex),
+//! "Tie6ng1 Vie6t5" (VNI) or "Tie^'ng Vie^.t" (VIQR).
+//!
+//! Canonical form per word:
+//! - Letters in order, each followed by its tone key (â → aa/a6/a^, ư → uw/u7/u+)
+//! - đ typed as dd (Telex, VIQR) or d9 (VNI)
+//! - Mark key at the end of the word (Telex, VNI) or right after its vowel (VIQR)
+//! - Telex: a plain double letter gets a third one to undo the doubling
+//!   (xoong → xooong, not xông)
+//!
+//! Feeding the result to `convert` with the same method gives back the
+//! original text, as long as the engine uses the same tone placement style
+//! (modern/traditional) as the text. Non-Vietnamese words are emitted as is,
+//! so they may be transformed on the way back (Telex "w", VNI "a1"). Double-o
+//! loanwords with a mark (soóc, goòng) do not convert back in any method: the
+//! engine does not put marks on them.
+
+use crate::data::chars::{self, mark, tone, ParsedChar};
+use crate::data::keys;
+use crate::input::Family;
+use crate::utils::key_to_char;
+
+/// Convert Vietnamese text to Telex keystrokes
+///
+/// # Example
+/// ```
+/// use gonhanh_core::convert::to_telex;
+/// assert_eq!(to_telex("Tiếng Việt"), "Tieengs Vieetj");
+/// ```
+pub fn to_telex(text: &str) -> String {
+    to_keystrokes(text, Family::Telex)
+}
+
+/// Convert Vietnamese text to VNI keystrokes
+///
+/// # Example
+/// ```
+/// use gonhanh_core::convert::to_vni;
+/// assert_eq!(to_vni("Tiếng Việt"), "Tie6ng1 Vie6t5");
+/// ```
+pub fn to_vni(text: &str) -> String {
+    to_keystrokes(text, Family::Vni)
+}
+
+/// Convert Vietnamese text to VIQR keystrokes
+///
+/// Punctuation that VIQR reads as a modifier (e.g. the dot in "học.") is
+/// escaped with a backslash.
+pub fn to_viqr(text: &str) -> String {
+    to_keystrokes(text, Family::Viqr)
+}
+
+/// Convert Vietnamese text to the canonical keystrokes of an input method family
+pub fn to_keystrokes(text: &str, family: Family) -> String {
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut word: Vec<ParsedChar> = Vec::new();
+    // VIQR: modifier chars right after a word must be escaped
+    let mut after_word = false;
+
+    let mut iter = text.chars().peekable();
+    while let Some(c) = iter.next() {
+        if let Some(p) = chars::parse_char(c) {
+            word.push(p);
+            continue;
+        }
+        if !word.is_empty() {
+            write_word(&mut out, &word, family);
+            word.clear();
+            after_word = true;
+        }
+        if family == Family::Viqr {
+            if is_viqr_modifier(c) && after_word {
+                out.push('\\');
+            } else if c == '\\'
+                && iter
+                    .peek()
+                    .is_some_and(|&n| n == '\\' || is_viqr_modifier(n))
+            {
+                // "\\" types one literal backslash
+                out.push('\\');
+            } else {
+                after_word = false;
+            }
+        }
+        out.push(c);
+    }
+    if !word.is_empty() {
+        write_word(&mut out, &word, family);
+    }
+    out
+}
+
+/// VIQR tone/mark characters
+fn is_viqr_modifier(c: char) -> bool {
+    matches!(c, '\'' | '`' | '?' | '~' | '.' | '^' | '+' | '(')
+}
+
+/// Write one word (run of Vietnamese letters) as keystrokes
+fn write_word(out: &mut String, word: &[ParsedChar], family: Family) {
+    let marked = word.iter().filter(|p| p.mark != mark::NONE).count();
+    // More than one mark is not a Vietnamese syllable: type each in place
+    let mark_in_place = family == Family::Viqr || marked > 1;
+    // Modifier keys are uppercase only in all-caps words (Đây → Ddaay)
+    let all_caps = word.iter().all(|p| p.caps);
+    let mut word_mark = mark::NONE;
+
+    for (i, p) in word.iter().enumerate() {
+        let Some(letter) = key_to_char(p.key, p.caps) else {
+            continue;
+        };
+        out.push(letter);
+        // Telex "oo" types ô: a third o reverts it to a plain "oo" (xoong)
+        if family == Family::Telex && is_plain_double(p, word.get(i + 1)) {
+            out.push(letter);
+        }
+        if p.stroke {
+            out.push(match family {
+                Family::Vni => '9',
+                _ => case(letter, all_caps),
+            });
+        }
+        if p.tone != tone::NONE {
+            out.push(tone_key(family, p, all_caps));
+        }
+        if p.mark != mark::NONE {
+            if mark_in_place {
+                out.push(mark_key(family, p.mark, all_caps));
+            } else {
+                word_mark = p.mark;
+            }
+        }
+    }
+    if word_mark != mark::NONE {
+        out.push(mark_key(family, word_mark, all_caps));
+    }
+}
+
+/// Whether `p` and `next` are the same unmodified letter that Telex doubles
+/// into a tone or stroke (aa, ee, oo, dd)
+fn is_plain_double(p: &ParsedChar, next: Option<&ParsedChar>) -> bool {
+    let plain = |c: &ParsedChar| c.tone == tone::NONE && c.mark == mark::NONE && !c.stroke;
+    matches!(p.key, keys::A | keys::E | keys::O | keys::D)
+        && plain(p)
+        && next.is_some_and(|n| n.key == p.key && n.tone == tone::NONE && !n.stroke)
+}
+
+/// Letter in the requested case
+fn case(c: char, upper: bool) -> char {
+    if upper {
+        c.to_ascii_uppercase()
+    } else {
+        c.to_ascii_lowercase()
+    }
+}
+
+/// Key that adds a tone (circumflex, horn, breve) to a parsed vowel
+fn tone_key(family: Family, p: &ParsedChar, upper: bool) -> char {
+    let breve = p.key == keys::A && p.tone == tone::HORN;
+    match (family, p.tone) {
+        (Family::Telex, tone::CIRCUMFLEX) => key_to_char(p.key, upper).unwrap_or_default(),
+        (Family::Telex, _) => case('w', upper),
+        (Family::Vni, tone::CIRCUMFLEX) => '6',
+        (Family::Vni, _) if breve => '8',
+        (Family::Vni, _) => '7',
+        (Family::Viqr, tone::CIRCUMFLEX) => '^',
+        (Family::Viqr, _) if breve => '(',
+        (Family::Viqr, _) => '+',
+    }
+}
+
+/// Key that adds a mark (sắc, huyền, hỏi, ngã, nặng)
+fn mark_key(family: Family, m: u8, upper: bool) -> char {
+    let idx = (m - 1) as usize;
+    match family {
+        Family::Telex => case(['s', 'f', 'r', 'x', 'j'][idx], upper),
+        Family::Vni => ['1', '2', '3', '4', '5'][idx],
+        Family::Viqr => ['\'', '`', '?', '~', '.'][idx],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::{convert, ConvertOptions};
+    use quickcheck::{Arbitrary, Gen, QuickCheck, TestResult};
+
+    const TELEX: &[(&str, &str)] = &[
+        ("Tiếng Việt", "Tieengs Vieetj"),
+        ("xin chào, tôi tên là Nam.", "xin chaof, tooi teen laf Nam."),
+        ("Đây là đường!", "Ddaay laf dduwowngf!"),
+        ("ĐƯỜNG", "DDUWOWNGF"),
+        ("Ăn uống", "Awn uoongs"),
+        ("ngoằn ngoèo", "ngoawnf ngoeof"),
+        ("khuỷu tay", "khuyur tay"),
+        ("xoong nồi, boong tàu", "xooong nooif, booong tauf"),
+        ("XOONG", "XOOONG"),
+        ("", ""),
+        ("123 abc", "123 abc"),
+    ];
+
+    const VNI: &[(&str, &str)] = &[
+        ("Tiếng Việt", "Tie6ng1 Vie6t5"),
+        ("Đây là đường!", "D9a6y la2 d9u7o7ng2!"),
+        ("Ăn uống", "A8n uo6ng1"),
+        ("hoà bình", "hoa2 binh2"),
+    ];
+
+    const VIQR: &[(&str, &str)] = &[
+        ("Tiếng Việt", "Tie^'ng Vie^.t"),
+        ("đi học.", "ddi ho.c\\."),
+        ("Ăn uống?", "A(n uo^'ng\\?"),
+        ("what?", "what\\?"),
+        ("a\\.b", "a\\\\\\.b"),
+    ];
+
+    fn run(family: Family, cases: &[(&str, &str)]) {
+        for (input, expected) in cases {
+            assert_eq!(to_keystrokes(input, family), *expected, "'{}'", input);
+        }
+    }
+
+    #[test]
+    fn test_telex() {
+        run(Family::Telex, TELEX);
+        assert_eq!(to_telex("Tiếng Việt"), "Tieengs Vieetj");
+    }
+
+    #[test]
+    fn test_vni() {
+        run(Family::Vni, VNI);
+        assert_eq!(to_vni("Tiếng Việt"), "Tie6ng1 Vie6t5");
+    }
+
+    #[test]
+    fn test_viqr() {
+        run(Family::Viqr, VIQR);
+    }
+
+    #[test]
+    fn test_non_vietnamese_passthrough() {
+        assert_eq!(to_telex("日本 và 中国"), "日本 vaf 中国");
+        assert_eq!(to_vni("😀chào😀"), "😀chao2😀");
+        // Not a syllable: marks stay on their vowels
+        assert_eq!(to_telex("áé"), "ases");
+    }
+
+    // ===== Round-trip properties =====
+
+    /// Telex spellings of syllable parts, typed through the engine to get
+    /// real Vietnamese text
+    const INITIALS: &[&str] = &[
+        "", "b", "c", "ch", "d", "dd", "g", "gi", "h", "kh", "l", "m", "n", "ng", "nh", "ph", "qu",
+        "r", "s", "t", "th", "tr", "v", "x",
+    ];
+    const FRONT_INITIALS: &[&str] = &[
+        "", "b", "ch", "dd", "gh", "h", "k", "kh", "l", "m", "n", "ngh", "nh", "ph", "t", "th",
+        "tr", "v", "x",
+    ];
+    const RHYMES: &[&str] = &[
+        "a", "ai", "ao", "au", "ay", "an", "ang", "anh", "am", "ac", "at", "aw", "awn", "awm",
+        "awng", "awc", "awt", "aa", "aau", "aay", "aan", "aam", "aang", "aat", "o", "oi", "on",
+        "om", "ong", "oc", "ot", "oa", "oai", "oan", "oang", "oanh", "oat", "oawn", "oo", "ooi",
+        "oon", "oong", "oom", "ooc", "ow", "owi", "own", "owm", "owt", "u", "ua", "ui", "un",
+        "ung", "um", "ut", "uc", "uy", "uyeen", "uyeet", "uoon", "uoong", "uooi", "uw", "uwa",
+        "uwi", "uwu", "uwng", "uwc", "uwowi", "uwown", "uwowng", "uwowc", "uwowu", "ooong",
+    ];
+    const FRONT_RHYMES: &[&str] = &[
+        "i", "in", "inh", "im", "it", "ich", "iu", "ieen", "ieeng", "ieet", "ieeu", "e", "en",
+        "em", "eo", "et", "ee", "een", "eenh", "eem", "eeu", "eet", "eech",
+    ];
+    const MARKS: &[&str] = &["", "s", "f", "r", "x", "j"];
+    const SEPARATORS: &[&str] = &[" ", " ", " ", ", ", ". ", "\n", " - ", "? "];
+
+    /// A sentence of engine-produced Vietnamese syllables
+    #[derive(Debug, Clone)]
+    struct Sentence(String);
+
+    impl Arbitrary for Sentence {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let n = 1 + usize::arbitrary(g) % 8;
+            let mut typed = String::new();
+            for i in 0..n {
+                let (initials, rhymes) = if bool::arbitrary(g) {
+                    (INITIALS, RHYMES)
+                } else {
+                    (FRONT_INITIALS, FRONT_RHYMES)
+                };
+                let rhyme = *g.choose(rhymes).unwrap();
+                let mut initial = *g.choose(initials).unwrap();
+                // "gi" + "i..." and "qu" + "u..." are not separate parts
+                if (initial == "gi" && rhyme.starts_with('i'))
+                    || (initial == "qu" && rhyme.starts_with('u'))
+                {
+                    initial = "";
+                }
+                let mut syllable = format!("{}{}", initial, rhyme);
+                let stop = ['c', 't', 'p', 'h'].iter().any(|&f| rhyme.ends_with(f))
+                    && !rhyme.ends_with("nh");
+                // Marked double-o is not typeable (see test_double_o_mark)
+                let mark = if rhyme.contains("ooo") {
+                    ""
+                } else if stop {
+                    *g.choose(&["s", "j"]).unwrap()
+                } else {
+                    *g.choose(MARKS).unwrap()
+                };
+                syllable.push_str(mark);
+                match u8::arbitrary(g) % 4 {
+                    0 => syllable = syllable.to_uppercase(),
+                    1 => syllable[..1].make_ascii_uppercase(),
+                    _ => {}
+                }
+                if i > 0 {
+                    typed.push_str(g.choose(SEPARATORS).unwrap());
+                }
+                typed.push_str(&syllable);
+            }
+            Sentence(convert(&typed, 0, &ConvertOptions::default()))
+        }
+    }
+
+    fn round_trips(s: Sentence, method: u8, family: Family) -> TestResult {
+        let keystrokes = to_keystrokes(&s.0, family);
+        let back = convert(&keystrokes, method, &ConvertOptions::default());
+        if back == s.0 {
+            TestResult::passed()
+        } else {
+            TestResult::error(format!("{:?} → {:?} → {:?}", s.0, keystrokes, back))
+        }
+    }
+
+    fn check(prop: fn(Sentence) -> TestResult) {
+        QuickCheck::new().tests(2000).quickcheck(prop);
+    }
+
+    #[test]
+    fn test_round_trip_telex() {
+        check(|s| round_trips(s, 0, Family::Telex));
+    }
+
+    #[test]
+    fn test_round_trip_vni() {
+        check(|s| round_trips(s, 1, Family::Vni));
+    }
+
+    #[test]
+    fn test_round_trip_viqr() {
+        check(|s| round_trips(s, 2, Family::Viqr));
+    }
+
+    #[test]
+    fn test_double_o_mark() {
+        // Documented limitation: the mark is lost on the way back
+        for (method, family) in [(0, Family::Telex), (1, Family::Vni), (2, Family::Viqr)] {
+            for text in ["soóc", "goòng"] {
+                let back = convert(&to_keystrokes(text, family), method, &Default::default());
+                assert_ne!(back, text, "{:?}", family);
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_traditional_tone() {
+        let options = ConvertOptions {
+            modern_tone: false,
+            ..Default::default()
+        };
+        for text in ["hòa thúy", "khỏe mạnh", "Thủy họa"] {
+            let back = convert(&to_telex(text), 0, &options);
+            assert_eq!(back, text);
+        }
+    }
+}
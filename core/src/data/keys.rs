@@ -1,4 +1,7 @@
 //! macOS virtual keycodes
+//!
+//! `from_char`/`to_char` bridge keycodes and the characters they type on a
+//! US layout, for frontends that only see characters (`Engine::on_char`).
 
 // Letters
 pub const A: u16 = 0;
@@ -65,6 +68,49 @@ pub const MINUS: u16 = 27;
 pub const EQUAL: u16 = 24;
 pub const BACKQUOTE: u16 = 50;
 
+/// Keys typing a character on a US layout
+const PRINTABLE: [u16; 47] = [
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, N0, N1, N2, N3,
+    N4, N5, N6, N7, N8, N9, DOT, COMMA, SLASH, SEMICOLON, QUOTE, LBRACKET, RBRACKET, BACKSLASH,
+    MINUS, EQUAL, BACKQUOTE,
+];
+
+/// Key and Shift state typing `c` on a US layout ('A' → (A, true),
+/// '?' → (SLASH, true))
+///
+/// Control chars map to their keys: '\n' and '\r' → RETURN, '\u{8}' and
+/// '\u{7f}' → DELETE, '\u{1b}' → ESC. None for chars outside the layout
+/// ('é', '€').
+pub fn from_char(c: char) -> Option<(u16, bool)> {
+    let key = match c {
+        ' ' => SPACE,
+        '\t' => TAB,
+        '\n' | '\r' => RETURN,
+        '\u{8}' | '\u{7f}' => DELETE,
+        '\u{1b}' => ESC,
+        _ => {
+            return PRINTABLE.iter().find_map(|&key| {
+                [false, true]
+                    .into_iter()
+                    .find(|&shift| to_char(key, shift) == Some(c))
+                    .map(|shift| (key, shift))
+            })
+        }
+    };
+    Some((key, false))
+}
+
+/// Character typed by `key` on a US layout, None for keys typing nothing
+/// (DELETE, ESC, arrows)
+pub fn to_char(key: u16, shift: bool) -> Option<char> {
+    match key {
+        SPACE => Some(' '),
+        TAB => Some('\t'),
+        RETURN | ENTER => Some('\n'),
+        _ => crate::utils::key_to_char_ext(key, shift, shift),
+    }
+}
+
 /// Check if key breaks word (space, punctuation, arrows, etc.)
 /// When shift=true, also treat number keys as break (they produce !@#$%^&*())
 pub fn is_break(key: u16) -> bool {
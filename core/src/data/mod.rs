@@ -1,7 +1,7 @@
 //! Vietnamese Language Data Modules
 //!
 //! This module contains all linguistic data for Vietnamese input:
-//! - `keys`: Virtual keycode definitions (platform-specific)
+//! - `keys`: Virtual keycode definitions (platform-specific) and the char bridge
 //! - `chars`: Unicode character conversion (includes tone/mark constants)
 //! - `vowel`: Vietnamese vowel phonology system
 //! - `telex_doubles`: English words with Telex double patterns for auto-restore
@@ -0,0 +1,183 @@
+//! Engine Configuration
+//!
+//! All user-facing engine options in one value, so frontends can apply
+//! settings atomically (`Engine::apply_config`) and read them back
+//! (`Engine::config`) instead of calling each setter.
+
+use crate::charset::{Charset, Form};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Engine options
+///
+/// `Default` matches `Engine::new()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct EngineConfig {
+    /// Input method id: 0=Telex, 1=VNI, 2=VIQR, 3+=registered
+    pub method: u8,
+    /// Vietnamese transforms on (shortcuts work either way)
+    pub enabled: bool,
+    /// Modern tone placement (hoà) instead of traditional (hòa)
+    pub modern_tone: bool,
+    /// Allow diacritics anywhere without spelling validation
+    pub free_tone: bool,
+    /// Restore English words that were accidentally transformed
+    pub english_auto_restore: bool,
+    /// Capitalize the first letter after . ! ? and Enter
+    pub auto_capitalize: bool,
+    /// Disable "w" → "ư" at word start (Telex)
+    pub skip_w_shortcut: bool,
+    /// "]" → "ư", "[" → "ơ" (Telex)
+    pub bracket_shortcut: bool,
+    /// ESC restores the raw keystrokes of the current word
+    pub esc_restore: bool,
+    /// Restore words that are well formed but not Vietnamese ("nghẻ")
+    pub spell_check: bool,
+    /// Output encoding of `Result.chars`
+    pub charset: Charset,
+    /// Unicode composition of `Result.chars`
+    pub output_form: Form,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            method: 0,
+            enabled: true,
+            modern_tone: true,
+            free_tone: false,
+            english_auto_restore: false,
+            auto_capitalize: false,
+            skip_w_shortcut: false,
+            bracket_shortcut: false,
+            esc_restore: false,
+            spell_check: false,
+            charset: Charset::Unicode,
+            output_form: Form::Precomposed,
+        }
+    }
+}
+
+/// FFI layout of `EngineConfig` (one byte per field, no padding)
+///
+/// Unknown `charset`/`output_form` ids fall back to the defaults.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct FfiEngineConfig {
+    pub method: u8,
+    pub enabled: bool,
+    pub modern_tone: bool,
+    pub free_tone: bool,
+    pub english_auto_restore: bool,
+    pub auto_capitalize: bool,
+    pub skip_w_shortcut: bool,
+    pub bracket_shortcut: bool,
+    pub esc_restore: bool,
+    pub charset: u8,
+    pub output_form: u8,
+    pub spell_check: bool,
+}
+
+impl From<FfiEngineConfig> for EngineConfig {
+    fn from(c: FfiEngineConfig) -> Self {
+        Self {
+            method: c.method,
+            enabled: c.enabled,
+            modern_tone: c.modern_tone,
+            free_tone: c.free_tone,
+            english_auto_restore: c.english_auto_restore,
+            auto_capitalize: c.auto_capitalize,
+            skip_w_shortcut: c.skip_w_shortcut,
+            bracket_shortcut: c.bracket_shortcut,
+            esc_restore: c.esc_restore,
+            spell_check: c.spell_check,
+            charset: Charset::from_u8(c.charset).unwrap_or_default(),
+            output_form: Form::from_u8(c.output_form).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<EngineConfig> for FfiEngineConfig {
+    fn from(c: EngineConfig) -> Self {
+        Self {
+            method: c.method,
+            enabled: c.enabled,
+            modern_tone: c.modern_tone,
+            free_tone: c.free_tone,
+            english_auto_restore: c.english_auto_restore,
+            auto_capitalize: c.auto_capitalize,
+            skip_w_shortcut: c.skip_w_shortcut,
+            bracket_shortcut: c.bracket_shortcut,
+            esc_restore: c.esc_restore,
+            charset: c.charset as u8,
+            output_form: c.output_form as u8,
+            spell_check: c.spell_check,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Engine;
+
+    #[test]
+    fn test_default_matches_new_engine() {
+        assert_eq!(Engine::new().config(), EngineConfig::default());
+    }
+
+    #[test]
+    fn test_apply_and_read_back() {
+        let config = EngineConfig {
+            method: 1,
+            modern_tone: false,
+            free_tone: true,
+            auto_capitalize: true,
+            bracket_shortcut: true,
+            spell_check: true,
+            charset: Charset::VniWindows,
+            ..Default::default()
+        };
+        let mut e = Engine::new();
+        e.apply_config(&config);
+        assert_eq!(e.config(), config);
+    }
+
+    #[test]
+    fn test_ffi_round_trip() {
+        let config = EngineConfig {
+            method: 2,
+            enabled: false,
+            output_form: Form::Decomposed,
+            ..Default::default()
+        };
+        let ffi = FfiEngineConfig::from(config);
+        assert_eq!(std::mem::size_of::<FfiEngineConfig>(), 12);
+        assert_eq!(EngineConfig::from(ffi), config);
+
+        let bad = FfiEngineConfig { charset: 99, ..ffi };
+        assert_eq!(EngineConfig::from(bad).charset, Charset::Unicode);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json() {
+        let config = EngineConfig {
+            method: 1,
+            charset: Charset::Tcvn3,
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains("\"charset\":\"tcvn3\""), "{}", json);
+        let back: EngineConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, config);
+
+        // Missing fields take defaults
+        let partial: EngineConfig = serde_json::from_str(r#"{"method":1}"#).unwrap();
+        assert_eq!(partial.method, 1);
+        assert!(partial.modern_tone);
+    }
+}
@@ -0,0 +1,137 @@
+//! Key Events and Output
+//!
+//! Rust-side input and result types for `Engine::on_event`: a `KeyEvent`
+//! carries the key with its modifiers, auto-repeat and release state, and
+//! the engine answers with an `Output` holding the whole edit as a string.
+//! The C-shaped `Result` of `on_key_ext` is built from the same `Output`.
+//!
+//! `Engine::compose` reports the same edits as composition events for IME
+//! frameworks (IBus, Fcitx, TSF): the word being typed is an underlined
+//! preedit string and finished text is committed, with no synthetic
+//! backspaces.
+
+/// Modifier: Shift
+pub const MOD_SHIFT: u8 = 0x01;
+/// Modifier: Ctrl
+pub const MOD_CTRL: u8 = 0x02;
+/// Modifier: Alt/Option (also AltGr)
+pub const MOD_ALT: u8 = 0x04;
+/// Modifier: Cmd/Super/Windows key
+pub const MOD_META: u8 = 0x08;
+/// Modifier: Caps Lock is on
+pub const MOD_CAPS_LOCK: u8 = 0x10;
+
+/// One key press, auto-repeat or release
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyEvent {
+    /// macOS virtual keycode (`data::keys`)
+    pub key: u16,
+    /// `MOD_*` bits held or locked
+    pub modifiers: u8,
+    /// Auto-repeat of a held key
+    pub repeat: bool,
+    /// Key release
+    pub key_up: bool,
+    /// Event time in milliseconds (any monotonic clock), for hosts that
+    /// log or replay events; the engine does not read it
+    pub timestamp: u64,
+}
+
+impl KeyEvent {
+    /// Key press without modifiers
+    pub fn press(key: u16) -> Self {
+        Self {
+            key,
+            ..Self::default()
+        }
+    }
+
+    /// Key release
+    pub fn release(key: u16) -> Self {
+        Self {
+            key,
+            key_up: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_modifiers(mut self, modifiers: u8) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    /// Mark as an auto-repeat of a held key
+    pub fn repeated(mut self) -> Self {
+        self.repeat = true;
+        self
+    }
+
+    pub fn at(mut self, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Check if all `MOD_*` bits of `modifier` are set
+    pub fn has(&self, modifier: u8) -> bool {
+        self.modifiers & modifier == modifier
+    }
+
+    /// Ctrl, Alt or Cmd held: a shortcut for the app, not text
+    pub fn is_command(&self) -> bool {
+        self.modifiers & (MOD_CTRL | MOD_ALT | MOD_META) != 0
+    }
+
+    /// Letters are uppercase with Shift or Caps Lock, not both
+    pub fn is_uppercase(&self) -> bool {
+        self.has(MOD_SHIFT) != self.has(MOD_CAPS_LOCK)
+    }
+}
+
+/// What the frontend does for a key event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Output {
+    /// Let the key through unchanged
+    PassThrough,
+    /// Delete `delete` chars before the caret, then type `insert`
+    Replace {
+        delete: usize,
+        insert: String,
+        /// Swallow the key. When false (punctuation ending a word) the key
+        /// is typed after `insert`.
+        consume_key: bool,
+        /// Left-arrow moves after `insert` to place the caret (shortcut
+        /// `{cursor}` placeholder)
+        cursor_left: usize,
+    },
+}
+
+impl Output {
+    pub fn is_pass_through(&self) -> bool {
+        matches!(self, Self::PassThrough)
+    }
+}
+
+/// Edit reported by `Engine::compose`, applied in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompositionEvent {
+    /// Delete chars of committed text before the caret (edits reaching
+    /// past the preedit, like Backspace restoring the previous word)
+    DeleteSurrounding(usize),
+    /// Insert text at the caret for good
+    Commit(String),
+    /// Replace the preedit; `cursor` is its caret offset in chars (empty
+    /// text hides it)
+    Preedit { text: String, cursor: usize },
+}
+
+/// Result of a key in composition mode
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Composed {
+    pub events: Vec<CompositionEvent>,
+    /// Let the key through after the events (Enter, arrows, Ctrl combos,
+    /// Backspace with no preedit)
+    pub pass_through: bool,
+    /// Left-arrow moves after the commit to place the caret (shortcut
+    /// `{cursor}` placeholder)
+    pub cursor_left: usize,
+}
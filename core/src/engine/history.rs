@@ -0,0 +1,271 @@
+//! Edit History (IME undo/redo)
+//!
+//! Mirrors the text typed since the caret last moved and, for every key the
+//! IME rewrote (tone, mark, stroke, shortcut, auto-restore), records the span
+//! the IME wrote next to what the key alone would have typed. Undo puts the
+//! typed span back, redo the written one; text typed after the span is
+//! re-sent both times, since the frontend can only backspace from the caret.
+
+/// Mirrored text limit; older text is dropped along with edits on it
+const SCREEN_LIMIT: usize = 4096;
+
+/// Max edits kept for undo
+const DEPTH: usize = 64;
+
+/// What a key does to the text without the IME
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum KeyEffect {
+    /// Types a char
+    Insert(char),
+    /// Deletes the char before the caret
+    Delete,
+    /// Leaves the text alone (ESC)
+    Keep,
+    /// Moves the caret or edits text in ways not mirrored (Enter, Tab,
+    /// arrows, Ctrl shortcuts): history is dropped
+    Unknown,
+}
+
+/// One IME rewrite: `written` replaced `typed` at `start`
+#[derive(Clone, Debug, PartialEq)]
+struct Edit {
+    start: usize,
+    typed: Vec<char>,
+    written: Vec<char>,
+}
+
+/// Replacement for the frontend: delete `deleted` (the chars before the
+/// caret), then type `chars`
+#[derive(Debug, PartialEq)]
+pub(crate) struct Replay {
+    pub deleted: Vec<char>,
+    pub chars: Vec<char>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EditHistory {
+    screen: Vec<char>,
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+}
+
+impl EditHistory {
+    pub fn clear(&mut self) {
+        self.screen.clear();
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Track one key
+    ///
+    /// `sent` is the IME output for the key: backspace count, chars, and
+    /// whether the key's own char is typed after them (break keys not
+    /// consumed by a shortcut). None when the key passed through.
+    pub fn key(&mut self, effect: KeyEffect, sent: Option<(usize, &[char], bool)>) {
+        let n = self.screen.len();
+        let removed = match effect {
+            KeyEffect::Unknown => return self.clear(),
+            KeyEffect::Delete => 1,
+            _ => 0,
+        };
+        let backspace = sent.map_or(removed, |(bs, _, _)| bs);
+        if backspace > n || removed > n {
+            // Deleting text from before the caret last moved
+            return self.clear();
+        }
+        self.redo.clear();
+
+        // Only the tail from the first deleted char can differ
+        let from = n - backspace.max(removed);
+        let mut typed = self.screen[from..n - removed].to_vec();
+        if let KeyEffect::Insert(c) = effect {
+            typed.push(c);
+        }
+        let written = match sent {
+            None => typed.clone(),
+            Some((bs, chars, key_follows)) => {
+                let mut w = self.screen[from..n - bs].to_vec();
+                w.extend_from_slice(chars);
+                if let (true, KeyEffect::Insert(c)) = (key_follows, effect) {
+                    w.push(c);
+                }
+                w
+            }
+        };
+
+        self.screen.truncate(from);
+        self.screen.extend_from_slice(&written);
+        if typed != written {
+            let prefix = typed
+                .iter()
+                .zip(&written)
+                .take_while(|(a, b)| a == b)
+                .count();
+            let suffix = typed[prefix..]
+                .iter()
+                .rev()
+                .zip(written[prefix..].iter().rev())
+                .take_while(|(a, b)| a == b)
+                .count();
+            if self.undo.len() == DEPTH {
+                self.undo.remove(0);
+            }
+            self.undo.push(Edit {
+                start: from + prefix,
+                typed: typed[prefix..typed.len() - suffix].to_vec(),
+                written: written[prefix..written.len() - suffix].to_vec(),
+            });
+        }
+        if removed > 0 {
+            let len = self.screen.len();
+            self.undo.retain(|e| e.start + e.written.len() <= len);
+        }
+        self.trim();
+    }
+
+    /// Revert the last IME rewrite
+    ///
+    /// `fits` tells whether the frontend can delete the given chars in one
+    /// result; if not (or the text no longer matches), history is dropped.
+    pub fn undo(&mut self, fits: impl Fn(&[char]) -> bool) -> Option<Replay> {
+        let edit = self.undo.pop()?;
+        match self.swap(&edit.written, &edit.typed, edit.start, fits) {
+            Some(replay) => {
+                self.redo.push(edit);
+                Some(replay)
+            }
+            None => {
+                self.clear();
+                None
+            }
+        }
+    }
+
+    /// Re-apply the last undone rewrite
+    pub fn redo(&mut self, fits: impl Fn(&[char]) -> bool) -> Option<Replay> {
+        let edit = self.redo.pop()?;
+        match self.swap(&edit.typed, &edit.written, edit.start, fits) {
+            Some(replay) => {
+                self.undo.push(edit);
+                Some(replay)
+            }
+            None => {
+                self.clear();
+                None
+            }
+        }
+    }
+
+    /// Replace `old` at `start` with `new`, re-sending the text after it
+    fn swap(
+        &mut self,
+        old: &[char],
+        new: &[char],
+        start: usize,
+        fits: impl Fn(&[char]) -> bool,
+    ) -> Option<Replay> {
+        if self.screen.get(start..start + old.len()) != Some(old) || !fits(&self.screen[start..]) {
+            return None;
+        }
+        let deleted = self.screen.split_off(start);
+        self.screen.extend_from_slice(new);
+        self.screen.extend_from_slice(&deleted[old.len()..]);
+        Some(Replay {
+            chars: self.screen[start..].to_vec(),
+            deleted,
+        })
+    }
+
+    /// Drop the oldest text past `SCREEN_LIMIT`
+    fn trim(&mut self) {
+        let excess = self.screen.len().saturating_sub(SCREEN_LIMIT);
+        if excess == 0 {
+            return;
+        }
+        self.screen.drain(..excess);
+        self.undo.retain(|e| e.start >= excess);
+        for e in &mut self.undo {
+            e.start -= excess;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(h: &mut EditHistory, s: &str) {
+        for c in s.chars() {
+            h.key(KeyEffect::Insert(c), None);
+        }
+    }
+
+    fn screen(h: &EditHistory) -> String {
+        h.screen.iter().collect()
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut h = EditHistory::default();
+        typed(&mut h, "vie");
+        // Second 'e' rewrites "e" as "ê"
+        h.key(KeyEffect::Insert('e'), Some((1, &['ê'], false)));
+        typed(&mut h, "t ");
+        assert_eq!(screen(&h), "viêt ");
+
+        let r = h.undo(|_| true).unwrap();
+        assert_eq!(r.deleted, ['ê', 't', ' ']);
+        assert_eq!(r.chars.iter().collect::<String>(), "eet ");
+        assert_eq!(screen(&h), "vieet ");
+        assert!(h.undo(|_| true).is_none());
+
+        let r = h.redo(|_| true).unwrap();
+        assert_eq!(r.chars.iter().collect::<String>(), "êt ");
+        assert_eq!(screen(&h), "viêt ");
+        assert!(h.can_undo() && !h.can_redo());
+    }
+
+    #[test]
+    fn test_break_key_and_delete() {
+        let mut h = EditHistory::default();
+        typed(&mut h, "vn");
+        // Comma shortcut keeps the comma typed after the replacement
+        h.key(KeyEffect::Insert(','), Some((2, &['V', 'N'], true)));
+        assert_eq!(screen(&h), "VN,");
+        assert_eq!(h.undo[0].typed, ['v', 'n']);
+
+        // Deleting into the rewritten span drops it
+        h.key(KeyEffect::Delete, None);
+        h.key(KeyEffect::Delete, None);
+        assert!(!h.can_undo());
+        assert_eq!(screen(&h), "V");
+
+        // Caret moved: deleting before the mirrored text resets
+        h.key(KeyEffect::Unknown, None);
+        h.key(KeyEffect::Delete, None);
+        assert_eq!(screen(&h), "");
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut h = EditHistory::default();
+        typed(&mut h, "a");
+        h.key(KeyEffect::Insert('a'), Some((1, &['â'], false)));
+        typed(&mut h, &"x".repeat(10));
+        assert!(h.undo(|d| d.len() <= 5).is_none());
+        assert!(!h.can_undo());
+
+        h.key(KeyEffect::Insert('a'), Some((0, &['â'], false)));
+        typed(&mut h, &"x".repeat(SCREEN_LIMIT));
+        assert_eq!(h.screen.len(), SCREEN_LIMIT);
+        assert!(!h.can_undo());
+    }
+}
@@ -11,20 +11,37 @@
 //! 4. **Longest-Match-First**: For diacritic placement
 
 pub mod buffer;
+pub mod config;
+pub mod event;
+mod history;
+pub mod predict;
 pub mod shortcut;
+pub mod shortcut_file;
+pub mod spell;
 pub mod syllable;
 pub mod transform;
+pub mod user_dict;
 pub mod validation;
 
+use crate::charset::{self, Charset, Form};
 use crate::data::{
     chars::{self, mark, tone},
     constants, english_dict, keys, telex_doubles,
     vowel::{Phonology, Vowel},
 };
-use crate::input::{self, ToneType};
+use crate::input::{self, Family, ToneType};
 use crate::utils;
 use buffer::{Buffer, Char, MAX};
-use shortcut::{InputMethod, ShortcutTable};
+pub use config::EngineConfig;
+pub use event::{
+    Composed, CompositionEvent, KeyEvent, Output, MOD_ALT, MOD_CAPS_LOCK, MOD_CTRL, MOD_META,
+    MOD_SHIFT,
+};
+use history::{EditHistory, KeyEffect, Replay};
+use predict::NgramModel;
+use shortcut::{InputMethod, ShortcutMatch, ShortcutTable};
+use spell::Spelling;
+use user_dict::{UserDictionary, UserList};
 use validation::{is_foreign_word_pattern, is_valid, is_valid_for_transform, is_valid_with_tones};
 
 /// Engine action result
@@ -34,6 +51,8 @@ pub enum Action {
     None = 0,
     Send = 1,
     Restore = 2,
+    /// Undo/redo of an IME rewrite: apply like Send
+    Undo = 3,
 }
 
 /// Result for FFI
@@ -46,11 +65,53 @@ pub struct Result {
     /// Flags byte:
     /// - bit 0 (0x01): key_consumed - if set, the trigger key should NOT be passed through
     ///   Used for shortcuts where the trigger key is part of the replacement
+    /// - bit 1 (0x02): more - output continues, fetch it with `Engine::next_chunk`
     pub flags: u8,
+    /// Left-arrow moves to send after the output to place the caret
+    /// (shortcut `{cursor}` placeholder). Set on the last chunk only.
+    pub cursor_left: u16,
+}
+
+/// Edit from `Engine::handle_key`: the Unicode result and the whole text it
+/// inserts, which can be longer than `Result::chars`
+struct KeyEdit {
+    result: Result,
+    chars: Vec<char>,
+}
+
+impl KeyEdit {
+    /// Shortcut replacement, kept whole however long it is
+    fn shortcut(backspace: u8, output: Vec<char>, consumed: bool, cursor_left: usize) -> Self {
+        let mut result = if consumed {
+            Result::send_consumed(backspace, &output)
+        } else {
+            Result::send(backspace, &output)
+        };
+        result.cursor_left = cursor_left.min(u16::MAX as usize) as u16;
+        Self {
+            result,
+            chars: output,
+        }
+    }
+}
+
+impl From<Result> for KeyEdit {
+    fn from(result: Result) -> Self {
+        let chars = result.chars[..result.count as usize]
+            .iter()
+            .filter_map(|&c| char::from_u32(c))
+            .collect();
+        Self { result, chars }
+    }
 }
 
 /// Flag: key was consumed by shortcut, don't pass through
 pub const FLAG_KEY_CONSUMED: u8 = 0x01;
+/// Flag: output is longer than one Result, more chunks follow
+pub const FLAG_MORE: u8 = 0x02;
+
+/// Max chars in one Result (`count` is a u8)
+pub const CHUNK_LEN: usize = u8::MAX as usize;
 
 impl Result {
     pub fn none() -> Self {
@@ -60,6 +121,7 @@ impl Result {
             backspace: 0,
             count: 0,
             flags: 0,
+            cursor_left: 0,
         }
     }
 
@@ -68,10 +130,11 @@ impl Result {
             chars: [0; MAX],
             action: Action::Send as u8,
             backspace,
-            count: chars.len().min(MAX) as u8,
+            count: chars.len().min(CHUNK_LEN) as u8,
             flags: 0,
+            cursor_left: 0,
         };
-        for (i, &c) in chars.iter().take(MAX).enumerate() {
+        for (i, &c) in chars.iter().take(CHUNK_LEN).enumerate() {
             result.chars[i] = c as u32;
         }
         result
@@ -88,6 +151,19 @@ impl Result {
     pub fn key_consumed(&self) -> bool {
         self.flags & FLAG_KEY_CONSUMED != 0
     }
+
+    /// Check if more output chunks follow (see `Engine::next_chunk`)
+    pub fn has_more(&self) -> bool {
+        self.flags & FLAG_MORE != 0
+    }
+}
+
+/// Check if the app types the key after a Send result (punctuation, Enter)
+///
+/// Letters, Backspace and Space are part of the output; a shortcut can
+/// consume the others (`FLAG_KEY_CONSUMED`).
+fn types_after_output(key: u16, shift: bool) -> bool {
+    key != keys::SPACE && keys::is_break_ext(key, shift)
 }
 
 /// Transform type for revert tracking
@@ -115,6 +191,7 @@ const HISTORY_CAPACITY: usize = 10;
 /// Used for backspace-after-space feature: when user presses backspace
 /// immediately after committing a word with space, restore the previous
 /// buffer state to allow editing.
+#[derive(Clone)]
 struct WordHistory {
     data: [Buffer; HISTORY_CAPACITY],
     head: usize,
@@ -155,6 +232,70 @@ impl WordHistory {
     }
 }
 
+/// Composition state of one text field (see `Engine::snapshot`)
+///
+/// Holds the current word, its raw keystrokes, word history and pending
+/// flags; options and shortcuts stay with the engine. `Default` is the state
+/// of a field nothing was typed into yet.
+#[derive(Clone)]
+pub struct EngineState {
+    buf: Buffer,
+    last_transform: Option<Transform>,
+    raw_input: Vec<(u16, bool, bool)>,
+    has_non_letter_prefix: bool,
+    word_history: WordHistory,
+    spaces_after_commit: u8,
+    pending_breve_pos: Option<usize>,
+    pending_u_horn_pos: Option<usize>,
+    stroke_reverted: bool,
+    had_mark_revert: bool,
+    pending_mark_revert_pop: bool,
+    had_any_transform: bool,
+    had_vowel_triggered_circumflex: bool,
+    had_circumflex_revert: bool,
+    had_telex_transform: bool,
+    telex_double_raw: Option<String>,
+    telex_double_raw_len: usize,
+    shortcut_prefix: String,
+    restored_pending_clear: bool,
+    pending_capitalize: bool,
+    auto_capitalize_used: bool,
+    saw_sentence_ending: bool,
+    pending_escape: bool,
+    history: EditHistory,
+    prev_words: Vec<String>,
+    preedit: String,
+}
+
+impl Default for EngineState {
+    fn default() -> Self {
+        Engine::new().snapshot()
+    }
+}
+
+/// Buffer of a word on screen, parsing Vietnamese chars back to their
+/// components (other chars are skipped)
+fn word_buffer(word: &str) -> Buffer {
+    let mut buf = Buffer::new();
+    for parsed in word.chars().filter_map(chars::parse_char) {
+        let mut ch = Char::new(parsed.key, parsed.caps);
+        ch.tone = parsed.tone;
+        ch.mark = parsed.mark;
+        ch.stroke = parsed.stroke;
+        buf.push(ch);
+    }
+    buf
+}
+
+/// Whether deleting `chars` fits in one Result's backspace count
+fn fits_backspace(chars: &[char], charset: Charset, form: Form) -> bool {
+    let width: usize = chars
+        .iter()
+        .map(|&c| charset::output_width(c, charset, form))
+        .sum();
+    width <= u8::MAX as usize
+}
+
 /// Check if key is sentence-ending punctuation (. ! ?) but NOT Enter
 /// Issue #185: Only set pending_capitalize after punctuation + space
 #[inline]
@@ -271,6 +412,9 @@ pub struct Engine {
     /// When true, automatically restores English words that were transformed
     /// e.g., "tẽt" → "text", "ễpct" → "expect"
     english_auto_restore: bool,
+    /// Restore committed words that are well formed but not attested
+    /// Vietnamese syllables, e.g. "nghẻ" → "ngher"
+    spell_check: bool,
     /// Word history for backspace-after-space feature
     word_history: WordHistory,
     /// Number of spaces typed after committing a word (for backspace tracking)
@@ -339,6 +483,30 @@ pub struct Engine {
     /// Only set pending_capitalize when space/Enter follows
     /// Issue #185: don't capitalize immediately after punctuation (e.g., google.com)
     saw_sentence_ending: bool,
+    /// VIQR escape key (\) was just typed: the next modifier symbol is literal
+    pending_escape: bool,
+    /// Encoding of `Result.chars` (legacy fonts need TCVN3/VNI/VISCII bytes)
+    charset: Charset,
+    /// Unicode composition of `Result.chars` (precomposed or decomposed)
+    output_form: Form,
+    /// Output chars that did not fit in the last Result, not yet fetched
+    /// with `next_chunk`
+    pending_output: Vec<char>,
+    /// Caret move for the last pending chunk
+    pending_cursor_left: u16,
+    /// Context tags for scoped shortcuts (see `set_context`)
+    context: Vec<String>,
+    /// Text typed since the caret moved and the IME rewrites in it,
+    /// for `undo_last_transform`
+    history: EditHistory,
+    /// Word prediction model, learning from committed words (see `predict`)
+    predictor: Option<NgramModel>,
+    /// Last words committed in the current phrase, oldest first
+    prev_words: Vec<String>,
+    /// User word lists overriding English auto-restore
+    user_dict: UserDictionary,
+    /// Word shown as preedit in composition mode (see `compose`)
+    preedit: String,
 }
 
 impl Default for Engine {
@@ -363,6 +531,7 @@ impl Engine {
             free_tone_enabled: false,
             modern_tone: true,           // Default: modern style (hoà, thuý)
             english_auto_restore: false, // Default: OFF (experimental feature)
+            spell_check: false,
             word_history: WordHistory::new(),
             spaces_after_commit: 0,
             pending_breve_pos: None,
@@ -382,6 +551,17 @@ impl Engine {
             pending_capitalize: false,
             auto_capitalize_used: false,
             saw_sentence_ending: false,
+            pending_escape: false,
+            charset: Charset::Unicode,
+            output_form: Form::Precomposed,
+            pending_output: Vec::new(),
+            pending_cursor_left: 0,
+            context: Vec::new(),
+            history: EditHistory::default(),
+            predictor: None,
+            prev_words: Vec::new(),
+            user_dict: UserDictionary::new(),
+            preedit: String::new(),
         }
     }
 
@@ -389,12 +569,26 @@ impl Engine {
         self.method = method;
     }
 
+    /// Select input method by name (built-in or registered via `input::register`)
+    ///
+    /// Returns false (method unchanged) if no method has this name.
+    pub fn set_method_by_name(&mut self, name: &str) -> bool {
+        match input::find(name) {
+            Some(id) => {
+                self.method = id;
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn set_enabled(&mut self, enabled: bool) {
         self.enabled = enabled;
         if !enabled {
             self.buf.clear();
             self.word_history.clear();
             self.spaces_after_commit = 0;
+            self.prev_words.clear();
         }
     }
 
@@ -428,6 +622,28 @@ impl Engine {
         self.english_auto_restore = enabled;
     }
 
+    /// Set whether to restore words that are not attested Vietnamese syllables
+    pub fn set_spell_check(&mut self, enabled: bool) {
+        self.spell_check = enabled;
+    }
+
+    /// Set output encoding for `Result.chars` and backspace counts
+    ///
+    /// Legacy charsets emit one Latin-1 char per byte, so VNI "á" is sent as
+    /// "aù" and deleting it takes 2 backspaces.
+    pub fn set_charset(&mut self, charset: Charset) {
+        self.charset = charset;
+    }
+
+    /// Set Unicode composition for `Result.chars` and backspace counts
+    ///
+    /// Decomposed letters take several codepoints (ệ = e + U+0323 + U+0302),
+    /// so replacing them takes one backspace per codepoint. Ignored when a
+    /// legacy charset is set.
+    pub fn set_output_form(&mut self, form: Form) {
+        self.output_form = form;
+    }
+
     /// Set whether to enable auto-capitalize after sentence-ending punctuation
     pub fn set_auto_capitalize(&mut self, enabled: bool) {
         self.auto_capitalize = enabled;
@@ -437,6 +653,40 @@ impl Engine {
         }
     }
 
+    /// Apply all options at once (same effect as calling each setter)
+    pub fn apply_config(&mut self, config: &EngineConfig) {
+        self.set_method(config.method);
+        self.set_enabled(config.enabled);
+        self.set_modern_tone(config.modern_tone);
+        self.set_free_tone(config.free_tone);
+        self.set_english_auto_restore(config.english_auto_restore);
+        self.set_auto_capitalize(config.auto_capitalize);
+        self.set_skip_w_shortcut(config.skip_w_shortcut);
+        self.set_bracket_shortcut(config.bracket_shortcut);
+        self.set_esc_restore(config.esc_restore);
+        self.set_spell_check(config.spell_check);
+        self.set_charset(config.charset);
+        self.set_output_form(config.output_form);
+    }
+
+    /// Current options
+    pub fn config(&self) -> EngineConfig {
+        EngineConfig {
+            method: self.method,
+            enabled: self.enabled,
+            modern_tone: self.modern_tone,
+            free_tone: self.free_tone_enabled,
+            english_auto_restore: self.english_auto_restore,
+            auto_capitalize: self.auto_capitalize,
+            skip_w_shortcut: self.skip_w_shortcut,
+            bracket_shortcut: self.bracket_shortcut,
+            esc_restore: self.esc_restore_enabled,
+            spell_check: self.spell_check,
+            charset: self.charset,
+            output_form: self.output_form,
+        }
+    }
+
     pub fn shortcuts(&self) -> &ShortcutTable {
         &self.shortcuts
     }
@@ -445,6 +695,66 @@ impl Engine {
         &mut self.shortcuts
     }
 
+    pub fn user_dict(&self) -> &UserDictionary {
+        &self.user_dict
+    }
+
+    /// Personal word lists checked before auto-restore on commit
+    pub fn user_dict_mut(&mut self) -> &mut UserDictionary {
+        &mut self.user_dict
+    }
+
+    /// Set the word prediction model (None turns prediction off)
+    ///
+    /// The model learns every word committed with space; `predictor` gives
+    /// it back for saving.
+    pub fn set_predictor(&mut self, model: Option<NgramModel>) {
+        self.predictor = model;
+        self.prev_words.clear();
+    }
+
+    pub fn predictor(&self) -> Option<&NgramModel> {
+        self.predictor.as_ref()
+    }
+
+    /// Top `k` completions of the word being typed, or next-word
+    /// suggestions between words; empty without a model
+    pub fn predict(&self, k: usize) -> Vec<String> {
+        match &self.predictor {
+            Some(model) => model.complete(&self.prev_words, &self.buf.to_full_string(), k),
+            None => Vec::new(),
+        }
+    }
+
+    /// Feed the word in the buffer to the prediction model
+    ///
+    /// Words with digits or symbols are not learned and end the phrase.
+    fn learn_word(&mut self) {
+        let Some(model) = &mut self.predictor else {
+            return;
+        };
+        let word = self.buf.to_full_string();
+        if word.is_empty() || !word.chars().all(char::is_alphabetic) {
+            self.prev_words.clear();
+            return;
+        }
+        model.learn(&self.prev_words, &word);
+        if self.prev_words.len() == 2 {
+            self.prev_words.remove(0);
+        }
+        self.prev_words.push(word.to_lowercase());
+    }
+
+    /// Set the context tags for scoped shortcuts (e.g. foreground process name)
+    pub fn set_context(&mut self, tags: &[&str]) {
+        self.context = tags.iter().map(|t| t.to_string()).collect();
+    }
+
+    /// Current context tags
+    pub fn context(&self) -> &[String] {
+        &self.context
+    }
+
     /// Debug: get buffer length
     pub fn debug_buffer_len(&self) -> usize {
         self.buf.len()
@@ -492,14 +802,54 @@ impl Engine {
     }
 
     /// Get current input method as InputMethod enum
+    /// Registered methods are scoped by their family
     fn current_input_method(&self) -> InputMethod {
-        match self.method {
-            0 => InputMethod::Telex,
-            1 => InputMethod::Vni,
-            _ => InputMethod::All,
+        match self.family() {
+            Family::Telex => InputMethod::Telex,
+            Family::Vni => InputMethod::Vni,
+            Family::Viqr => InputMethod::Viqr,
         }
     }
 
+    /// Match a shortcut for the current input method and context
+    fn match_shortcut(
+        &self,
+        buffer: &str,
+        key_char: Option<char>,
+        is_word_boundary: bool,
+    ) -> Option<ShortcutMatch> {
+        let context: Vec<&str> = self.context.iter().map(String::as_str).collect();
+        self.shortcuts.try_match_in_context(
+            buffer,
+            key_char,
+            is_word_boundary,
+            self.current_input_method(),
+            &context,
+        )
+    }
+
+    /// Keystroke family of the current input method
+    #[inline]
+    fn family(&self) -> Family {
+        input::get(self.method).family()
+    }
+
+    /// Check if current method uses Telex-family heuristics
+    #[inline]
+    fn is_telex(&self) -> bool {
+        self.family() == Family::Telex
+    }
+
+    /// Check if key+shift is a punctuation modifier of a VIQR-family method
+    /// (e.g., ' for sắc, ^ for circumflex)
+    fn is_symbol_modifier(&self, key: u16, shift: bool) -> bool {
+        if self.family() != Family::Viqr || !keys::is_break_ext(key, shift) {
+            return false;
+        }
+        let m = input::get(self.method);
+        m.mark_ext(key, shift).is_some() || m.tone_ext(key, shift).is_some()
+    }
+
     /// Handle key event - main entry point
     ///
     /// # Arguments
@@ -537,14 +887,406 @@ impl Engine {
     /// * `caps` - true if Caps Lock is active (for uppercase letters)
     /// * `ctrl` - true if Cmd/Ctrl/Alt is pressed (bypasses IME)
     /// * `shift` - true if Shift key is pressed (for symbols like @, #, $)
+    ///
+    /// Output longer than `CHUNK_LEN` chars is split: the result has `FLAG_MORE`
+    /// set and the rest comes from `next_chunk`.
     pub fn on_key_ext(&mut self, key: u16, caps: bool, ctrl: bool, shift: bool) -> Result {
+        let output = self.key_output(key, caps, ctrl, shift);
+        self.output_result(output, types_after_output(key, shift))
+    }
+
+    /// Handle a key event - entry point for Rust frontends
+    ///
+    /// Same processing as `on_key_ext`, with the whole edit in one `Output`
+    /// (no chunks). Releases pass through. Auto-repeats of keys that type a
+    /// char end the word and pass through, so holding a key types it instead
+    /// of cycling its transform ("ooo" held is not ô → oo → ô); like any typed
+    /// char they keep undo and backspace-after-space. Other repeats
+    /// (Backspace, Space, arrows) are handled as presses.
+    /// Ctrl, Alt or Cmd bypasses the IME.
+    pub fn on_event(&mut self, event: KeyEvent) -> Output {
+        if event.key_up {
+            return Output::PassThrough;
+        }
+        let command = event.is_command();
+        let (caps, shift) = if keys::is_letter(event.key) {
+            (event.is_uppercase(), false)
+        } else {
+            (false, event.has(MOD_SHIFT))
+        };
+        if event.repeat && !command {
+            if let Some(ch) = utils::key_to_char_ext(event.key, caps, shift) {
+                self.pass_char(ch);
+                return Output::PassThrough;
+            }
+        }
+        self.key_output(event.key, caps, command, shift)
+    }
+
+    /// Handle a key event in composition mode - entry point for IME frameworks
+    ///
+    /// Same processing as `on_event`, reported as preedit and commit events
+    /// instead of backspace edits: the word being typed stays in the preedit
+    /// until a space, punctuation or another key ends it. Use either this or
+    /// `on_event`/`on_key_ext` for a field, not both. `clear` drops the
+    /// preedit; commit it first with `end_composition`.
+    pub fn compose(&mut self, event: KeyEvent) -> Composed {
+        if event.key_up {
+            return Composed {
+                pass_through: true,
+                ..Composed::default()
+            };
+        }
+        let key = event.key;
+        let command = event.is_command();
+        let shift = if keys::is_letter(key) {
+            event.is_uppercase()
+        } else {
+            event.has(MOD_SHIFT)
+        };
+        // Char the key types when the engine leaves it to the app
+        let typed = keys::to_char(key, shift).filter(|c| !command && !c.is_control());
+
+        // Apply the edit to the preedit, deleting committed text past it
+        let before = std::mem::take(&mut self.preedit);
+        let mut text: Vec<char> = before.chars().collect();
+        let mut composed = Composed::default();
+        let mut deleted = 0;
+        match self.on_event(event) {
+            Output::PassThrough => match typed {
+                Some(c) => text.push(c),
+                None if key == keys::DELETE && !command && !text.is_empty() => {
+                    text.pop();
+                }
+                None => composed.pass_through = true,
+            },
+            Output::Replace {
+                delete,
+                insert,
+                consume_key,
+                cursor_left,
+            } => {
+                deleted = delete.saturating_sub(text.len());
+                text.truncate(text.len().saturating_sub(delete));
+                text.extend(insert.chars());
+                if !consume_key {
+                    match typed {
+                        Some(c) => text.push(c),
+                        None => composed.pass_through = key != keys::ESC,
+                    }
+                }
+                composed.cursor_left = cursor_left;
+            }
+        }
+
+        // The word still being composed stays in the preedit, the rest is done
+        let word: Vec<char> = self.buf.to_full_string().chars().collect();
+        let word = charset::to_output_chars(&word, self.charset, self.output_form);
+        let split = if text.ends_with(&word) {
+            text.len() - word.len()
+        } else {
+            text.len()
+        };
+        let preedit: String = text[split..].iter().collect();
+        if deleted > 0 {
+            composed
+                .events
+                .push(CompositionEvent::DeleteSurrounding(deleted));
+        }
+        if split > 0 {
+            composed
+                .events
+                .push(CompositionEvent::Commit(text[..split].iter().collect()));
+        }
+        if preedit != before {
+            composed.events.push(CompositionEvent::Preedit {
+                cursor: preedit.chars().count(),
+                text: preedit.clone(),
+            });
+        }
+        self.preedit = preedit;
+        composed
+    }
+
+    /// End composition (focus out, caret moved by the user)
+    ///
+    /// Returns the preedit to commit, None when empty. The word is done:
+    /// the next key starts a new one.
+    pub fn end_composition(&mut self) -> Option<String> {
+        let text = std::mem::take(&mut self.preedit);
+        self.clear();
+        (!text.is_empty()).then_some(text)
+    }
+
+    /// Process a key into the full edit, in output charset chars
+    fn key_output(&mut self, key: u16, caps: bool, ctrl: bool, shift: bool) -> Output {
+        self.pending_output.clear();
+        let unicode = self.charset == Charset::Unicode && self.output_form == Form::Precomposed;
+        // On-screen word before the key, to count output chars being deleted
+        let before: Vec<char> = if unicode {
+            Vec::new()
+        } else {
+            self.buf.to_full_string().chars().collect()
+        };
+        let KeyEdit { result, chars } = self.handle_key(key, caps, ctrl, shift);
+        self.track_key(key, caps, ctrl, shift, &result, &chars);
+        if result.action != Action::Send as u8 {
+            return Output::PassThrough;
+        }
+
+        let consume_key = result.key_consumed() || !types_after_output(key, shift);
+        if unicode {
+            return Output::Replace {
+                delete: result.backspace as usize,
+                insert: chars.into_iter().collect(),
+                consume_key,
+                cursor_left: result.cursor_left as usize,
+            };
+        }
+        let delete = self.output_backspace(result.backspace, &before) as usize;
+        let after = chars.len().saturating_sub(result.cursor_left as usize);
+        let cursor_left = chars[after..]
+            .iter()
+            .map(|&c| charset::output_width(c, self.charset, self.output_form))
+            .sum();
+        Output::Replace {
+            delete,
+            insert: charset::to_output_chars(&chars, self.charset, self.output_form)
+                .into_iter()
+                .collect(),
+            consume_key,
+            cursor_left,
+        }
+    }
+
+    /// FFI result for a key's output; text longer than `CHUNK_LEN` chars
+    /// continues in `next_chunk`
+    ///
+    /// `key_follows`: the key is typed after the output unless consumed
+    /// (`types_after_output`).
+    fn output_result(&mut self, output: Output, key_follows: bool) -> Result {
+        match output {
+            Output::PassThrough => Result::none(),
+            Output::Replace {
+                delete,
+                insert,
+                consume_key,
+                cursor_left,
+            } => {
+                let flags = if key_follows && consume_key {
+                    FLAG_KEY_CONSUMED
+                } else {
+                    0
+                };
+                self.send_chunked(
+                    delete.min(u8::MAX as usize) as u8,
+                    insert.chars().collect(),
+                    flags,
+                    cursor_left.min(u16::MAX as usize) as u16,
+                )
+            }
+        }
+    }
+
+    /// Handle a typed character - entry point for frontends without keycodes
+    ///
+    /// Use this when the OS gives the character the layout produced (Dvorak,
+    /// AZERTY, X11 keysyms, evdev + xkb) instead of a US key position: the
+    /// character is mapped with `keys::from_char`, so 'w' is the Telex horn
+    /// key wherever it sits on the keyboard. Pass '\u{8}' for Backspace and
+    /// '\u{1b}' for Esc.
+    ///
+    /// # Arguments
+    /// * `ch` - character typed (case included, so Caps Lock needs no flag)
+    /// * `modifiers` - `MOD_*` bits held; Ctrl or Meta bypasses the IME.
+    ///   Shift is read from `ch` and Alt is ignored, as Option/AltGr layers
+    ///   type real characters.
+    ///
+    /// Characters outside the US layout ('é', '€') end the word and pass
+    /// through; undo and backspace-after-space keep working across them.
+    pub fn on_char(&mut self, ch: char, modifiers: u8) -> Result {
+        let bypass = modifiers & (MOD_CTRL | MOD_META) != 0;
+        let Some((key, shift)) = keys::from_char(ch) else {
+            return self.pass_char(ch);
+        };
+        if keys::is_letter(key) {
+            self.on_key_ext(key, shift, bypass, false)
+        } else {
+            self.on_key_ext(key, false, bypass, shift)
+        }
+    }
+
+    /// Pass through a char without composing it, ending the word
+    ///
+    /// For chars outside the layout and held keys. The word and the char
+    /// become plain text: backspace-after-space counts them like spaces, and
+    /// the char counts as typed for undo. The caret has not moved, so no
+    /// history is dropped.
+    fn pass_char(&mut self, ch: char) -> Result {
+        self.pending_output.clear();
+        if self.spaces_after_commit > 0 {
+            let typed = (self.buf.len() + 1).min(u8::MAX as usize) as u8;
+            self.spaces_after_commit = self.spaces_after_commit.saturating_add(typed);
+        }
+        self.clear();
+        self.history.key(KeyEffect::Insert(ch), None);
+        Result::none()
+    }
+
+    /// Next chunk of an output that did not fit in one Result
+    ///
+    /// Call while the previous result has `FLAG_MORE` set. Chunks only insert
+    /// text (no backspaces). Returns `Action::None` when nothing is pending.
+    pub fn next_chunk(&mut self) -> Result {
+        if self.pending_output.is_empty() {
+            return Result::none();
+        }
+        let len = self.pending_output.len().min(CHUNK_LEN);
+        let chunk: Vec<char> = self.pending_output.drain(..len).collect();
+        let mut result = Result::send(0, &chunk);
+        if self.pending_output.is_empty() {
+            result.cursor_left = std::mem::take(&mut self.pending_cursor_left);
+        } else {
+            result.flags = FLAG_MORE;
+        }
+        result
+    }
+
+    /// Revert the last IME rewrite (tone, mark, stroke, shortcut, restore)
+    ///
+    /// Works after the word was committed too, until the caret moves
+    /// (`clear_all`, Enter, arrows, Ctrl shortcuts). Text typed after the
+    /// rewrite is deleted and typed again. The result has `Action::Undo` and
+    /// is applied like Send; `Action::None` when there is nothing to undo.
+    pub fn undo_last_transform(&mut self) -> Result {
+        let (charset, form) = (self.charset, self.output_form);
+        let replay = self
+            .history
+            .undo(|deleted| fits_backspace(deleted, charset, form));
+        self.send_replay(replay)
+    }
+
+    /// Re-apply the rewrite reverted by the last `undo_last_transform`
+    ///
+    /// Any key typed after the undo drops what could be redone.
+    pub fn redo_last_transform(&mut self) -> Result {
+        let (charset, form) = (self.charset, self.output_form);
+        let replay = self
+            .history
+            .redo(|deleted| fits_backspace(deleted, charset, form));
+        self.send_replay(replay)
+    }
+
+    /// Whether `undo_last_transform` has a rewrite to revert
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    /// Whether `redo_last_transform` has a rewrite to re-apply
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// Mirror the key in the edit history (see `undo_last_transform`)
+    fn track_key(
+        &mut self,
+        key: u16,
+        caps: bool,
+        ctrl: bool,
+        shift: bool,
+        result: &Result,
+        chars: &[char],
+    ) {
+        let effect = match key {
+            _ if ctrl => KeyEffect::Unknown,
+            keys::DELETE => KeyEffect::Delete,
+            keys::ESC => KeyEffect::Keep,
+            keys::SPACE => KeyEffect::Insert(' '),
+            _ => utils::key_to_char_ext(key, caps, shift)
+                .map_or(KeyEffect::Unknown, KeyEffect::Insert),
+        };
+        if result.action != Action::Send as u8 {
+            self.history.key(effect, None);
+            return;
+        }
+        // Break keys are typed after the output unless a shortcut consumed them
+        let key_follows = types_after_output(key, shift) && !result.key_consumed();
+        self.history.key(
+            effect,
+            Some((result.backspace as usize, chars, key_follows)),
+        );
+    }
+
+    /// Result for an undo/redo replay, encoded for the output charset
+    fn send_replay(&mut self, replay: Option<Replay>) -> Result {
+        let replay = match replay {
+            Some(r) => r,
+            None => return Result::none(),
+        };
+        // The composed word no longer matches the text
+        self.clear();
+        self.word_history.clear();
+        self.spaces_after_commit = 0;
+        self.prev_words.clear();
+        self.pending_output.clear();
+        let backspace: usize = replay
+            .deleted
+            .iter()
+            .map(|&c| charset::output_width(c, self.charset, self.output_form))
+            .sum();
+        let chars = charset::to_output_chars(&replay.chars, self.charset, self.output_form);
+        let mut result = self.send_chunked(backspace as u8, chars, 0, 0);
+        result.action = Action::Undo as u8;
+        result
+    }
+
+    /// Backspace count in output chars for the output charset and form
+    ///
+    /// Backspaces beyond the current word (shortcut triggers, restored words)
+    /// are counted as one char each.
+    fn output_backspace(&self, backspace: u8, before: &[char]) -> u8 {
+        let backspace = backspace as usize;
+        let deleted = backspace.min(before.len());
+        let width: usize = before[before.len() - deleted..]
+            .iter()
+            .map(|&c| charset::output_width(c, self.charset, self.output_form))
+            .sum::<usize>()
+            + (backspace - deleted);
+        width.min(u8::MAX as usize) as u8
+    }
+
+    /// Build the result for `chars`, keeping what does not fit for `next_chunk`
+    fn send_chunked(
+        &mut self,
+        backspace: u8,
+        mut chars: Vec<char>,
+        flags: u8,
+        cursor_left: u16,
+    ) -> Result {
+        if chars.len() > CHUNK_LEN {
+            self.pending_output = chars.split_off(CHUNK_LEN);
+        }
+        let mut result = Result::send(backspace, &chars);
+        result.flags = flags;
+        if self.pending_output.is_empty() {
+            result.cursor_left = cursor_left;
+        } else {
+            result.flags |= FLAG_MORE;
+            self.pending_cursor_left = cursor_left;
+        }
+        result
+    }
+
+    /// Process a key with Unicode output (see `on_key_ext`)
+    fn handle_key(&mut self, key: u16, caps: bool, ctrl: bool, shift: bool) -> KeyEdit {
         // Issue #129: Process shortcuts even when IME is disabled
         // Only bypass completely for Ctrl/Cmd modifier keys
         if ctrl {
             self.clear();
             self.word_history.clear();
             self.spaces_after_commit = 0;
-            return Result::none();
+            self.prev_words.clear();
+            return Result::none().into();
         }
 
         // When IME is disabled, process shortcuts but skip Vietnamese transforms
@@ -555,16 +1297,15 @@ impl Engine {
             self.raw_input.clear();
             self.word_history.clear();
             self.spaces_after_commit = 0;
+            self.prev_words.clear();
 
             // Word boundary keys (Space, Enter): check for word shortcuts
             if key == keys::SPACE || key == keys::RETURN || key == keys::ENTER {
                 if !self.shortcut_prefix.is_empty() {
-                    let input_method = self.current_input_method();
-                    if let Some(m) = self.shortcuts.try_match_for_method(
+                    if let Some(m) = self.match_shortcut(
                         &self.shortcut_prefix,
                         None,
                         true, // is_word_boundary = true for word shortcuts
-                        input_method,
                     ) {
                         let output: Vec<char> = m.output.chars().collect();
                         let backspace_count = m.backspace_count as u8;
@@ -573,14 +1314,29 @@ impl Engine {
                         if key == keys::SPACE {
                             let mut output_with_space = output;
                             output_with_space.push(' ');
-                            return Result::send(backspace_count, &output_with_space);
+                            // The space goes after the caret position too
+                            let cursor_left = match m.cursor_left {
+                                0 => 0,
+                                n => n + 1,
+                            };
+                            return KeyEdit::shortcut(
+                                backspace_count,
+                                output_with_space,
+                                false,
+                                cursor_left,
+                            );
                         } else {
-                            return Result::send(backspace_count, &output);
+                            return KeyEdit::shortcut(
+                                backspace_count,
+                                output,
+                                false,
+                                m.cursor_left,
+                            );
                         }
                     }
                 }
                 self.shortcut_prefix.clear();
-                return Result::none();
+                return Result::none().into();
             }
 
             // Break keys (punctuation): check for immediate shortcuts like "->"
@@ -588,34 +1344,42 @@ impl Engine {
                 if let Some(ch) = break_key_to_char(key, shift) {
                     self.shortcut_prefix.push(ch);
 
-                    let input_method = self.current_input_method();
-                    if let Some(m) = self.shortcuts.try_match_for_method(
-                        &self.shortcut_prefix,
-                        None,
-                        false,
-                        input_method,
-                    ) {
+                    if let Some(m) = self.match_shortcut(&self.shortcut_prefix, None, false) {
                         let output: Vec<char> = m.output.chars().collect();
                         let backspace_count = (m.backspace_count as u8).saturating_sub(1);
                         self.shortcut_prefix.clear();
-                        return Result::send_consumed(backspace_count, &output);
+                        return KeyEdit::shortcut(backspace_count, output, true, m.cursor_left);
                     }
-                    return Result::none();
+                    return Result::none().into();
                 }
                 // Break key without char mapping (Tab, arrows, etc.) - clear and pass through
                 self.shortcut_prefix.clear();
-                return Result::none();
+                return Result::none().into();
             }
 
             // Letter and number keys: accumulate for word shortcuts (e.g., "btw", "f1", "a1")
             if let Some(ch) = utils::key_to_char(key, caps) {
                 self.shortcut_prefix.push(ch);
-                return Result::none();
+                return Result::none().into();
             }
 
             // Unknown keys: clear shortcut prefix and pass through
             self.shortcut_prefix.clear();
-            return Result::none();
+            return Result::none().into();
+        }
+
+        // VIQR escape: \ followed by a modifier symbol (or another \) types it
+        // literally, replacing the backslash already on screen
+        let is_escape = input::get(self.method).escape(key, shift);
+        if std::mem::take(&mut self.pending_escape)
+            && (is_escape || self.is_symbol_modifier(key, shift))
+        {
+            if let Some(ch) = break_key_to_char(key, shift) {
+                return Result::send_consumed(1, &[ch]).into();
+            }
+        }
+        if is_escape {
+            self.pending_escape = true;
         }
 
         // Check for word boundary shortcuts ONLY on SPACE
@@ -653,8 +1417,9 @@ impl Engine {
 
             // First check for shortcut
             let shortcut_result = self.try_word_boundary_shortcut();
-            if shortcut_result.action != 0 {
+            if shortcut_result.result.action != 0 {
                 self.clear();
+                self.prev_words.clear();
                 return shortcut_result;
             }
 
@@ -675,6 +1440,7 @@ impl Engine {
 
             // Push buffer to history before clearing (for backspace-after-space feature)
             if !self.buf.is_empty() {
+                self.learn_word();
                 self.word_history.push(self.buf.clone());
                 self.spaces_after_commit = 1; // First space after word
             } else if self.spaces_after_commit > 0 {
@@ -691,7 +1457,7 @@ impl Engine {
             }
 
             self.clear();
-            return restore_result;
+            return restore_result.into();
         }
 
         // ESC key: restore to raw ASCII (undo all Vietnamese transforms)
@@ -705,14 +1471,23 @@ impl Engine {
             self.clear();
             self.word_history.clear();
             self.spaces_after_commit = 0;
-            return result;
+            self.prev_words.clear();
+            return result.into();
         }
 
         // Issue #159: In Telex mode, `]` → ư and `[` → ơ
         // caps affects revert: ]] → ], uppercase (Shift/CapsLock) → }
-        if self.method == 0 && (key == keys::RBRACKET || key == keys::LBRACKET) {
+        if self.is_telex() && (key == keys::RBRACKET || key == keys::LBRACKET) {
             if let Some(result) = self.try_bracket_as_vowel(key, caps) {
-                return result;
+                return result.into();
+            }
+        }
+
+        // VIQR: punctuation modifiers (a' → á, o+ → ơ) apply to the current word;
+        // only when they can't be applied is the key treated as a word break
+        if !self.buf.is_empty() && self.is_symbol_modifier(key, shift) {
+            if let Some(result) = self.try_symbol_modifier(key, caps, shift) {
+                return result.into();
             }
         }
 
@@ -743,20 +1518,14 @@ impl Engine {
                     self.shortcut_prefix.push(ch);
 
                     // Check for immediate shortcut match
-                    let input_method = self.current_input_method();
-                    if let Some(m) = self.shortcuts.try_match_for_method(
-                        &self.shortcut_prefix,
-                        None,
-                        false,
-                        input_method,
-                    ) {
+                    if let Some(m) = self.match_shortcut(&self.shortcut_prefix, None, false) {
                         // Found a match! Send the replacement with key_consumed flag
                         // Note: backspace_count - 1 because current key hasn't been typed yet
                         // Example: "->" trigger has backspace_count=2, but only '-' is on screen
                         let output: Vec<char> = m.output.chars().collect();
                         let backspace_count = (m.backspace_count as u8).saturating_sub(1);
                         self.shortcut_prefix.clear();
-                        return Result::send_consumed(backspace_count, &output);
+                        return KeyEdit::shortcut(backspace_count, output, true, m.cursor_left);
                     }
 
                     // Issue #185: Only set saw_sentence_ending for punctuation (not Enter)
@@ -768,7 +1537,7 @@ impl Engine {
                         self.pending_capitalize = true;
                         self.saw_sentence_ending = false;
                     }
-                    return Result::none(); // Let the char pass through, keep accumulating
+                    return Result::none().into(); // Let the char pass through, keep accumulating
                 }
             }
 
@@ -798,18 +1567,23 @@ impl Engine {
             };
             if let Some(ch) = trigger_char {
                 let shortcut_result = self.try_word_boundary_shortcut_with_char(ch);
-                if shortcut_result.action != 0 {
+                if shortcut_result.result.action != 0 {
                     self.clear();
                     self.word_history.clear();
                     self.spaces_after_commit = 0;
+                    self.prev_words.clear();
                     return shortcut_result;
                 }
             }
 
             let restore_result = self.try_auto_restore_on_break();
+            if restore_result.action == 0 {
+                self.learn_word();
+            }
             self.clear();
             self.word_history.clear();
             self.spaces_after_commit = 0;
+            self.prev_words.clear();
 
             // Issue #130: After clearing buffer, store break char as potential shortcut prefix
             // This allows shortcuts like "->" to work after "abc->" (where "-" clears "abc")
@@ -818,7 +1592,7 @@ impl Engine {
                 self.shortcut_prefix.push(ch);
             }
 
-            return restore_result;
+            return restore_result.into();
         }
 
         if key == keys::DELETE {
@@ -829,6 +1603,8 @@ impl Engine {
                 if self.spaces_after_commit == 0 {
                     // All spaces deleted - restore the word buffer
                     if let Some(restored_buf) = self.word_history.pop() {
+                        // The word is being edited again, not followed yet
+                        self.prev_words.pop();
                         // Restore raw_input from buffer (for ESC restore to work)
                         self.restore_raw_input_from_buffer(&restored_buf);
                         self.buf = restored_buf;
@@ -838,7 +1614,7 @@ impl Engine {
                     }
                 }
                 // Delete one space
-                return Result::send(1, &[]);
+                return Result::send(1, &[]).into();
             }
             // DON'T reset spaces_after_commit here!
             // User might delete all new input and want to restore previous word.
@@ -869,7 +1645,7 @@ impl Engine {
                     self.auto_capitalize_used = false;
                 }
             }
-            return Result::none();
+            return Result::none().into();
         }
 
         // After DELETE restore, determine if user wants to:
@@ -921,11 +1697,11 @@ impl Engine {
         // we need to send the uppercase character since the original key was lowercase
         if was_auto_capitalized && result.action == Action::None as u8 && self.buf.len() == 1 {
             if let Some(ch) = crate::utils::key_to_char(key, true) {
-                return Result::send(0, &[ch]);
+                return Result::send(0, &[ch]).into();
             }
         }
 
-        result
+        result.into()
     }
 
     /// Main processing pipeline - pattern-based
@@ -990,8 +1766,8 @@ impl Engine {
         //   e.g., "dod" → "đo" + 'o' → "đô" (user typed d-o-d-o fast, intended "ddoo")
         // - Stroke keys ('d') - handled separately in try_stroke for proper revert behavior
         //   e.g., "dadd" → "dad" (d reverts stroke and adds itself, not "dadd")
-        let is_mark_key = m.mark(key).is_some();
-        let is_tone_key = m.tone(key).is_some();
+        let is_mark_key = m.mark_ext(key, shift).is_some();
+        let is_tone_key = m.tone_ext(key, shift).is_some();
         let is_stroke_key = m.stroke(key);
 
         if keys::is_letter(key)
@@ -1031,7 +1807,7 @@ impl Engine {
 
         // In VNI mode, if Shift is pressed with a number key, skip all modifiers
         // User wants the symbol (@ for Shift+2, # for Shift+3, etc.), not VNI marks
-        let skip_vni_modifiers = self.method == 1 && shift && keys::is_number(key);
+        let skip_vni_modifiers = self.family() == Family::Vni && shift && keys::is_number(key);
 
         // Check modifiers by scanning buffer for patterns
 
@@ -1044,7 +1820,7 @@ impl Engine {
 
         // 2. Tone modifier (circumflex, horn, breve)
         if !skip_vni_modifiers {
-            if let Some(tone_type) = m.tone(key) {
+            if let Some(tone_type) = m.tone_ext(key, shift) {
                 let targets = m.tone_targets(key);
                 if let Some(result) = self.try_tone(key, caps, tone_type, targets) {
                     return result;
@@ -1054,7 +1830,7 @@ impl Engine {
 
         // 3. Mark modifier
         if !skip_vni_modifiers {
-            if let Some(mark_val) = m.mark(key) {
+            if let Some(mark_val) = m.mark_ext(key, shift) {
                 if let Some(result) = self.try_mark(key, caps, mark_val) {
                     return result;
                 }
@@ -1072,7 +1848,7 @@ impl Engine {
 
         // 5. In Telex: "w" as vowel "ư" when valid Vietnamese context
         // Examples: "w" → "ư", "nhw" → "như", but "kw" → "kw" (invalid)
-        if self.method == 0 && key == keys::W {
+        if self.is_telex() && key == keys::W {
             if let Some(result) = self.try_w_as_vowel(caps) {
                 return result;
             }
@@ -1084,17 +1860,17 @@ impl Engine {
 
     /// Try word boundary shortcuts (triggered by space, punctuation, etc.)
     /// The `trigger_char` is appended to the output (space for space, punctuation for punctuation)
-    fn try_word_boundary_shortcut_with_char(&mut self, trigger_char: char) -> Result {
+    fn try_word_boundary_shortcut_with_char(&mut self, trigger_char: char) -> KeyEdit {
         // Issue #107: Allow shortcuts with special char prefix (like "#fne")
         // If shortcut_prefix is set, we still try to match even with empty buffer
         if self.buf.is_empty() && self.shortcut_prefix.is_empty() {
-            return Result::none();
+            return Result::none().into();
         }
 
         // Don't trigger shortcut if word has non-letter prefix (like "149k")
         // But DO allow shortcut_prefix (like "#fne") - that's intentional
         if self.has_non_letter_prefix {
-            return Result::none();
+            return Result::none().into();
         }
 
         // Build full trigger string including shortcut_prefix if present
@@ -1104,8 +1880,6 @@ impl Engine {
             format!("{}{}", self.shortcut_prefix, self.buf.to_full_string())
         };
 
-        let input_method = self.current_input_method();
-
         // Check for word boundary shortcut match
         // For SPACE: append to output (space is "consumed" via Result::forward later)
         // For punctuation: pass None - don't append, platform layer types it normally
@@ -1115,20 +1889,17 @@ impl Engine {
         } else {
             None // Punctuation: don't append, let platform type it
         };
-        if let Some(m) =
-            self.shortcuts
-                .try_match_for_method(&full_trigger, key_char, true, input_method)
-        {
+        if let Some(m) = self.match_shortcut(&full_trigger, key_char, true) {
             let output: Vec<char> = m.output.chars().collect();
             // backspace_count = trigger.len() which already includes prefix (e.g., "#fne" = 4)
-            return Result::send(m.backspace_count as u8, &output);
+            return KeyEdit::shortcut(m.backspace_count as u8, output, false, m.cursor_left);
         }
 
-        Result::none()
+        Result::none().into()
     }
 
     /// Try word boundary shortcuts (triggered by space)
-    fn try_word_boundary_shortcut(&mut self) -> Result {
+    fn try_word_boundary_shortcut(&mut self) -> KeyEdit {
         self.try_word_boundary_shortcut_with_char(' ')
     }
 
@@ -1322,7 +2093,15 @@ impl Engine {
 
         // Find position of un-stroked 'd' to apply stroke
         // Also track if this is a short pattern stroke (revertible)
-        let (pos, is_short_pattern_stroke) = if self.method == 0 {
+        let (pos, is_short_pattern_stroke) = if self.family() == Family::Viqr {
+            // VIQR: only adjacent "dd" → "đ" ('d' is an ordinary letter otherwise)
+            let last_pos = self.buf.len().checked_sub(1)?;
+            let last_char = self.buf.get(last_pos)?;
+            if last_char.key != keys::D || last_char.stroke {
+                return None;
+            }
+            (last_pos, false)
+        } else if self.is_telex() {
             // Telex: First try adjacent 'd' (last char is un-stroked d)
             let last_pos = self.buf.len().checked_sub(1)?;
             let last_char = self.buf.get(last_pos)?;
@@ -1539,7 +2318,7 @@ impl Engine {
                 // 2. No consonants between target and end (delayed diphthong: "oio" → "ôi")
                 // This prevents transformation in words like "teacher" where consonants
                 // (c, h) appear between the two 'e's
-                let is_telex_circumflex = self.method == 0
+                let is_telex_circumflex = self.is_telex()
                     && tone_type == ToneType::Circumflex
                     && matches!(key, keys::A | keys::E | keys::O);
 
@@ -2163,7 +2942,7 @@ impl Engine {
         // Telex: Check for delayed stroke pattern (d + vowels + d)
         // When buffer is "dod" and mark key is typed, apply stroke to initial 'd'
         // This enables "dods" → "đó" while preventing "de" + "d" → "đe"
-        let had_delayed_stroke = self.method == 0
+        let had_delayed_stroke = self.is_telex()
             && self.buf.len() >= 2
             && self
                 .buf
@@ -2224,7 +3003,7 @@ impl Engine {
         // This enables "totos" → "tốt" while preventing "data" → "dât"
         // Pattern: C₁ + V + C₂ + V where V is same vowel (a, e, o)
         let mut had_delayed_circumflex = false;
-        if self.method == 0 && self.buf.len() >= 3 {
+        if self.is_telex() && self.buf.len() >= 3 {
             // Get vowel positions
             let vowel_positions: Vec<(usize, u16)> = self
                 .buf
@@ -3124,7 +3903,7 @@ impl Engine {
         // IMPORTANT: Only apply this revert for DELAYED circumflex (V+C+V pattern), not for
         // immediate circumflex (VV pattern like "deep" → "dêp"). For immediate circumflex,
         // typing another vowel should NOT revert (allows words like "deeper").
-        if self.method == 0
+        if self.is_telex()
             && self.had_vowel_triggered_circumflex
             && matches!(key, keys::A | keys::E | keys::O)
             && self.buf.len() >= 2
@@ -3173,7 +3952,7 @@ impl Engine {
         // The second vowel triggers circumflex on the first vowel (keeping existing mark)
         // IMPORTANT: Must have initial consonant to form valid Vietnamese syllable
         // "expect" (e-x-p-e) should NOT trigger because no initial consonant
-        if self.method == 0 && matches!(key, keys::A | keys::E | keys::O) && self.buf.len() >= 3 {
+        if self.is_telex() && matches!(key, keys::A | keys::E | keys::O) && self.buf.len() >= 3 {
             let last_idx = self.buf.len() - 1;
             let vowel_idx = self.buf.len() - 2;
 
@@ -3584,6 +4363,7 @@ impl Engine {
         self.telex_double_raw_len = 0;
         self.restored_pending_clear = false;
         self.shortcut_prefix.clear();
+        self.preedit.clear();
     }
 
     /// Clear everything including word history
@@ -3593,6 +4373,74 @@ impl Engine {
         self.clear();
         self.word_history.clear();
         self.spaces_after_commit = 0;
+        self.history.clear();
+        self.prev_words.clear();
+    }
+
+    /// Copy the composition state of the focused field
+    ///
+    /// Frontends keep one `EngineState` per window/field: snapshot on focus
+    /// out, `restore` on focus in, so words and backspace-after-space history
+    /// don't leak between fields.
+    pub fn snapshot(&self) -> EngineState {
+        EngineState {
+            buf: self.buf.clone(),
+            last_transform: self.last_transform,
+            raw_input: self.raw_input.clone(),
+            has_non_letter_prefix: self.has_non_letter_prefix,
+            word_history: self.word_history.clone(),
+            spaces_after_commit: self.spaces_after_commit,
+            pending_breve_pos: self.pending_breve_pos,
+            pending_u_horn_pos: self.pending_u_horn_pos,
+            stroke_reverted: self.stroke_reverted,
+            had_mark_revert: self.had_mark_revert,
+            pending_mark_revert_pop: self.pending_mark_revert_pop,
+            had_any_transform: self.had_any_transform,
+            had_vowel_triggered_circumflex: self.had_vowel_triggered_circumflex,
+            had_circumflex_revert: self.had_circumflex_revert,
+            had_telex_transform: self.had_telex_transform,
+            telex_double_raw: self.telex_double_raw.clone(),
+            telex_double_raw_len: self.telex_double_raw_len,
+            shortcut_prefix: self.shortcut_prefix.clone(),
+            restored_pending_clear: self.restored_pending_clear,
+            pending_capitalize: self.pending_capitalize,
+            auto_capitalize_used: self.auto_capitalize_used,
+            saw_sentence_ending: self.saw_sentence_ending,
+            pending_escape: self.pending_escape,
+            history: self.history.clone(),
+            prev_words: self.prev_words.clone(),
+            preedit: self.preedit.clone(),
+        }
+    }
+
+    /// Switch to a field's composition state (from `snapshot` or `Default`)
+    pub fn restore(&mut self, state: EngineState) {
+        self.buf = state.buf;
+        self.last_transform = state.last_transform;
+        self.raw_input = state.raw_input;
+        self.has_non_letter_prefix = state.has_non_letter_prefix;
+        self.word_history = state.word_history;
+        self.spaces_after_commit = state.spaces_after_commit;
+        self.pending_breve_pos = state.pending_breve_pos;
+        self.pending_u_horn_pos = state.pending_u_horn_pos;
+        self.stroke_reverted = state.stroke_reverted;
+        self.had_mark_revert = state.had_mark_revert;
+        self.pending_mark_revert_pop = state.pending_mark_revert_pop;
+        self.had_any_transform = state.had_any_transform;
+        self.had_vowel_triggered_circumflex = state.had_vowel_triggered_circumflex;
+        self.had_circumflex_revert = state.had_circumflex_revert;
+        self.had_telex_transform = state.had_telex_transform;
+        self.telex_double_raw = state.telex_double_raw;
+        self.telex_double_raw_len = state.telex_double_raw_len;
+        self.shortcut_prefix = state.shortcut_prefix;
+        self.restored_pending_clear = state.restored_pending_clear;
+        self.pending_capitalize = state.pending_capitalize;
+        self.auto_capitalize_used = state.auto_capitalize_used;
+        self.saw_sentence_ending = state.saw_sentence_ending;
+        self.pending_escape = state.pending_escape;
+        self.history = state.history;
+        self.prev_words = state.prev_words;
+        self.preedit = state.preedit;
     }
 
     /// Get the full composed buffer as a Vietnamese string with diacritics.
@@ -3623,16 +4471,62 @@ impl Engine {
     /// Parses Vietnamese characters back to buffer components.
     pub fn restore_word(&mut self, word: &str) {
         self.clear();
-        for c in word.chars() {
-            if let Some(parsed) = chars::parse_char(c) {
-                let mut ch = Char::new(parsed.key, parsed.caps);
-                ch.tone = parsed.tone;
-                ch.mark = parsed.mark;
-                ch.stroke = parsed.stroke;
-                self.buf.push(ch);
-                self.raw_input.push((parsed.key, parsed.caps, false));
+        self.history.clear();
+        let buf = word_buffer(word);
+        self.restore_raw_input_from_buffer(&buf);
+        self.buf = buf;
+    }
+
+    /// Resynchronize with the text around the caret
+    ///
+    /// Call when the caret moved without typing (mouse click, arrow keys,
+    /// focus in) with the text IBus/TSF report and the caret offset in chars.
+    /// The word ending at the caret becomes the current word, so the next
+    /// mark or tone key edits it ("Viêt| Nam" + j → "Việt Nam"). After
+    /// spaces, Backspace brings the word before them back as usual; earlier
+    /// words of the phrase feed prediction. A caret inside a word starts
+    /// fresh, as the engine only edits text before the caret.
+    pub fn set_surrounding_text(&mut self, text: &str, cursor: usize) {
+        self.clear_all();
+        let chars: Vec<char> = text.chars().collect();
+        let cursor = cursor.min(chars.len());
+        let is_letter = |c: &char| chars::parse_char(*c).is_some();
+        let inside_word = cursor > 0 && is_letter(&chars[cursor - 1]);
+        if inside_word && chars.get(cursor).is_some_and(is_letter) {
+            return;
+        }
+        let before = &chars[..cursor];
+        let word_start = before
+            .iter()
+            .rposition(|c| !is_letter(c))
+            .map_or(0, |i| i + 1);
+        let (rest, word) = before.split_at(word_start);
+
+        // Words of the phrase before the current word, oldest first
+        let phrase_start = rest
+            .iter()
+            .rposition(|c| !c.is_whitespace() && !is_letter(c))
+            .map_or(0, |i| i + 1);
+        let phrase: Vec<String> = rest[phrase_start..]
+            .split(|c| c.is_whitespace())
+            .filter(|w| !w.is_empty())
+            .map(|w| w.iter().collect())
+            .collect();
+        let spaces = rest.iter().rev().take_while(|&&c| c == ' ').count();
+        let follows_word =
+            spaces > 0 && rest.len() > spaces && is_letter(&rest[rest.len() - spaces - 1]);
+        if let Some(last) = phrase.last().filter(|_| follows_word) {
+            self.word_history.push(word_buffer(last));
+            if word.is_empty() {
+                self.spaces_after_commit = spaces.min(u8::MAX as usize) as u8;
             }
         }
+        let start = phrase.len().saturating_sub(2);
+        self.prev_words = phrase[start..].iter().map(|w| w.to_lowercase()).collect();
+
+        let buf = word_buffer(&word.iter().collect::<String>());
+        self.restore_raw_input_from_buffer(&buf);
+        self.buf = buf;
     }
 
     /// Check if buffer has transforms and is invalid Vietnamese
@@ -3641,6 +4535,32 @@ impl Engine {
     /// `is_word_complete`: true when called on space/break (word is complete)
     ///                     false when called mid-word (during typing)
     fn should_auto_restore(&self, is_word_complete: bool) -> Option<Vec<char>> {
+        // Personal dictionary: the user's lists override every check below
+        if is_word_complete && self.had_any_transform && !self.user_dict.is_empty() {
+            if self
+                .user_dict
+                .contains(UserList::Vietnamese, &self.buf.to_full_string())
+            {
+                return None;
+            }
+            if let Some(raw) = self.build_raw_chars_exact() {
+                let word: String = raw.iter().collect();
+                if self.user_dict.contains(UserList::English, &word) {
+                    return Some(raw);
+                }
+            }
+        }
+
+        // Spell check: a transformed word that is well formed but not a
+        // Vietnamese syllable ("nghẻ") is restored to what was typed
+        if self.spell_check
+            && is_word_complete
+            && self.had_any_transform
+            && spell::check_word(&self.buf.to_full_string()) == Spelling::Unattested
+        {
+            return self.build_raw_chars_exact();
+        }
+
         // Only run auto-restore if the feature is enabled
         if !self.english_auto_restore {
             return None;
@@ -4218,7 +5138,7 @@ impl Engine {
 
         // Check if it's a vowel tone key (Telex: a, e, o for circumflex; w for horn/breve)
         // These are always intentional reverts - no English words use double vowels like this
-        if self.method == 0 {
+        if self.is_telex() {
             if matches!(last_key, keys::A | keys::E | keys::O | keys::W) {
                 return true;
             }
@@ -4230,7 +5150,7 @@ impl Engine {
         }
 
         // Check if it's a mark key
-        let is_mark_key = if self.method == 0 {
+        let is_mark_key = if self.is_telex() {
             // Telex tone modifiers: s, f, r, x, j
             matches!(last_key, keys::S | keys::F | keys::R | keys::X | keys::J)
         } else {
@@ -4275,7 +5195,7 @@ impl Engine {
         // For longer words (5+ chars), check modifier type:
         // - 'x', 'j' (Telex) or VNI numbers: not common doubles in English → keep
         // - 's', 'f', 'r' (Telex): very common doubles in English (bass, staff, error) → restore
-        if self.method == 0 {
+        if self.is_telex() {
             // Telex: only keep for uncommon double letters (x, j)
             matches!(last_key, keys::X | keys::J)
         } else {
@@ -4655,7 +5575,7 @@ impl Engine {
             // Example: "tafoo" = t + a + f + o + o → restore to "tàoo"
             // - Keep the tone on first vowel (from 'f' = huyền)
             // - Keep double vowel at end (not collapsed to circumflex)
-            if chars.len() == 5 && self.method == 0 {
+            if chars.len() == 5 && self.is_telex() {
                 // Telex only
                 let c0 = chars[0].to_ascii_lowercase();
                 let c1 = chars[1].to_ascii_lowercase();
@@ -6091,6 +7011,57 @@ impl Engine {
         false
     }
 
+    /// Try to apply a VIQR punctuation modifier to the current word
+    ///
+    /// Returns None (key becomes a normal break) when the modifier doesn't apply,
+    /// or when the same modifier is repeated: VIQR has no double-key revert,
+    /// so "a''" commits "á" and types a literal '.
+    fn try_symbol_modifier(&mut self, key: u16, caps: bool, shift: bool) -> Option<Result> {
+        let m = input::get(self.method);
+        let tone_type = m.tone_ext(key, shift);
+        let mark_val = m.mark_ext(key, shift);
+
+        // ` (huyền) and ~ (ngã) share one key: only the same value is a repeat
+        match self.last_transform {
+            Some(Transform::Tone(k, v)) if k == key => {
+                if tone_type.map(|t| t.value()) == Some(v) {
+                    return None;
+                }
+                self.last_transform = None;
+            }
+            Some(Transform::Mark(k, v)) if k == key => {
+                if mark_val == Some(v) {
+                    return None;
+                }
+                self.last_transform = None;
+            }
+            _ => {}
+        }
+
+        self.raw_input.push((key, caps, shift));
+        let mut result = match tone_type {
+            Some(t) => self.try_tone(key, caps, t, m.tone_targets(key)),
+            None => None,
+        };
+        if result.is_none() {
+            if let Some(v) = mark_val {
+                result = self.try_mark(key, caps, v);
+            }
+        }
+
+        match result {
+            Some(mut r) => {
+                // The symbol itself must not reach the app
+                r.flags |= FLAG_KEY_CONSUMED;
+                Some(r)
+            }
+            None => {
+                self.raw_input.pop();
+                None
+            }
+        }
+    }
+
     /// Try to convert bracket key to vowel: ] → ư, [ → ơ (Issue #159)
     ///
     /// Returns Some(Result) if bracket was converted, None otherwise.
@@ -6254,8 +7225,11 @@ impl Engine {
 
 #[cfg(test)]
 mod tests {
-    use super::Engine;
-    use crate::utils::{telex, type_word, vni};
+    use super::shortcut::Shortcut;
+    use super::{Action, Engine, CHUNK_LEN};
+    use crate::charset::{Charset, Form};
+    use crate::input::MethodSpec;
+    use crate::utils::{custom_engine, telex, telex_custom, type_word, viqr, vni, vni_custom};
 
     const TELEX_BASIC: &[(&str, &str)] = &[
         ("as", "á"),
@@ -6308,6 +7282,48 @@ mod tests {
         ("d9", "đ"),
     ];
 
+    const VIQR_BASIC: &[(&str, &str)] = &[
+        ("a'", "á"),
+        ("a`", "à"),
+        ("a?", "ả"),
+        ("a~", "ã"),
+        ("a.", "ạ"),
+        ("a^", "â"),
+        ("a(", "ă"),
+        ("e^", "ê"),
+        ("o^", "ô"),
+        ("o+", "ơ"),
+        ("u+", "ư"),
+        ("dd", "đ"),
+        ("DD", "Đ"),
+        // Words
+        ("Vie^.t Nam", "Việt Nam"),
+        ("tie^'ng", "tiếng"),
+        ("ddu+o+`ng", "đường"),
+        ("nguo+`i", "người"),
+        ("a(n", "ăn"),
+        // Mark switch on the shared `/~ key
+        ("a`~", "ã"),
+        // 'd' is a plain letter unless doubled (no delayed stroke)
+        ("did", "did"),
+    ];
+
+    // Punctuation that can't be a modifier stays punctuation
+    const VIQR_PUNCTUATION: &[(&str, &str)] = &[
+        ("hello.", "hello."),
+        ("what?", "what?"),
+        ("a, b", "a, b"),
+        ("'a'", "'á"),
+        // Repeating a modifier commits the word and types the symbol
+        ("a''", "á'"),
+        ("ba..", "bạ."),
+        // Escape: \ makes the next modifier literal
+        ("a\\'", "a'"),
+        ("ba\\.", "ba."),
+        ("a\\\\", "a\\"),
+        ("a\\b", "a\\b"),
+    ];
+
     const TELEX_COMPOUND: &[(&str, &str)] =
         &[("duocw", "dươc"), ("nguoiw", "ngươi"), ("tuoiws", "tưới")];
 
@@ -6350,6 +7366,757 @@ mod tests {
         telex(TELEX_COMPOUND);
     }
 
+    #[test]
+    fn test_viqr_basic() {
+        viqr(VIQR_BASIC);
+    }
+
+    #[test]
+    fn test_viqr_punctuation() {
+        viqr(VIQR_PUNCTUATION);
+    }
+
+    #[test]
+    fn test_viqr_esc_restore() {
+        let mut e = Engine::new();
+        e.set_method(2);
+        e.set_esc_restore(true);
+        assert_eq!(type_word(&mut e, "vie^.t\x1b"), "vie^.t");
+    }
+
+    #[test]
+    fn test_viqr_shortcut_scope() {
+        use super::shortcut::InputMethod;
+
+        // Only unscoped and VIQR shortcuts expand while typing VIQR
+        let cases = [
+            (InputMethod::All, "Việt Nam "),
+            (InputMethod::Telex, "vn "),
+            (InputMethod::Vni, "vn "),
+            (InputMethod::Viqr, "Việt Nam "),
+        ];
+        for (method, expected) in cases {
+            let mut e = Engine::new();
+            e.set_method(2);
+            e.shortcuts_mut()
+                .add(Shortcut::new("vn", "Việt Nam").for_method(method));
+            assert_eq!(type_word(&mut e, "vn "), expected, "{:?}", method);
+        }
+    }
+
+    // Data-driven methods must behave exactly like the built-ins they describe
+    #[test]
+    fn test_custom_telex_tables() {
+        telex_custom(TELEX_BASIC);
+        telex_custom(TELEX_COMPOUND);
+        telex_custom(TELEX_NORMAL);
+        for cases in [
+            TELEX_AUTO_RESTORE_R,
+            TELEX_AUTO_RESTORE_X,
+            TELEX_AUTO_RESTORE_J,
+            TELEX_INTERLEAVED_DIPHTHONG,
+        ] {
+            for (input, expected) in cases {
+                let mut e = custom_engine(MethodSpec::telex());
+                e.set_english_auto_restore(true);
+                let result = type_word(&mut e, input);
+                assert_eq!(
+                    result, *expected,
+                    "[Custom AutoRestore] '{}' → '{}'",
+                    input, result
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_vni_tables() {
+        vni_custom(VNI_BASIC);
+    }
+
+    #[test]
+    fn test_custom_esc_restore() {
+        for (spec, cases) in [
+            (MethodSpec::telex(), TELEX_ESC_RESTORE),
+            (MethodSpec::vni(), VNI_ESC_RESTORE),
+        ] {
+            for (input, expected) in cases {
+                let mut e = custom_engine(spec.clone());
+                e.set_esc_restore(true);
+                let result = type_word(&mut e, input);
+                assert_eq!(result, *expected, "[Custom] '{}' → '{}'", input, result);
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_layout_keys() {
+        // In-house layout: Telex family with q as the stroke key
+        let mut spec = MethodSpec::telex();
+        spec.name = "inhouse".to_string();
+        spec.stroke = "q".to_string();
+        let mut e = custom_engine(spec);
+        assert_eq!(type_word(&mut e, "dqas"), "đá");
+    }
+
+    #[test]
+    fn test_legacy_charset_output() {
+        // (charset, typed, screen as Latin-1 chars of the legacy font)
+        let cases = [
+            (Charset::VniWindows, "vieejt", "vieät"),
+            (Charset::VniWindows, "dduwowngf", "ñöôøng"),
+            (Charset::VniWindows, "hoaf", "hoaø"),
+            (Charset::Tcvn3, "vieejt", "vi\u{D6}t"),
+            (Charset::Tcvn3, "DDUWOWNGF", "\u{A7}\u{A6}\u{EA}NG"),
+            (Charset::Viscii, "tieengs vieejt", "ti\u{AA}ng vi\u{AE}t"),
+        ];
+        for (charset, input, expected) in cases {
+            let mut e = Engine::new();
+            e.set_charset(charset);
+            assert_eq!(
+                type_word(&mut e, input),
+                expected,
+                "{:?} '{}'",
+                charset,
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_decomposed_output() {
+        let cases = [
+            (Form::Decomposed, "vieejt", "vie\u{323}\u{302}t"),
+            (Form::Decomposed, "dduwowngf", "đu\u{31B}o\u{31B}\u{300}ng"),
+            (Form::Decomposed, "hoaf", "hoa\u{300}"),
+            (
+                Form::Decomposed,
+                "tieengs vieetj",
+                "tie\u{302}\u{301}ng vie\u{323}\u{302}t",
+            ),
+            (Form::Windows1258, "vieejt", "viê\u{323}t"),
+            (Form::Windows1258, "nguwowif", "ngươ\u{300}i"),
+            // Mark moves from one vowel to another: old letter fully deleted
+            (Form::Decomposed, "huyeenf", "huye\u{302}\u{300}n"),
+        ];
+        for (form, input, expected) in cases {
+            let mut e = Engine::new();
+            e.set_output_form(form);
+            assert_eq!(type_word(&mut e, input), expected, "{:?} '{}'", form, input);
+        }
+    }
+
+    /// Press `key`, then fetch remaining chunks: (backspace, text, chunk sizes)
+    fn key_chunked(e: &mut Engine, key: u16, shift: bool) -> (u8, String, Vec<usize>) {
+        let mut r = e.on_key_ext(key, false, false, shift);
+        let backspace = r.backspace;
+        let (mut text, mut sizes) = (String::new(), Vec::new());
+        loop {
+            assert_eq!(r.action, Action::Send as u8);
+            sizes.push(r.count as usize);
+            text.extend(
+                r.chars[..r.count as usize]
+                    .iter()
+                    .filter_map(|&c| char::from_u32(c)),
+            );
+            if !r.has_more() {
+                break;
+            }
+            r = e.next_chunk();
+            assert_eq!(r.backspace, 0, "only the first chunk deletes");
+            assert!(!r.key_consumed(), "only the first chunk carries key flags");
+        }
+        assert_eq!(e.next_chunk().action, Action::None as u8);
+        (backspace, text, sizes)
+    }
+
+    #[test]
+    fn test_long_shortcut_chunks() {
+        use crate::data::keys;
+
+        // Output is replacement + space: sizes around one and two chunks
+        for len in [1, 253, 254, 255, 508, 509, 510, 1000] {
+            let replacement: String = "Việt Nam ".chars().cycle().take(len).collect();
+            let mut e = Engine::new();
+            e.shortcuts_mut().add(Shortcut::new("sig", &replacement));
+            for key in [keys::S, keys::I, keys::G] {
+                e.on_key(key, false, false);
+            }
+            let (backspace, text, sizes) = key_chunked(&mut e, keys::SPACE, false);
+            assert_eq!(backspace, 3, "len {}", len);
+            assert_eq!(text, format!("{} ", replacement), "len {}", len);
+            assert_eq!(sizes.len(), (len + 1).div_ceil(CHUNK_LEN), "len {}", len);
+            assert!(sizes[..sizes.len() - 1].iter().all(|&n| n == CHUNK_LEN));
+        }
+    }
+
+    #[test]
+    fn test_long_shortcut_chunks_consumed_and_encoded() {
+        use crate::data::keys;
+
+        // Immediate shortcut: key_consumed stays on the first chunk
+        let long: String = "→".repeat(600);
+        let mut e = Engine::new();
+        e.shortcuts_mut().add(Shortcut::immediate("->", &long));
+        e.on_key(keys::MINUS, false, false);
+        let r = e.on_key_ext(keys::DOT, false, false, true);
+        assert!(r.key_consumed() && r.has_more());
+        assert_eq!((r.backspace, r.count as usize), (1, CHUNK_LEN));
+
+        // A new key drops the unfetched rest
+        e.on_key(keys::A, false, false);
+        assert_eq!(e.next_chunk().action, Action::None as u8);
+
+        // Decomposed output grows past one chunk: each ệ becomes 3 chars
+        let mut e = Engine::new();
+        e.set_output_form(Form::Decomposed);
+        e.shortcuts_mut().add(Shortcut::new("vv", &"ệ".repeat(100)));
+        e.on_key(keys::V, false, false);
+        e.on_key(keys::V, false, false);
+        let (_, text, sizes) = key_chunked(&mut e, keys::SPACE, false);
+        assert_eq!(text, format!("{} ", "e\u{323}\u{302}".repeat(100)));
+        assert_eq!(sizes, vec![CHUNK_LEN, 301 - CHUNK_LEN]);
+    }
+
+    #[test]
+    fn test_template_cursor_output() {
+        use crate::data::keys;
+
+        let type_trigger = |e: &mut Engine| {
+            e.on_key(keys::B, false, false);
+            e.on_key(keys::B, false, false);
+        };
+
+        // Precomposed: one move per char after the caret, including the space
+        let mut e = Engine::new();
+        e.shortcuts_mut().add(Shortcut::new("bb", "({cursor}ệ)"));
+        type_trigger(&mut e);
+        let r = e.on_key(keys::SPACE, false, false);
+        assert_eq!((r.count, r.cursor_left), (4, 3));
+
+        // Decomposed: ệ is three output chars
+        let mut e = Engine::new();
+        e.set_output_form(Form::Decomposed);
+        e.shortcuts_mut().add(Shortcut::new("bb", "({cursor}ệ)"));
+        type_trigger(&mut e);
+        assert_eq!(e.on_key(keys::SPACE, false, false).cursor_left, 5);
+
+        // Chunked: the caret move comes with the last chunk
+        let mut e = Engine::new();
+        let long = format!("{}{{cursor}}.", "a".repeat(300));
+        e.shortcuts_mut().add(Shortcut::new("bb", &long));
+        type_trigger(&mut e);
+        let r = e.on_key(keys::SPACE, false, false);
+        assert!(r.has_more());
+        assert_eq!(r.cursor_left, 0);
+        let r = e.next_chunk();
+        assert!(!r.has_more());
+        assert_eq!(r.cursor_left, 2);
+    }
+
+    #[test]
+    fn test_context_shortcuts() {
+        let mut e = Engine::new();
+        e.shortcuts_mut()
+            .add(Shortcut::new("fn", "function").in_contexts(&["code.exe"]));
+        assert_eq!(type_word(&mut e, "fn "), "fn ");
+
+        e.set_context(&["code.exe"]);
+        assert_eq!(type_word(&mut e, "fn "), "function ");
+
+        e.set_context(&["zalo.exe"]);
+        assert_eq!(type_word(&mut e, "fn "), "fn ");
+    }
+
+    /// Apply an undo/redo result to `screen` as a frontend would
+    fn replay(screen: &mut String, r: &super::Result) {
+        assert_eq!(r.action, Action::Undo as u8);
+        for _ in 0..r.backspace {
+            screen.pop();
+        }
+        screen.extend(
+            r.chars[..r.count as usize]
+                .iter()
+                .filter_map(|&c| char::from_u32(c)),
+        );
+    }
+
+    #[test]
+    fn test_undo_transform() {
+        // Mark after the word was committed: the rest is typed again
+        let mut e = Engine::new();
+        let mut screen = type_word(&mut e, "vieetj nam");
+        assert_eq!(screen, "việt nam");
+        replay(&mut screen, &e.undo_last_transform());
+        assert_eq!(screen, "viêtj nam");
+        replay(&mut screen, &e.undo_last_transform());
+        assert_eq!(screen, "vieetj nam");
+        assert!(!e.can_undo());
+        assert_eq!(e.undo_last_transform().action, Action::None as u8);
+
+        replay(&mut screen, &e.redo_last_transform());
+        replay(&mut screen, &e.redo_last_transform());
+        assert_eq!(screen, "việt nam");
+        assert!(!e.can_redo());
+
+        // Stroke, then typing goes on from a fresh word
+        let mut e = Engine::new();
+        let mut screen = type_word(&mut e, "ddi");
+        replay(&mut screen, &e.undo_last_transform());
+        assert_eq!(screen, "ddi");
+        screen += &type_word(&mut e, " ok");
+        assert_eq!(screen, "ddi ok");
+        assert!(!e.can_redo(), "typing drops redo");
+    }
+
+    #[test]
+    fn test_undo_shortcut() {
+        let mut e = Engine::new();
+        e.shortcuts_mut().add(Shortcut::new("vn", "Việt Nam"));
+        let mut screen = type_word(&mut e, "vn toi");
+        assert_eq!(screen, "Việt Nam toi");
+        replay(&mut screen, &e.undo_last_transform());
+        assert_eq!(screen, "vn toi");
+        replay(&mut screen, &e.redo_last_transform());
+        assert_eq!(screen, "Việt Nam toi");
+
+        // Legacy charset: backspaces count output chars
+        let mut e = Engine::new();
+        e.set_output_form(Form::Decomposed);
+        e.shortcuts_mut().add(Shortcut::new("vn", "Việt Nam"));
+        type_word(&mut e, "vn ");
+        let r = e.undo_last_transform();
+        assert_eq!(r.backspace, "Vie\u{323}\u{302}t Nam ".chars().count() as u8);
+        assert_eq!(r.count, 3);
+    }
+
+    #[test]
+    fn test_undo_dropped() {
+        use crate::data::keys;
+
+        // Backspacing into the rewrite
+        let mut e = Engine::new();
+        type_word(&mut e, "as b");
+        for _ in 0..3 {
+            e.on_key(keys::DELETE, false, false);
+        }
+        assert!(!e.can_undo());
+
+        // Caret moved
+        let mut e = Engine::new();
+        type_word(&mut e, "as");
+        e.on_key(keys::LEFT, false, false);
+        assert!(!e.can_undo());
+        type_word(&mut e, "as");
+        e.clear_all();
+        assert!(!e.can_undo());
+        type_word(&mut e, "as");
+        e.on_key(keys::A, false, true);
+        assert!(!e.can_undo());
+
+        // History belongs to the field
+        let mut e = Engine::new();
+        type_word(&mut e, "as");
+        let a = e.snapshot();
+        e.restore(Default::default());
+        assert!(!e.can_undo());
+        e.restore(a);
+        assert!(e.can_undo());
+    }
+
+    #[test]
+    fn test_user_dict() {
+        use super::user_dict::UserList;
+
+        let mut e = Engine::new();
+        assert_eq!(type_word(&mut e, "Samsung "), "Sámung ");
+        e.user_dict_mut().add(UserList::English, "samsung");
+        e.user_dict_mut().add(UserList::English, "terraform");
+        assert_eq!(type_word(&mut e, "Samsung "), "Samsung ");
+        assert_eq!(type_word(&mut e, "samsung,"), "samsung,");
+        assert_eq!(type_word(&mut e, "terraform "), "terraform ");
+        assert_eq!(type_word(&mut e, "tesla "), "téla ");
+
+        // Never restore a word the English heuristics revert
+        let mut e = Engine::new();
+        e.set_english_auto_restore(true);
+        assert_eq!(type_word(&mut e, "Pacs "), "Pacs ");
+        e.user_dict_mut().add(UserList::Vietnamese, "pác");
+        assert_eq!(type_word(&mut e, "Pacs "), "Pác ");
+
+        // Wins over spell check too
+        let mut e = Engine::new();
+        e.set_spell_check(true);
+        e.user_dict_mut().add(UserList::Vietnamese, "nghẻ");
+        assert_eq!(type_word(&mut e, "ngher "), "nghẻ ");
+    }
+
+    #[test]
+    fn test_predict() {
+        let model = "5\tviệt\n3\tviện\n4\tviệt nam\n2\tnam\n";
+        let mut e = Engine::new();
+        assert!(e.predict(3).is_empty());
+        e.set_predictor(Some(
+            super::predict::NgramModel::from_text(model, super::predict::DEFAULT_CAPACITY).unwrap(),
+        ));
+
+        // Completions of the word being typed, then next words
+        type_word(&mut e, "vie");
+        assert_eq!(e.predict(2), ["việt", "viện"]);
+        type_word(&mut e, "etj ");
+        assert_eq!(e.predict(1), ["nam"]);
+
+        // Committed words are learned, phrases end at punctuation
+        type_word(&mut e, "nam tooi tooi.");
+        assert_eq!(e.predictor().unwrap().complete(&[], "t", 1), ["tôi"]);
+        assert_eq!(e.predict(1), ["việt"]);
+        type_word(&mut e, "tooi ");
+        assert_eq!(e.predict(1), ["tôi"]);
+
+        // Prediction context belongs to the field
+        let a = e.snapshot();
+        e.restore(Default::default());
+        assert_eq!(e.predict(1), ["việt"]);
+        e.restore(a);
+        assert_eq!(e.predict(1), ["tôi"]);
+    }
+
+    #[test]
+    fn test_on_char() {
+        use super::{MOD_ALT, MOD_CTRL};
+        use crate::data::keys;
+        use crate::utils::type_chars;
+
+        // Same results as keycodes across the method tables
+        for (method, cases) in [
+            (0, TELEX_BASIC),
+            (0, TELEX_COMPOUND),
+            (0, TELEX_ESC_RESTORE),
+            (1, VNI_BASIC),
+            (1, VNI_ESC_RESTORE),
+            (2, VIQR_BASIC),
+            (2, VIQR_PUNCTUATION),
+        ] {
+            for (input, expected) in cases {
+                let mut e = Engine::new();
+                e.set_method(method);
+                e.set_esc_restore(true);
+                assert_eq!(type_chars(&mut e, input), *expected, "{}", input);
+            }
+        }
+
+        // Bridge round trip
+        for c in ('!'..='~').chain([' ', '\t', '\n']) {
+            let (key, shift) = keys::from_char(c).unwrap();
+            assert_eq!(keys::to_char(key, shift), Some(c), "{:?}", c);
+        }
+        assert_eq!(keys::from_char('W'), Some((keys::W, true)));
+        assert_eq!(keys::from_char('\u{8}'), Some((keys::DELETE, false)));
+        assert_eq!(keys::from_char('é'), None);
+
+        // Chars outside the layout end the word
+        let mut e = Engine::new();
+        assert_eq!(type_chars(&mut e, "vieés"), "vieés");
+        // ... without dropping undo or backspace-after-space
+        let mut e = Engine::new();
+        assert_eq!(type_chars(&mut e, "vieetj é<<s"), "viết");
+        let mut e = Engine::new();
+        assert_eq!(type_chars(&mut e, "vieetj vieé<<<<<s"), "viết");
+        let mut e = Engine::new();
+        type_chars(&mut e, "vieetj ");
+        assert_eq!(e.on_char('é', 0).action, Action::None as u8);
+        assert!(e.can_undo());
+        let r = e.undo_last_transform();
+        let mut screen: Vec<char> = "việt é".chars().collect();
+        screen.truncate(screen.len() - r.backspace as usize);
+        screen.extend(
+            r.chars[..r.count as usize]
+                .iter()
+                .filter_map(|&c| char::from_u32(c)),
+        );
+        assert_eq!(screen.into_iter().collect::<String>(), "viêtj é");
+
+        // Ctrl bypasses and drops the word; Alt (AltGr) does not
+        let mut e = Engine::new();
+        type_chars(&mut e, "vie");
+        assert_eq!(e.on_char('c', MOD_CTRL).action, Action::None as u8);
+        assert_eq!(e.get_buffer_string(), "");
+        type_chars(&mut e, "a");
+        let r = e.on_char('a', MOD_ALT);
+        assert_eq!(char::from_u32(r.chars[0]), Some('â'));
+    }
+
+    #[test]
+    fn test_on_event() {
+        use super::{KeyEvent, Output, MOD_CAPS_LOCK, MOD_CTRL, MOD_SHIFT};
+        use crate::data::keys;
+
+        /// Apply outputs to a screen like a frontend would
+        fn type_events(e: &mut Engine, events: &[KeyEvent]) -> String {
+            let mut screen = String::new();
+            for &event in events {
+                let typed = keys::to_char(event.key, event.is_uppercase());
+                match e.on_event(event) {
+                    _ if event.key_up => {}
+                    Output::PassThrough => screen.extend(typed),
+                    Output::Replace {
+                        delete,
+                        insert,
+                        consume_key,
+                        ..
+                    } => {
+                        for _ in 0..delete {
+                            screen.pop();
+                        }
+                        screen.push_str(&insert);
+                        if !consume_key {
+                            screen.extend(typed);
+                        }
+                    }
+                }
+            }
+            screen
+        }
+        let press =
+            |keys: &[u16]| -> Vec<KeyEvent> { keys.iter().map(|&k| KeyEvent::press(k)).collect() };
+
+        let mut e = Engine::new();
+        let events = press(&[keys::V, keys::I, keys::E, keys::E, keys::T, keys::J]);
+        assert_eq!(type_events(&mut e, &events), "việt");
+
+        // Shift or Caps Lock capitalizes, both cancel out; releases do nothing
+        let mut e = Engine::new();
+        let events = [
+            KeyEvent::press(keys::D).with_modifiers(MOD_SHIFT),
+            KeyEvent::release(keys::D),
+            KeyEvent::press(keys::D).with_modifiers(MOD_CAPS_LOCK),
+            KeyEvent::press(keys::A).with_modifiers(MOD_SHIFT | MOD_CAPS_LOCK),
+        ];
+        assert_eq!(type_events(&mut e, &events), "Đa");
+
+        // Holding a key types it instead of cycling the transform
+        let mut e = Engine::new();
+        let events = [
+            KeyEvent::press(keys::O),
+            KeyEvent::press(keys::O).repeated().at(500),
+            KeyEvent::press(keys::O).repeated().at(530),
+        ];
+        assert_eq!(type_events(&mut e, &events), "ooo");
+        // ... keeping undo and backspace-after-space
+        let mut e = Engine::new();
+        let mut events = press(&[keys::V, keys::I, keys::E, keys::E, keys::T, keys::J]);
+        events.push(KeyEvent::press(keys::SPACE));
+        events.push(KeyEvent::press(keys::O));
+        events.push(KeyEvent::press(keys::O).repeated().at(500));
+        assert_eq!(type_events(&mut e, &events), "việt oo");
+        assert!(e.can_undo());
+        events.extend([
+            KeyEvent::press(keys::DELETE),
+            KeyEvent::press(keys::DELETE).repeated().at(500),
+            KeyEvent::press(keys::DELETE).repeated().at(530),
+            KeyEvent::press(keys::S),
+        ]);
+        assert_eq!(type_events(&mut Engine::new(), &events), "viết");
+
+        // Ctrl bypasses and drops the word
+        let mut e = Engine::new();
+        type_events(&mut e, &press(&[keys::A]));
+        let ctrl_a = KeyEvent::press(keys::A).with_modifiers(MOD_CTRL);
+        assert!(e.on_event(ctrl_a).is_pass_through());
+        assert_eq!(e.get_buffer_string(), "");
+
+        // Punctuation after a restore is typed by the app, a shortcut trigger is not
+        let mut e = Engine::new();
+        e.set_english_auto_restore(true);
+        let events = press(&[keys::T, keys::E, keys::X, keys::T, keys::COMMA]);
+        assert_eq!(type_events(&mut e, &events), "text,");
+        let long = "→".repeat(600);
+        let mut e = Engine::new();
+        e.shortcuts_mut().add(Shortcut::immediate("->", &long));
+        let events = [
+            KeyEvent::press(keys::MINUS),
+            KeyEvent::press(keys::DOT).with_modifiers(MOD_SHIFT),
+        ];
+        // The whole expansion comes at once
+        assert_eq!(type_events(&mut e, &events), long);
+    }
+
+    #[test]
+    fn test_compose() {
+        use super::{CompositionEvent, KeyEvent};
+        use crate::data::keys;
+        use crate::utils::type_composing;
+
+        // Same final text as backspace edits across the method tables
+        let tables = [
+            (0, false, TELEX_BASIC),
+            (0, false, TELEX_COMPOUND),
+            (0, false, TELEX_ESC_RESTORE),
+            (0, false, TELEX_NORMAL),
+            (0, true, TELEX_AUTO_RESTORE_R),
+            (0, true, TELEX_AUTO_RESTORE_X),
+            (0, true, TELEX_AUTO_RESTORE_J),
+            (0, true, TELEX_INTERLEAVED_DIPHTHONG),
+            (1, false, VNI_BASIC),
+            (1, false, VNI_ESC_RESTORE),
+        ];
+        let extra = [
+            // Backspace in the preedit, then into committed text
+            "vieet<<ej",
+            "ab <<<c",
+            // Backspace after space brings the word back for a mark
+            "vieet <s",
+            "Tieengs Vieetj, nha ",
+            "dd\x1b",
+        ];
+        for (method, auto_restore, cases) in tables {
+            let inputs = cases.iter().map(|&(input, _)| input).chain(extra);
+            for input in inputs {
+                let engine = || {
+                    let mut e = Engine::new();
+                    e.set_method(method);
+                    e.set_esc_restore(true);
+                    e.set_english_auto_restore(auto_restore);
+                    e
+                };
+                let expected = type_word(&mut engine(), input);
+                assert_eq!(type_composing(&mut engine(), input), expected, "{}", input);
+            }
+        }
+
+        // The word is a preedit until space commits it
+        let mut e = Engine::new();
+        type_composing(&mut e, "vieet");
+        let r = e.compose(KeyEvent::press(keys::J));
+        let preedit = CompositionEvent::Preedit {
+            text: "việt".to_string(),
+            cursor: 4,
+        };
+        assert_eq!((r.events, r.pass_through), (vec![preedit], false));
+        let r = e.compose(KeyEvent::press(keys::SPACE));
+        let hide = CompositionEvent::Preedit {
+            text: String::new(),
+            cursor: 0,
+        };
+        assert_eq!(
+            r.events,
+            [CompositionEvent::Commit("việt ".to_string()), hide]
+        );
+
+        // Enter commits and goes to the app; focus out commits the preedit
+        type_composing(&mut e, "ab");
+        let r = e.compose(KeyEvent::press(keys::RETURN));
+        assert!(r.pass_through);
+        assert_eq!(r.events[0], CompositionEvent::Commit("ab".to_string()));
+        type_composing(&mut e, "dd");
+        assert_eq!(e.end_composition(), Some("đ".to_string()));
+        assert_eq!(e.end_composition(), None);
+
+        // Long shortcut expansions commit whole, with their caret move
+        let mut e = Engine::new();
+        e.shortcuts_mut()
+            .add(Shortcut::new("sig", &"Việt Nam ".repeat(50)));
+        assert_eq!(type_composing(&mut e, "sig "), "Việt Nam ".repeat(50) + " ");
+    }
+
+    #[test]
+    fn test_surrounding_text() {
+        /// Click at `cursor` (chars) in `text`, then type `input` there
+        fn click_and_type(e: &mut Engine, text: &str, cursor: usize, input: &str) -> String {
+            let mut text: Vec<char> = text.chars().collect();
+            let mut cursor = cursor;
+            e.set_surrounding_text(&text.iter().collect::<String>(), cursor);
+            for c in input.chars() {
+                let c = if c == '<' { '\u{8}' } else { c };
+                let r = e.on_char(c, 0);
+                if r.action == Action::Send as u8 {
+                    let delete = r.backspace as usize;
+                    let insert = r.chars[..r.count as usize]
+                        .iter()
+                        .filter_map(|&ch| char::from_u32(ch));
+                    text.splice(cursor - delete..cursor, insert);
+                    cursor = cursor - delete + r.count as usize;
+                } else if c == '\u{8}' {
+                    cursor -= 1;
+                    text.remove(cursor);
+                } else {
+                    text.insert(cursor, c);
+                    cursor += 1;
+                }
+            }
+            text.into_iter().collect()
+        }
+
+        let mut e = Engine::new();
+        let typed = type_word(&mut e, "Tooi laf nguwowif Vieet Nam, ");
+        assert_eq!(typed, "Tôi là người Viêt Nam, ");
+        let cases = [
+            // Caret after a word: marks and tones edit it
+            ("Viêt", "j", "Tôi là người Việt Nam, "),
+            ("Tôi", "s", "Tối là người Viêt Nam, "),
+            ("Tôi là", "s", "Tôi lá người Viêt Nam, "),
+            // Caret after the space: Backspace brings the word back
+            ("Tôi là ", "<s ", "Tôi lá người Viêt Nam, "),
+            // Caret inside a word: typing starts fresh
+            ("Vi", "s", "Tôi là người Visêt Nam, "),
+            // Caret after punctuation: a new word
+            ("Nam,", "as", "Tôi là người Viêt Nam,á "),
+        ];
+        for (prefix, input, expected) in cases {
+            let cursor = typed.find(prefix).unwrap() + prefix.len();
+            let cursor = typed[..cursor].chars().count();
+            let mut e = Engine::new();
+            let result = click_and_type(&mut e, &typed, cursor, input);
+            assert_eq!(result, expected, "{}|{}", prefix, input);
+        }
+
+        // The phrase before the caret is the prediction context
+        let mut e = Engine::new();
+        e.set_surrounding_text("Xin chào. Tôi là người Việt", 27);
+        assert_eq!(e.prev_words, ["là", "người"]);
+        assert_eq!(e.get_buffer_string(), "Việt");
+        e.set_surrounding_text("Xin chào. Tôi là người ", 23);
+        assert_eq!(e.prev_words, ["là", "người"]);
+        assert_eq!(e.get_buffer_string(), "");
+    }
+
+    #[test]
+    fn test_snapshot_restore_fields() {
+        use crate::data::keys;
+
+        let mut e = Engine::new();
+        assert_eq!(type_word(&mut e, "vieet "), "viêt ");
+        let a = e.snapshot();
+
+        // Field B starts clean and has its own history
+        e.restore(Default::default());
+        assert_eq!(type_word(&mut e, "as"), "á");
+        let b = e.snapshot();
+
+        // Back to A: backspace deletes the space and brings "viêt" back
+        e.restore(a);
+        let r = e.on_key(keys::DELETE, false, false);
+        assert_eq!((r.backspace, r.count), (1, 0));
+        assert_eq!(e.get_buffer_string(), "viêt");
+        let r = e.on_key(keys::S, false, false);
+        assert_eq!(r.backspace, 2);
+        assert_eq!(char::from_u32(r.chars[0]), Some('ế'));
+        assert_eq!(e.get_buffer_string(), "viết");
+
+        // B kept its word
+        e.restore(b);
+        assert_eq!(e.get_buffer_string(), "á");
+    }
+
+    #[test]
+    fn test_restore_keeps_config() {
+        let mut e = Engine::new();
+        let state = e.snapshot();
+        e.set_method(1);
+        e.restore(state);
+        assert_eq!(type_word(&mut e, "a1"), "á");
+    }
+
     #[test]
     fn test_telex_esc_restore() {
         // ESC restore is disabled by default, enable it for this test
@@ -6460,6 +8227,40 @@ mod tests {
         }
     }
 
+    // Spell check: (input, with spell check, without)
+    const TELEX_SPELL_CHECK: &[(&str, &str, &str)] = &[
+        ("ngher ", "ngher ", "nghẻ "),
+        ("trosp ", "trosp ", "tróp "),
+        ("Ngher,", "Ngher,", "Nghẻ,"),
+        // Attested syllables are kept
+        ("vieetj ", "việt ", "việt "),
+        ("nghix ", "nghĩ ", "nghĩ "),
+        ("hoaf ", "hoà ", "hoà "),
+        // Untransformed words are left alone
+        ("ngo ", "ngo ", "ngo "),
+        ("xooong ", "xoong ", "xoong "),
+        ("booong ", "boong ", "boong "),
+        ("dayx ", "dãy ", "dãy "),
+        ("teepj ", "tệp ", "tệp "),
+        ("secs ", "séc ", "séc "),
+    ];
+
+    #[test]
+    fn test_spell_check_restore() {
+        for (input, on, off) in TELEX_SPELL_CHECK {
+            let mut e = Engine::new();
+            e.set_spell_check(true);
+            assert_eq!(type_word(&mut e, input), *on, "[Spell check] '{}'", input);
+            let mut e = Engine::new();
+            assert_eq!(
+                type_word(&mut e, input),
+                *off,
+                "[No spell check] '{}'",
+                input
+            );
+        }
+    }
+
     /// Issue: Typing "DDD" with shift/capslock held → should produce "DD", not "Dd"
     /// When stroke is reverted (ddd → dd), the added 'd' must preserve caps state
     #[test]
@@ -0,0 +1,330 @@
+//! Word Prediction (n-gram model)
+//!
+//! Counts unigrams, bigrams and trigrams of committed words and ranks
+//! candidates with stupid backoff: the trigram score if the two previous
+//! words were seen before the candidate, else 0.4 × the bigram score, else
+//! 0.16 × the unigram score. Words are stored lowercase.
+//!
+//! Model file, one `count<TAB>words` n-gram per line (`#` starts a comment
+//! line), e.g. `120<TAB>không`, `35<TAB>không có`, `12<TAB>tôi không có`.
+
+use crate::data::chars;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// Default max n-gram entries kept in memory
+pub const DEFAULT_CAPACITY: usize = 100_000;
+
+/// Max words in an n-gram
+const ORDER: usize = 3;
+
+/// Score factor per backoff step
+const BACKOFF: f64 = 0.4;
+
+/// Error loading or saving a model file
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelFileError {
+    /// Line without `count<TAB>words`, a zero count, or more than 3 words
+    /// (1-based line number)
+    InvalidLine(usize),
+    /// Reading or writing the file failed
+    Io(String),
+}
+
+impl fmt::Display for ModelFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLine(n) => write!(f, "line {}: expected count<TAB>words", n),
+            Self::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ModelFileError {}
+
+/// Unigram/bigram/trigram counts with a bounded number of entries
+///
+/// When learning goes past the capacity, all counts are halved and the
+/// ones dropping to zero are removed, so rare n-grams go first.
+#[derive(Debug, Clone)]
+pub struct NgramModel {
+    /// Follower counts by context: "" (unigrams), "w1", "w1 w2"
+    grams: HashMap<String, HashMap<String, u32>>,
+    entries: usize,
+    capacity: usize,
+}
+
+impl Default for NgramModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NgramModel {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Empty model keeping at most `capacity` n-grams
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            grams: HashMap::new(),
+            entries: 0,
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Number of n-grams stored
+    pub fn len(&self) -> usize {
+        self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries == 0
+    }
+
+    /// Parse a model file (see module docs), keeping at most `capacity`
+    pub fn from_text(text: &str, capacity: usize) -> Result<Self, ModelFileError> {
+        let mut model = Self::with_capacity(capacity);
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = ModelFileError::InvalidLine(i + 1);
+            let (count, words) = line.split_once('\t').ok_or(invalid.clone())?;
+            let count: u32 = count.trim().parse().map_err(|_| invalid.clone())?;
+            let words: Vec<String> = words.split_whitespace().map(str::to_lowercase).collect();
+            if count == 0 || words.is_empty() || words.len() > ORDER {
+                return Err(invalid);
+            }
+            let (word, context) = words.split_last().unwrap();
+            model.add(context.join(" "), word, count);
+        }
+        model.prune();
+        Ok(model)
+    }
+
+    /// Model file text, n-grams sorted for stable diffs
+    pub fn to_text(&self) -> String {
+        let mut lines: Vec<(String, u32)> = self
+            .grams
+            .iter()
+            .flat_map(|(context, followers)| {
+                followers.iter().map(move |(word, &count)| {
+                    let gram = if context.is_empty() {
+                        word.clone()
+                    } else {
+                        format!("{} {}", context, word)
+                    };
+                    (gram, count)
+                })
+            })
+            .collect();
+        lines.sort();
+        lines
+            .iter()
+            .map(|(gram, count)| format!("{}\t{}\n", count, gram))
+            .collect()
+    }
+
+    /// Load a model file
+    pub fn load(path: impl AsRef<Path>, capacity: usize) -> Result<Self, ModelFileError> {
+        let text = std::fs::read_to_string(path).map_err(|e| ModelFileError::Io(e.to_string()))?;
+        Self::from_text(&text, capacity)
+    }
+
+    /// Save as a model file (learned counts included)
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ModelFileError> {
+        std::fs::write(path, self.to_text()).map_err(|e| ModelFileError::Io(e.to_string()))
+    }
+
+    /// Count `word` after `context` (the previous words, oldest first)
+    pub fn learn(&mut self, context: &[String], word: &str) {
+        let word = word.to_lowercase();
+        let start = context.len().saturating_sub(ORDER - 1);
+        for from in start..=context.len() {
+            self.add(context[from..].join(" "), &word, 1);
+        }
+        self.prune();
+    }
+
+    /// Top `k` words after `context` starting with `prefix`, best first
+    ///
+    /// `prefix` matches with or without diacritics ("ngu" finds "người",
+    /// "ngư" does not find "nguôi"); a capitalized prefix capitalizes the
+    /// results. Ties are broken alphabetically.
+    pub fn complete(&self, context: &[String], prefix: &str, k: usize) -> Vec<String> {
+        let capitalize = prefix.chars().next().is_some_and(char::is_uppercase);
+        let prefix: Vec<char> = prefix.to_lowercase().chars().collect();
+        let start = context.len().saturating_sub(ORDER - 1);
+        let contexts: Vec<String> = (start..=context.len())
+            .map(|from| context[from..].join(" ").to_lowercase())
+            .collect();
+
+        let mut scores: HashMap<&str, f64> = HashMap::new();
+        let mut weight = 1.0;
+        // Longest context first: a word keeps its best-backed score
+        for context in &contexts {
+            if let Some(followers) = self.grams.get(context) {
+                let total: u32 = followers.values().sum();
+                for (word, &count) in followers {
+                    if has_prefix(word, &prefix) {
+                        let score = weight * f64::from(count) / f64::from(total);
+                        scores.entry(word).or_insert(score);
+                    }
+                }
+            }
+            weight *= BACKOFF;
+        }
+
+        let mut ranked: Vec<(&str, f64)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+        ranked
+            .into_iter()
+            .take(k)
+            .map(|(word, _)| {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(c) if capitalize => c.to_uppercase().chain(chars).collect(),
+                    _ => word.to_string(),
+                }
+            })
+            .collect()
+    }
+
+    fn add(&mut self, context: String, word: &str, count: u32) {
+        let followers = self.grams.entry(context).or_default();
+        match followers.get_mut(word) {
+            Some(c) => *c = c.saturating_add(count),
+            None => {
+                followers.insert(word.to_string(), count);
+                self.entries += 1;
+            }
+        }
+    }
+
+    /// Halve all counts until the model fits its capacity
+    fn prune(&mut self) {
+        while self.entries > self.capacity {
+            for followers in self.grams.values_mut() {
+                followers.retain(|_, count| {
+                    *count /= 2;
+                    *count > 0
+                });
+            }
+            self.grams.retain(|_, followers| !followers.is_empty());
+            self.entries = self.grams.values().map(HashMap::len).sum();
+        }
+    }
+}
+
+/// `word` starts with `prefix`, where a plain prefix letter also matches
+/// the letter with diacritics
+fn has_prefix(word: &str, prefix: &[char]) -> bool {
+    let mut chars = word.chars();
+    prefix.iter().all(|&p| {
+        chars
+            .next()
+            .is_some_and(|c| c == p || base_char(c) == Some(p))
+    })
+}
+
+/// Letter without tone mark, vowel mark or stroke ("ữ" → 'u', "đ" → 'd')
+fn base_char(c: char) -> Option<char> {
+    let p = chars::parse_char(c)?;
+    crate::utils::key_to_char(p.key, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tiny fixture model
+    const MODEL: &str = "# count<TAB>words\n\
+        50\ttôi\n\
+        40\tkhông\n\
+        30\tcó\n\
+        20\tngười\n\
+        10\tnguyên\n\
+        5\tngủ\n\
+        20\ttôi không\n\
+        5\ttôi có\n\
+        15\tkhông có\n\
+        3\tkhông người\n\
+        9\ttôi không người\n";
+
+    fn words(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_next_word() {
+        let m = NgramModel::from_text(MODEL, DEFAULT_CAPACITY).unwrap();
+        assert_eq!(m.len(), 11);
+        assert_eq!(m.complete(&[], "", 2), ["tôi", "không"]);
+        assert_eq!(
+            m.complete(&words("tôi"), "", 5),
+            ["không", "có", "tôi", "người", "nguyên"]
+        );
+        // Trigram wins over the bigram "không có"
+        assert_eq!(m.complete(&words("tôi không"), "", 2), ["người", "có"]);
+        // Unseen context backs off to unigrams
+        assert_eq!(m.complete(&words("mưa"), "", 1), ["tôi"]);
+    }
+
+    #[test]
+    fn test_complete_prefix() {
+        let m = NgramModel::from_text(MODEL, DEFAULT_CAPACITY).unwrap();
+        assert_eq!(m.complete(&[], "ngu", 5), ["người", "nguyên", "ngủ"]);
+        assert_eq!(m.complete(&[], "ngư", 5), ["người"]);
+        assert_eq!(m.complete(&[], "Ng", 1), ["Người"]);
+        assert!(m.complete(&[], "xyz", 5).is_empty());
+    }
+
+    #[test]
+    fn test_learn_and_capacity() {
+        let mut m = NgramModel::with_capacity(4);
+        m.learn(&[], "Xin");
+        m.learn(&words("xin"), "chào");
+        assert_eq!(m.len(), 3);
+        assert_eq!(m.complete(&words("xin"), "", 1), ["chào"]);
+
+        // Over capacity: counts of 1 are dropped, frequent n-grams stay
+        let mut m = NgramModel::with_capacity(4);
+        for _ in 0..3 {
+            m.learn(&[], "a");
+        }
+        for w in ["b", "c", "d", "e"] {
+            m.learn(&[], w);
+        }
+        assert!(m.len() <= 4);
+        assert_eq!(m.complete(&[], "", 5), ["a"]);
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let m = NgramModel::from_text(MODEL, DEFAULT_CAPACITY).unwrap();
+        let text = m.to_text();
+        assert!(text.starts_with("30\tcó\n"), "{}", text);
+        let back = NgramModel::from_text(&text, DEFAULT_CAPACITY).unwrap();
+        assert_eq!(back.to_text(), text);
+
+        for (bad, line) in [
+            ("5 tôi", 1),
+            ("#\n0\ttôi", 2),
+            ("x\ttôi", 1),
+            ("1\ta b c d", 1),
+        ] {
+            assert_eq!(
+                NgramModel::from_text(bad, 10).unwrap_err(),
+                ModelFileError::InvalidLine(line)
+            );
+        }
+        assert!(matches!(
+            NgramModel::load("/nonexistent/model.txt", 10),
+            Err(ModelFileError::Io(_))
+        ));
+    }
+}
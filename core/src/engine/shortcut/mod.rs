@@ -2,17 +2,25 @@
 //!
 //! Allows users to define shortcuts like "vn" → "Việt Nam"
 //! Shortcuts can be specific to input methods (Telex/VNI) or apply to all.
+//! Replacements may contain template placeholders (see `template`).
 
-use super::buffer::MAX;
-use std::collections::HashMap;
+pub mod template;
+mod trie;
 
-/// Maximum replacement length in UTF-32 codepoints (matches Result.chars array size)
-/// This limit ensures replacement fits in the FFI result buffer.
-/// Note: Vietnamese characters with diacritics (ồ, ế, ẫ) count as 1 codepoint each.
-pub const MAX_REPLACEMENT_LEN: usize = MAX - 1; // -1 to leave room for trailing space
+use std::fmt;
+use template::{Clipboard, Clock, SystemClock};
+use trie::Trie;
+
+/// Minimum prefix length for typo-tolerant suggestions
+const FUZZY_MIN_LEN: usize = 3;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// Input method that shortcut applies to
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum InputMethod {
     /// Apply to all input methods
     #[default]
@@ -21,10 +29,14 @@ pub enum InputMethod {
     Telex,
     /// Apply only to VNI
     Vni,
+    /// Apply only to VIQR
+    Viqr,
 }
 
 /// Trigger condition for shortcut
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum TriggerCondition {
     /// Trigger immediately when buffer matches
     Immediate,
@@ -34,6 +46,8 @@ pub enum TriggerCondition {
 
 /// Case handling mode
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum CaseMode {
     /// Keep replacement exactly as defined
     Exact,
@@ -56,74 +70,69 @@ pub struct Shortcut {
     pub enabled: bool,
     /// Which input method this shortcut applies to
     pub input_method: InputMethod,
+    /// Context tags (e.g. process names) this shortcut is limited to;
+    /// empty = everywhere. Stored lowercase.
+    pub contexts: Vec<String>,
 }
 
 impl Shortcut {
-    /// Validate and truncate replacement if it exceeds MAX_REPLACEMENT_LEN.
-    /// Counts UTF-32 codepoints (Vietnamese diacritics = 1 codepoint each).
-    fn validate_replacement(replacement: &str) -> String {
-        let char_count = replacement.chars().count();
-        if char_count <= MAX_REPLACEMENT_LEN {
-            replacement.to_string()
-        } else {
-            // Truncate to MAX_REPLACEMENT_LEN codepoints
-            replacement.chars().take(MAX_REPLACEMENT_LEN).collect()
-        }
-    }
-
     /// Create a new shortcut with word boundary trigger (applies to all input methods)
     /// Issue #86: Case-insensitive matching, smart case output (ko→không, KO→KHÔNG, Ko→Không)
-    /// Replacement is truncated to MAX_REPLACEMENT_LEN (255) codepoints if too long.
+    /// Replacements of any length are kept; the engine emits long ones in chunks.
     pub fn new(trigger: &str, replacement: &str) -> Self {
         Self {
             trigger: trigger.to_lowercase(), // Store lowercase for case-insensitive matching
-            replacement: Self::validate_replacement(replacement),
+            replacement: replacement.to_string(),
             condition: TriggerCondition::OnWordBoundary,
             case_mode: CaseMode::MatchCase, // Smart case transformation
             enabled: true,
             input_method: InputMethod::All,
+            contexts: Vec::new(),
         }
     }
 
     /// Create an immediate trigger shortcut (applies to all input methods).
     /// Issue #86: Case-insensitive matching, smart case output
-    /// Replacement is truncated to MAX_REPLACEMENT_LEN (255) codepoints if too long.
+    /// Replacements of any length are kept; the engine emits long ones in chunks.
     pub fn immediate(trigger: &str, replacement: &str) -> Self {
         Self {
             trigger: trigger.to_lowercase(), // Store lowercase for case-insensitive matching
-            replacement: Self::validate_replacement(replacement),
+            replacement: replacement.to_string(),
             condition: TriggerCondition::Immediate,
             case_mode: CaseMode::MatchCase, // Smart case transformation
             enabled: true,
             input_method: InputMethod::All,
+            contexts: Vec::new(),
         }
     }
 
     /// Create a Telex-specific shortcut with immediate trigger.
     /// Issue #86: Case-insensitive matching, smart case output
-    /// Replacement is truncated to MAX_REPLACEMENT_LEN (255) codepoints if too long.
+    /// Replacements of any length are kept; the engine emits long ones in chunks.
     pub fn telex(trigger: &str, replacement: &str) -> Self {
         Self {
             trigger: trigger.to_lowercase(), // Store lowercase for case-insensitive matching
-            replacement: Self::validate_replacement(replacement),
+            replacement: replacement.to_string(),
             condition: TriggerCondition::Immediate,
             case_mode: CaseMode::MatchCase, // Smart case transformation
             enabled: true,
             input_method: InputMethod::Telex,
+            contexts: Vec::new(),
         }
     }
 
     /// Create a VNI-specific shortcut with immediate trigger.
     /// Issue #86: Case-insensitive matching, smart case output
-    /// Replacement is truncated to MAX_REPLACEMENT_LEN (255) codepoints if too long.
+    /// Replacements of any length are kept; the engine emits long ones in chunks.
     pub fn vni(trigger: &str, replacement: &str) -> Self {
         Self {
             trigger: trigger.to_lowercase(), // Store lowercase for case-insensitive matching
-            replacement: Self::validate_replacement(replacement),
+            replacement: replacement.to_string(),
             condition: TriggerCondition::Immediate,
             case_mode: CaseMode::MatchCase, // Smart case transformation
             enabled: true,
             input_method: InputMethod::Vni,
+            contexts: Vec::new(),
         }
     }
 
@@ -133,22 +142,37 @@ impl Shortcut {
         self
     }
 
+    /// Limit this shortcut to the given context tags (e.g. "code.exe")
+    pub fn in_contexts(mut self, tags: &[&str]) -> Self {
+        self.contexts = tags.iter().map(|t| t.trim().to_lowercase()).collect();
+        self.contexts.sort();
+        self.contexts.dedup();
+        self
+    }
+
+    /// Check if shortcut applies in the current context
+    ///
+    /// Unscoped shortcuts apply everywhere; scoped ones need one of their
+    /// tags in `context` (case-insensitive).
+    pub fn applies_in(&self, context: &[&str]) -> bool {
+        self.contexts.is_empty()
+            || context
+                .iter()
+                .any(|tag| self.contexts.contains(&tag.trim().to_lowercase()))
+    }
+
     /// Check if shortcut applies to given input method
     ///
     /// - If shortcut is for `All`: matches any method
     /// - If shortcut is for `Telex`: matches `Telex` or `All` query
     /// - If shortcut is for `Vni`: matches `Vni` or `All` query
+    /// - If shortcut is for `Viqr`: matches `Viqr` or `All` query
     pub fn applies_to(&self, query_method: InputMethod) -> bool {
         match self.input_method {
             // Shortcut for All → matches any query
             InputMethod::All => true,
             // Shortcut for specific method → matches if query is same method OR query is All
-            InputMethod::Telex => {
-                query_method == InputMethod::Telex || query_method == InputMethod::All
-            }
-            InputMethod::Vni => {
-                query_method == InputMethod::Vni || query_method == InputMethod::All
-            }
+            method => query_method == method || query_method == InputMethod::All,
         }
     }
 }
@@ -162,23 +186,66 @@ pub struct ShortcutMatch {
     pub output: String,
     /// Whether to include the trigger key in output
     pub include_trigger_key: bool,
+    /// Left-arrow moves after output to place the caret (`{cursor}`), 0 = at end
+    pub cursor_left: usize,
 }
 
 /// Shortcut table manager
-#[derive(Debug, Default)]
 pub struct ShortcutTable {
-    /// Shortcuts indexed by trigger (lowercase)
-    shortcuts: HashMap<String, Shortcut>,
-    /// Sorted triggers by length (longest first) for matching
-    sorted_triggers: Vec<String>,
+    /// Shortcuts indexed by trigger (lowercase), one per context set
+    shortcuts: Trie<Vec<Shortcut>>,
+    /// Time source for date/time placeholders
+    clock: Box<dyn Clock>,
+    /// Text source for `{clipboard}` (none: expands to nothing)
+    clipboard: Option<Box<dyn Clipboard>>,
+}
+
+impl Default for ShortcutTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for ShortcutTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShortcutTable")
+            .field("shortcuts", &self.shortcuts)
+            .finish_non_exhaustive()
+    }
 }
 
 impl ShortcutTable {
     pub fn new() -> Self {
         Self {
-            shortcuts: HashMap::new(),
-            sorted_triggers: vec![],
+            shortcuts: Trie::default(),
+            clock: Box::new(SystemClock::default()),
+            clipboard: None,
+        }
+    }
+
+    /// Set the time source for `{date}`, `{time}` and `{weekday_vi}`
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Read `{date}`, `{time}` and `{weekday_vi}` from the system time at
+    /// `minutes` east of UTC (420 for Vietnam)
+    ///
+    /// Replaces a clock set with `set_clock`. Returns false for offsets
+    /// beyond ±14 hours.
+    pub fn set_utc_offset(&mut self, minutes: i32) -> bool {
+        if minutes.abs() > 14 * 60 {
+            return false;
         }
+        self.clock = Box::new(SystemClock {
+            utc_offset_minutes: minutes,
+        });
+        true
+    }
+
+    /// Set the text source for `{clipboard}`
+    pub fn set_clipboard(&mut self, clipboard: Option<Box<dyn Clipboard>>) {
+        self.clipboard = clipboard;
     }
 
     /// Create with default Vietnamese shortcuts (common abbreviations)
@@ -228,19 +295,24 @@ impl ShortcutTable {
     }
 
     /// Add a shortcut
+    ///
+    /// Replaces the shortcut with the same trigger and contexts; shortcuts
+    /// with the same trigger but other contexts are kept.
     pub fn add(&mut self, shortcut: Shortcut) {
-        let trigger = shortcut.trigger.clone();
-        self.shortcuts.insert(trigger.clone(), shortcut);
-        self.rebuild_sorted_triggers();
+        let entries = self
+            .shortcuts
+            .get_or_insert_with(&shortcut.trigger, Vec::new);
+        match entries.iter_mut().find(|s| s.contexts == shortcut.contexts) {
+            Some(existing) => *existing = shortcut,
+            None => entries.push(shortcut),
+        }
     }
 
     /// Remove a shortcut (exact match, case-sensitive)
+    ///
+    /// Removes the trigger in all contexts and returns the first one removed.
     pub fn remove(&mut self, trigger: &str) -> Option<Shortcut> {
-        let result = self.shortcuts.remove(trigger);
-        if result.is_some() {
-            self.rebuild_sorted_triggers();
-        }
-        result
+        self.shortcuts.remove(trigger)?.into_iter().next()
     }
 
     /// Check if buffer matches any shortcut (for any input method)
@@ -259,18 +331,72 @@ impl ShortcutTable {
         buffer: &str,
         method: InputMethod,
     ) -> Option<(&str, &Shortcut)> {
-        let buffer_lower = buffer.to_lowercase();
-        // Longest-match-first, case-insensitive match
-        for trigger in &self.sorted_triggers {
-            if buffer_lower == *trigger {
-                if let Some(shortcut) = self.shortcuts.get(trigger) {
-                    if shortcut.enabled && shortcut.applies_to(method) {
-                        return Some((trigger, shortcut));
-                    }
-                }
-            }
+        self.lookup_in_context(buffer, method, &[])
+    }
+
+    /// Check if buffer matches any shortcut for the input method and context
+    ///
+    /// A shortcut scoped to the context wins over an unscoped one.
+    pub fn lookup_in_context(
+        &self,
+        buffer: &str,
+        method: InputMethod,
+        context: &[&str],
+    ) -> Option<(&str, &Shortcut)> {
+        // Case-insensitive match
+        let entries = self.shortcuts.get(&buffer.to_lowercase())?;
+        let shortcut = Self::pick(entries, method, context)?;
+        Some((&shortcut.trigger, shortcut))
+    }
+
+    /// Best usable shortcut among those sharing a trigger
+    fn pick<'a>(
+        entries: &'a [Shortcut],
+        method: InputMethod,
+        context: &[&str],
+    ) -> Option<&'a Shortcut> {
+        entries
+            .iter()
+            .filter(|s| s.enabled && s.applies_to(method) && s.applies_in(context))
+            .max_by_key(|s| !s.contexts.is_empty())
+    }
+
+    /// Suggest shortcuts for a completion popup (any input method, no context)
+    pub fn suggest(&self, prefix: &str, limit: usize) -> Vec<&Shortcut> {
+        self.suggest_in_context(prefix, limit, InputMethod::All, &[])
+    }
+
+    /// Suggest shortcuts whose trigger starts with `prefix`, best first
+    ///
+    /// Prefixes of `FUZZY_MIN_LEN` chars or more also match triggers one typo
+    /// away. Ranked by typo count, then trigger length, then trigger.
+    pub fn suggest_in_context(
+        &self,
+        prefix: &str,
+        limit: usize,
+        method: InputMethod,
+        context: &[&str],
+    ) -> Vec<&Shortcut> {
+        let query: Vec<char> = prefix.to_lowercase().chars().collect();
+        if query.is_empty() || limit == 0 {
+            return Vec::new();
         }
-        None
+        let max_typos = usize::from(query.len() >= FUZZY_MIN_LEN);
+        let mut found: Vec<(usize, &Shortcut)> = self
+            .shortcuts
+            .fuzzy_prefix(&query, max_typos)
+            .into_iter()
+            .filter_map(|(entries, typos)| Some((typos, Self::pick(entries, method, context)?)))
+            .collect();
+        found.sort_by(|(ta, a), (tb, b)| {
+            (ta, a.trigger.chars().count(), &a.trigger).cmp(&(
+                tb,
+                b.trigger.chars().count(),
+                &b.trigger,
+            ))
+        });
+        found.truncate(limit);
+        found.into_iter().map(|(_, s)| s).collect()
     }
 
     /// Try to match buffer with trigger key (for any input method)
@@ -308,31 +434,57 @@ impl ShortcutTable {
         is_word_boundary: bool,
         method: InputMethod,
     ) -> Option<ShortcutMatch> {
-        let (trigger, shortcut) = self.lookup_for_method(buffer, method)?;
+        self.try_match_in_context(buffer, key_char, is_word_boundary, method, &[])
+    }
+
+    /// Try to match buffer with trigger key in the current context
+    ///
+    /// # Arguments
+    /// * `buffer` - Current buffer content (as string)
+    /// * `key_char` - The key that was just pressed
+    /// * `is_word_boundary` - Whether key_char is a word boundary
+    /// * `method` - The current input method (Telex/VNI)
+    /// * `context` - Current context tags (e.g. foreground process name)
+    ///
+    /// # Returns
+    /// ShortcutMatch if a shortcut should be triggered
+    pub fn try_match_in_context(
+        &self,
+        buffer: &str,
+        key_char: Option<char>,
+        is_word_boundary: bool,
+        method: InputMethod,
+        context: &[&str],
+    ) -> Option<ShortcutMatch> {
+        let (trigger, shortcut) = self.lookup_in_context(buffer, method, context)?;
 
         match shortcut.condition {
             TriggerCondition::Immediate => {
-                let output = self.apply_case(buffer, &shortcut.replacement, shortcut.case_mode);
+                let (output, cursor_left) = self.render(buffer, shortcut);
                 Some(ShortcutMatch {
                     // Use char count, not byte length (UTF-8 chars like đ are multi-byte)
                     backspace_count: trigger.chars().count(),
                     output,
                     include_trigger_key: false,
+                    cursor_left,
                 })
             }
             TriggerCondition::OnWordBoundary => {
                 if is_word_boundary {
-                    let mut output =
-                        self.apply_case(buffer, &shortcut.replacement, shortcut.case_mode);
+                    let (mut output, mut cursor_left) = self.render(buffer, shortcut);
                     // Append the trigger key (space, etc.)
                     if let Some(ch) = key_char {
                         output.push(ch);
+                        if cursor_left > 0 {
+                            cursor_left += 1;
+                        }
                     }
                     Some(ShortcutMatch {
                         // Use char count, not byte length (UTF-8 chars like đ are multi-byte)
                         backspace_count: trigger.chars().count(),
                         output,
                         include_trigger_key: true,
+                        cursor_left,
                     })
                 } else {
                     None
@@ -341,6 +493,33 @@ impl ShortcutTable {
         }
     }
 
+    /// Expand templates and apply case: (output, chars after the caret)
+    fn render(&self, buffer: &str, shortcut: &Shortcut) -> (String, usize) {
+        if !template::is_template(&shortcut.replacement) {
+            let output = self.apply_case(buffer, &shortcut.replacement, shortcut.case_mode);
+            return (output, 0);
+        }
+        let e = template::expand(
+            &shortcut.replacement,
+            self.clock.as_ref(),
+            self.clipboard.as_deref(),
+        );
+        let full = format!("{}{}", e.before, e.after);
+        let output = self.apply_case(buffer, &full, shortcut.case_mode);
+        // Case changes only the first char or every char, so the cased
+        // `before` is a prefix of the cased output
+        let before_len = self
+            .apply_case(buffer, &e.before, shortcut.case_mode)
+            .chars()
+            .count();
+        let cursor_left = if e.after.is_empty() {
+            0
+        } else {
+            output.chars().count() - before_len
+        };
+        (output, cursor_left)
+    }
+
     /// Apply case transformation based on mode
     fn apply_case(&self, trigger: &str, replacement: &str, mode: CaseMode) -> String {
         match mode {
@@ -369,11 +548,11 @@ impl ShortcutTable {
         }
     }
 
-    /// Rebuild sorted triggers list (longest first)
-    fn rebuild_sorted_triggers(&mut self) {
-        self.sorted_triggers = self.shortcuts.keys().cloned().collect();
-        self.sorted_triggers
-            .sort_by_key(|s| std::cmp::Reverse(s.len()));
+    /// Iterate shortcuts sorted by trigger, then contexts
+    pub fn iter(&self) -> impl Iterator<Item = &Shortcut> {
+        let mut list: Vec<&Shortcut> = self.shortcuts.values().flatten().collect();
+        list.sort_by(|a, b| (&a.trigger, &a.contexts).cmp(&(&b.trigger, &b.contexts)));
+        list.into_iter()
     }
 
     /// Check if shortcut table is empty
@@ -381,15 +560,14 @@ impl ShortcutTable {
         self.shortcuts.is_empty()
     }
 
-    /// Get number of shortcuts
+    /// Get number of shortcuts (each context set counts separately)
     pub fn len(&self) -> usize {
-        self.shortcuts.len()
+        self.shortcuts.values().map(Vec::len).sum()
     }
 
     /// Clear all shortcuts
     pub fn clear(&mut self) {
         self.shortcuts.clear();
-        self.sorted_triggers.clear();
     }
 }
 
@@ -671,6 +849,14 @@ mod tests {
         assert!(vni_shortcut.applies_to(InputMethod::All));
         assert!(!vni_shortcut.applies_to(InputMethod::Telex));
         assert!(vni_shortcut.applies_to(InputMethod::Vni));
+        assert!(!vni_shortcut.applies_to(InputMethod::Viqr));
+
+        let viqr_shortcut = Shortcut::new("dd", "đ").for_method(InputMethod::Viqr);
+        assert!(viqr_shortcut.applies_to(InputMethod::All));
+        assert!(!viqr_shortcut.applies_to(InputMethod::Telex));
+        assert!(viqr_shortcut.applies_to(InputMethod::Viqr));
+        assert!(!telex_shortcut.applies_to(InputMethod::Viqr));
+        assert!(all_shortcut.applies_to(InputMethod::Viqr));
     }
 
     #[test]
@@ -682,24 +868,18 @@ mod tests {
     }
 
     #[test]
-    fn test_replacement_validation_truncation() {
-        // Create a very long replacement (>255 characters with Vietnamese)
-        // MAX_REPLACEMENT_LEN is 255, so we need more than that
+    fn test_replacement_longer_than_result_kept() {
+        // Longer than one FFI Result (CHUNK_LEN = 255 codepoints)
         let long_text = "Đây là một đoạn văn bản rất dài để kiểm tra việc cắt ngắn. Nó có nhiều ký tự tiếng Việt có dấu như ồ, ế, ẫ, ơ, ư. Tiếp tục thêm nhiều nội dung để vượt quá giới hạn 255 ký tự. Đây là một câu rất dài với nhiều từ tiếng Việt phức tạp để đảm bảo rằng chúng ta vượt quá giới hạn cho phép của hệ thống.";
         let char_count = long_text.chars().count();
         assert!(
-            char_count > MAX_REPLACEMENT_LEN,
-            "Test text should exceed limit (got {} chars, need > {})",
-            char_count,
-            MAX_REPLACEMENT_LEN
+            char_count > crate::engine::CHUNK_LEN,
+            "Test text should exceed one result (got {} chars)",
+            char_count
         );
 
         let shortcut = Shortcut::new("long", long_text);
-        let result_count = shortcut.replacement.chars().count();
-        assert_eq!(
-            result_count, MAX_REPLACEMENT_LEN,
-            "Should truncate to MAX_REPLACEMENT_LEN"
-        );
+        assert_eq!(shortcut.replacement, long_text, "Should not truncate");
     }
 
     #[test]
@@ -894,4 +1074,252 @@ mod tests {
             InputMethod::All,
         );
     }
+
+    // =========================================================================
+    // Templates
+    // =========================================================================
+
+    fn fixed_clock() -> Box<template::FixedClock> {
+        // Monday 2026-10-19 08:05
+        Box::new(template::FixedClock(template::DateTime::from_unix(
+            1_792_397_109,
+        )))
+    }
+
+    #[test]
+    fn test_template_expansion() {
+        let mut table = ShortcutTable::new();
+        table.set_clock(fixed_clock());
+        table.add(Shortcut::new("hn", "{weekday_vi}, ngày {date}"));
+        table.add(Shortcut::immediate("t@", "{time}"));
+        table.add(Shortcut::new("vn", "Việt Nam"));
+
+        let cases = [
+            ("hn", Some(' '), "Thứ Hai, ngày 19/10/2026 "),
+            ("HN", None, "THỨ HAI, NGÀY 19/10/2026"),
+            ("vn", Some(' '), "Việt Nam "),
+        ];
+        for (buffer, key, expected) in cases {
+            let m = table.try_match(buffer, key, true).unwrap();
+            assert_eq!(
+                (m.output.as_str(), m.cursor_left),
+                (expected, 0),
+                "{}",
+                buffer
+            );
+        }
+        let m = table.try_match("t@", None, false).unwrap();
+        assert_eq!(m.output, "08:05");
+    }
+
+    #[test]
+    fn test_template_utc_offset() {
+        use crate::utils::is_system_hour;
+
+        let mut table = ShortcutTable::new();
+        table.add(Shortcut::new("gio", "{date:%Y-%m-%d %H}"));
+        for minutes in [0, 420, -330] {
+            assert!(table.set_utc_offset(minutes));
+            assert!(
+                is_system_hour(minutes, || table
+                    .try_match("gio", None, true)
+                    .unwrap()
+                    .output),
+                "{}",
+                minutes
+            );
+        }
+        assert!(!table.set_utc_offset(15 * 60));
+        // A rejected offset keeps the clock
+        assert!(is_system_hour(-330, || {
+            table.try_match("gio", None, true).unwrap().output
+        }));
+    }
+
+    #[test]
+    fn test_template_cursor() {
+        let mut table = ShortcutTable::new();
+        table.add(Shortcut::new("bb", "<b>{cursor}</b>"));
+        table.add(Shortcut::new("qq", "{cursor}trích dẫn"));
+
+        let cases = [
+            ("bb", Some(' '), "<b></b> ", 5),
+            ("bb", None, "<b></b>", 4),
+            ("BB", Some(' '), "<B></B> ", 5),
+            // Capitalized first char lies after the caret
+            ("Qq", None, "Trích dẫn", 9),
+        ];
+        for (buffer, key, expected, cursor_left) in cases {
+            let m = table.try_match(buffer, key, true).unwrap();
+            assert_eq!(
+                (m.output.as_str(), m.cursor_left),
+                (expected, cursor_left),
+                "{}",
+                buffer
+            );
+        }
+    }
+
+    #[test]
+    fn test_plain_replacement_braces() {
+        // Replacements saved before templates existed keep their braces
+        let mut table = ShortcutTable::new();
+        table.set_clock(fixed_clock());
+        let cases = [
+            ("br", "a }} b"),
+            ("obj", "{{ }}"),
+            ("fmt", "{{date}} {0}"),
+            ("rs", "fn main() {}"),
+        ];
+        for (trigger, replacement) in cases {
+            table.add(Shortcut::new(trigger, replacement));
+            let m = table.try_match(trigger, None, true).unwrap();
+            assert_eq!(m.output, replacement, "{}", trigger);
+        }
+    }
+
+    #[test]
+    fn test_template_clipboard() {
+        struct Clip;
+        impl template::Clipboard for Clip {
+            fn text(&self) -> Option<String> {
+                Some("xin chào".to_string())
+            }
+        }
+
+        let mut table = table_with_shortcut("cb", "«{clipboard}»");
+        assert_eq!(table.try_match("cb", None, true).unwrap().output, "«»");
+        table.set_clipboard(Some(Box::new(Clip)));
+        assert_eq!(
+            table.try_match("cb", None, true).unwrap().output,
+            "«xin chào»"
+        );
+    }
+
+    // =========================================================================
+    // Context-scoped shortcuts
+    // =========================================================================
+
+    #[test]
+    fn test_context_scope() {
+        let mut table = ShortcutTable::new();
+        table.add(Shortcut::new("fn", "function").in_contexts(&["Code.exe", "devenv.exe"]));
+        table.add(Shortcut::new("vn", "Việt Nam"));
+
+        let cases: &[(&str, &[&str], Option<&str>)] = &[
+            ("fn", &["code.exe"], Some("function")),
+            ("fn", &["CODE.EXE"], Some("function")),
+            ("fn", &["zalo.exe", "devenv.exe"], Some("function")),
+            ("fn", &["zalo.exe"], None),
+            ("fn", &[], None),
+            // Unscoped shortcuts apply in every context
+            ("vn", &["zalo.exe"], Some("Việt Nam")),
+            ("vn", &[], Some("Việt Nam")),
+        ];
+        for &(buffer, context, expected) in cases {
+            let m = table.try_match_in_context(buffer, None, true, InputMethod::Telex, context);
+            assert_eq!(
+                m.map(|m| m.output).as_deref(),
+                expected,
+                "{} {:?}",
+                buffer,
+                context
+            );
+        }
+        assert!(table.try_match("fn", None, true).is_none());
+    }
+
+    #[test]
+    fn test_context_overrides_global() {
+        let mut table = ShortcutTable::new();
+        table.add(Shortcut::new("ty", "thank you"));
+        table.add(Shortcut::new("ty", "type").in_contexts(&["code.exe"]));
+        assert_eq!(table.len(), 2);
+
+        let m = |t: &ShortcutTable, ctx: &[&str]| {
+            t.try_match_in_context("ty", None, true, InputMethod::All, ctx)
+                .map(|m| m.output)
+        };
+        assert_eq!(m(&table, &["code.exe"]).as_deref(), Some("type"));
+        assert_eq!(m(&table, &["zalo.exe"]).as_deref(), Some("thank you"));
+
+        // Same trigger and contexts replaces; a disabled scoped entry falls back
+        let mut off = Shortcut::new("ty", "typed").in_contexts(&["code.exe"]);
+        off.enabled = false;
+        table.add(off);
+        assert_eq!(table.len(), 2);
+        assert_eq!(m(&table, &["code.exe"]).as_deref(), Some("thank you"));
+
+        // Remove drops every context variant
+        assert!(table.remove("ty").is_some());
+        assert!(table.is_empty());
+    }
+
+    // =========================================================================
+    // Suggestions
+    // =========================================================================
+
+    fn triggers(list: Vec<&Shortcut>) -> Vec<&str> {
+        list.into_iter().map(|s| s.trigger.as_str()).collect()
+    }
+
+    #[test]
+    fn test_suggest_prefix() {
+        let mut table = ShortcutTable::new();
+        for (t, r) in [
+            ("vnpt", "VNPT"),
+            ("vn", "Việt Nam"),
+            ("vnd", "đồng"),
+            ("vnm", "Vinamilk"),
+            ("hn", "Hà Nội"),
+        ] {
+            table.add(Shortcut::new(t, r));
+        }
+        let mut off = Shortcut::new("vna", "Vietnam Airlines");
+        off.enabled = false;
+        table.add(off);
+
+        // Shorter triggers first, then alphabetical; disabled ones skipped
+        assert_eq!(
+            triggers(table.suggest("v", 10)),
+            ["vn", "vnd", "vnm", "vnpt"]
+        );
+        assert_eq!(triggers(table.suggest("VN", 2)), ["vn", "vnd"]);
+        // Three chars: one-typo matches follow the exact prefix ones
+        assert_eq!(
+            triggers(table.suggest("vnp", 10)),
+            ["vnpt", "vn", "vnd", "vnm"]
+        );
+        assert!(table.suggest("x", 10).is_empty());
+        assert!(table.suggest("", 10).is_empty());
+        assert!(table.suggest("v", 0).is_empty());
+    }
+
+    #[test]
+    fn test_suggest_fuzzy() {
+        let mut table = ShortcutTable::new();
+        for t in ["sign", "signature", "sgn", "sig", "xyz"] {
+            table.add(Shortcut::new(t, t));
+        }
+        // Exact prefix matches rank above one-typo matches
+        assert_eq!(
+            triggers(table.suggest("sig", 10)),
+            ["sig", "sign", "signature", "sgn"]
+        );
+        assert_eq!(triggers(table.suggest("sihn", 10)), ["sign", "signature"]);
+        // Short prefixes are not typo-tolerant
+        assert_eq!(triggers(table.suggest("sg", 10)), ["sgn"]);
+    }
+
+    #[test]
+    fn test_suggest_in_context() {
+        let mut table = ShortcutTable::new();
+        table.add(Shortcut::new("fn", "function").in_contexts(&["code.exe"]));
+        table.add(Shortcut::new("fo", "for"));
+        table.add(Shortcut::telex("ff", "ff"));
+
+        let list = |method, ctx: &[&str]| triggers(table.suggest_in_context("f", 10, method, ctx));
+        assert_eq!(list(InputMethod::Telex, &["code.exe"]), ["ff", "fn", "fo"]);
+        assert_eq!(list(InputMethod::Vni, &[]), ["fo"]);
+    }
 }
@@ -0,0 +1,340 @@
+//! Shortcut Templates - placeholders expanded when a shortcut fires
+//!
+//! | Placeholder        | Output                                   |
+//! |--------------------|------------------------------------------|
+//! | `{date}`           | `18/10/2026` (same as `{date:%d/%m/%Y}`) |
+//! | `{date:FMT}`       | date/time in FMT (see below)             |
+//! | `{time}`           | `14:05` (same as `{time:%H:%M}`)         |
+//! | `{time:FMT}`       | date/time in FMT                         |
+//! | `{weekday_vi}`     | `Thứ Hai` … `Chủ Nhật`                   |
+//! | `{clipboard}`      | clipboard text (empty if unavailable)    |
+//! | `{cursor}`         | caret position after expansion           |
+//! | `{{` / `}}`        | literal `{` / `}`                        |
+//!
+//! FMT specifiers: `%d` `%m` `%Y` `%y` `%H` `%M` `%S` `%%`. Unknown
+//! placeholders and specifiers are kept literally. Replacements without a
+//! placeholder are not templates: their braces, `{{` and `}}` included, are
+//! output unchanged.
+//!
+//! Time comes from a `Clock` so expansion is deterministic in tests; the
+//! default `SystemClock` reads UTC plus a fixed offset, set with
+//! `ShortcutTable::set_utc_offset` (`ime_set_utc_offset` over FFI). Frontends
+//! with a local-time API can inject their own clock instead.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Calendar date and wall-clock time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: i32,
+    /// 1-12
+    pub month: u8,
+    /// 1-31
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    /// 0 = Sunday … 6 = Saturday
+    pub weekday: u8,
+}
+
+impl DateTime {
+    /// Date-time `secs` seconds after the Unix epoch (proleptic Gregorian)
+    pub fn from_unix(secs: i64) -> Self {
+        let days = secs.div_euclid(86_400);
+        let rem = secs.rem_euclid(86_400);
+        // Days to civil date (Howard Hinnant's algorithm)
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+        let year = (yoe + era * 400 + i64::from(month <= 2)) as i32;
+        Self {
+            year,
+            month,
+            day,
+            hour: (rem / 3600) as u8,
+            minute: (rem / 60 % 60) as u8,
+            second: (rem % 60) as u8,
+            // 1970-01-01 was a Thursday
+            weekday: (days + 4).rem_euclid(7) as u8,
+        }
+    }
+}
+
+/// Source of the current date-time for `{date}`, `{time}`, `{weekday_vi}`
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime;
+}
+
+/// Source of `{clipboard}` text
+pub trait Clipboard: Send + Sync {
+    fn text(&self) -> Option<String>;
+}
+
+/// System time at a fixed UTC offset
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock {
+    pub utc_offset_minutes: i32,
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime {
+        let secs = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs() as i64),
+        };
+        DateTime::from_unix(secs + i64::from(self.utc_offset_minutes) * 60)
+    }
+}
+
+/// Clock that always returns the same date-time (tests, previews)
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime {
+        self.0
+    }
+}
+
+/// Vietnamese weekday names, indexed by `DateTime::weekday`
+const WEEKDAYS_VI: [&str; 7] = [
+    "Chủ Nhật",
+    "Thứ Hai",
+    "Thứ Ba",
+    "Thứ Tư",
+    "Thứ Năm",
+    "Thứ Sáu",
+    "Thứ Bảy",
+];
+
+/// Expanded template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expansion {
+    /// Text before the caret (all text if there is no `{cursor}`)
+    pub before: String,
+    /// Text after the caret
+    pub after: String,
+}
+
+/// Placeholder at the start of a template chunk
+enum Placeholder<'a> {
+    /// `{date}` / `{time}` with their format
+    DateTime(&'a str),
+    WeekdayVi,
+    Clipboard,
+    Cursor,
+}
+
+/// Parse the placeholder `s` starts with: (placeholder, byte length)
+fn placeholder(s: &str) -> Option<(Placeholder<'_>, usize)> {
+    let end = s.strip_prefix('{')?.find('}')? + 1;
+    let name = &s[1..end];
+    let (name, arg) = match name.split_once(':') {
+        Some((n, a)) => (n, Some(a)),
+        None => (name, None),
+    };
+    let p = match (name, arg) {
+        ("date", _) => Placeholder::DateTime(arg.unwrap_or("%d/%m/%Y")),
+        ("time", _) => Placeholder::DateTime(arg.unwrap_or("%H:%M")),
+        ("weekday_vi", None) => Placeholder::WeekdayVi,
+        ("clipboard", None) => Placeholder::Clipboard,
+        ("cursor", None) => Placeholder::Cursor,
+        _ => return None,
+    };
+    Some((p, end + 1))
+}
+
+/// Check if a replacement needs template expansion
+///
+/// Only replacements with a placeholder are templates; braces elsewhere,
+/// `{{` and `}}` included, are plain text.
+pub fn is_template(replacement: &str) -> bool {
+    let mut rest = replacement;
+    while let Some(i) = rest.find('{') {
+        rest = &rest[i..];
+        if let Some(r) = rest.strip_prefix("{{") {
+            rest = r;
+            continue;
+        }
+        if placeholder(rest).is_some() {
+            return true;
+        }
+        rest = &rest[1..];
+    }
+    false
+}
+
+/// Expand placeholders in `template`
+///
+/// Only the first `{cursor}` sets the caret; later ones are dropped.
+pub fn expand(template: &str, clock: &dyn Clock, clipboard: Option<&dyn Clipboard>) -> Expansion {
+    let mut before = String::new();
+    let mut after = String::new();
+    let mut has_cursor = false;
+    let mut now = None;
+    let mut rest = template;
+
+    while !rest.is_empty() {
+        let out = if has_cursor { &mut after } else { &mut before };
+        if let Some(r) = rest.strip_prefix("{{") {
+            out.push('{');
+            rest = r;
+            continue;
+        }
+        if let Some(r) = rest.strip_prefix("}}") {
+            out.push('}');
+            rest = r;
+            continue;
+        }
+        if let Some((p, len)) = placeholder(rest) {
+            let mut now = || *now.get_or_insert_with(|| clock.now());
+            match p {
+                Placeholder::DateTime(fmt) => format_date(&now(), fmt, out),
+                Placeholder::WeekdayVi => out.push_str(WEEKDAYS_VI[now().weekday as usize % 7]),
+                Placeholder::Clipboard => {
+                    if let Some(text) = clipboard.and_then(|c| c.text()) {
+                        out.push_str(&text);
+                    }
+                }
+                Placeholder::Cursor => has_cursor = true,
+            }
+            rest = &rest[len..];
+            continue;
+        }
+        let ch = rest.chars().next().unwrap_or_default();
+        out.push(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+
+    Expansion { before, after }
+}
+
+/// Append `dt` formatted with strftime-style `fmt`
+fn format_date(dt: &DateTime, fmt: &str, out: &mut String) {
+    let mut chars = fmt.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('d') => out.push_str(&format!("{:02}", dt.day)),
+            Some('m') => out.push_str(&format!("{:02}", dt.month)),
+            Some('Y') => out.push_str(&dt.year.to_string()),
+            Some('y') => out.push_str(&format!("{:02}", dt.year.rem_euclid(100))),
+            Some('H') => out.push_str(&format!("{:02}", dt.hour)),
+            Some('M') => out.push_str(&format!("{:02}", dt.minute)),
+            Some('S') => out.push_str(&format!("{:02}", dt.second)),
+            Some('%') => out.push('%'),
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Monday 2026-10-19 08:05:09
+    const CLOCK: FixedClock = FixedClock(DateTime {
+        year: 2026,
+        month: 10,
+        day: 19,
+        hour: 8,
+        minute: 5,
+        second: 9,
+        weekday: 1,
+    });
+
+    struct Text(&'static str);
+
+    impl Clipboard for Text {
+        fn text(&self) -> Option<String> {
+            Some(self.0.to_string())
+        }
+    }
+
+    #[test]
+    fn test_from_unix() {
+        let cases = [
+            (0, (1970, 1, 1, 0, 0, 0, 4)),
+            (951_782_400, (2000, 2, 29, 0, 0, 0, 2)),
+            (1_792_397_109, (2026, 10, 19, 8, 5, 9, 1)),
+            (-1, (1969, 12, 31, 23, 59, 59, 3)),
+        ];
+        for (secs, (year, month, day, hour, minute, second, weekday)) in cases {
+            let expected = DateTime {
+                year,
+                month,
+                day,
+                hour,
+                minute,
+                second,
+                weekday,
+            };
+            assert_eq!(DateTime::from_unix(secs), expected, "{}", secs);
+        }
+    }
+
+    #[test]
+    fn test_expand() {
+        let cases = [
+            ("Ngày {date}", "Ngày 19/10/2026", ""),
+            ("{date:%Y-%m-%d %H:%M:%S}", "2026-10-19 08:05:09", ""),
+            ("{date:%y}", "26", ""),
+            ("{time}", "08:05", ""),
+            ("{weekday_vi}, {date}", "Thứ Hai, 19/10/2026", ""),
+            ("<b>{cursor}</b>", "<b>", "</b>"),
+            ("a{cursor}b{cursor}c", "a", "bc"),
+            ("{{date}}", "{date}", ""),
+            ("100%% {date:%Q}", "100%% %Q", ""),
+            // Not placeholders: kept as is
+            (
+                "{unknown} {weekday_vi:x} {",
+                "{unknown} {weekday_vi:x} {",
+                "",
+            ),
+            ("Trích: \"{clipboard}\"", "Trích: \"\"", ""),
+        ];
+        for (template, before, after) in cases {
+            let e = expand(template, &CLOCK, None);
+            assert_eq!(
+                (e.before.as_str(), e.after.as_str()),
+                (before, after),
+                "{}",
+                template
+            );
+        }
+
+        let e = expand("Trích: \"{clipboard}\"", &CLOCK, Some(&Text("xin chào")));
+        assert_eq!(e.before, "Trích: \"xin chào\"");
+    }
+
+    #[test]
+    fn test_is_template() {
+        let cases = [
+            ("{date}", true),
+            ("{{ {time:%H} }}", true),
+            ("a {cursor}", true),
+            ("Việt Nam", false),
+            // Braces without a placeholder are plain text
+            ("a }} b", false),
+            ("{{date}}", false),
+            ("fn() {{}}", false),
+            ("{unknown} {weekday_vi:x} {", false),
+        ];
+        for (replacement, expected) in cases {
+            assert_eq!(is_template(replacement), expected, "{}", replacement);
+        }
+    }
+}
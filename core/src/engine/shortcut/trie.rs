@@ -0,0 +1,266 @@
+//! Character trie keyed by shortcut trigger
+//!
+//! Nodes live in one Vec and children are kept sorted by char, so lookups
+//! are O(trigger length) with a binary search per char. Removing a key
+//! prunes the nodes it leaves empty; their slots are reused by later inserts.
+
+#[derive(Debug)]
+pub(crate) struct Trie<T> {
+    nodes: Vec<Node<T>>,
+    /// Slots of pruned nodes, reused before growing `nodes`
+    free: Vec<usize>,
+    /// Number of keys holding a value
+    len: usize,
+}
+
+#[derive(Debug)]
+struct Node<T> {
+    /// (char, node index), sorted by char
+    children: Vec<(char, usize)>,
+    value: Option<T>,
+}
+
+impl<T> Node<T> {
+    fn new() -> Self {
+        Self {
+            children: Vec::new(),
+            value: None,
+        }
+    }
+}
+
+impl<T> Default for Trie<T> {
+    fn default() -> Self {
+        Self {
+            nodes: vec![Node::new()],
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<T> Trie<T> {
+    fn child(&self, node: usize, c: char) -> Option<usize> {
+        let children = &self.nodes[node].children;
+        children
+            .binary_search_by_key(&c, |&(k, _)| k)
+            .ok()
+            .map(|i| children[i].1)
+    }
+
+    /// Index for a new empty node
+    fn alloc(&mut self) -> usize {
+        match self.free.pop() {
+            Some(id) => id,
+            None => {
+                self.nodes.push(Node::new());
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// Nodes in use, root included
+    #[cfg(test)]
+    fn node_count(&self) -> usize {
+        self.nodes.len() - self.free.len()
+    }
+
+    fn find(&self, key: &str) -> Option<usize> {
+        key.chars().try_fold(0, |node, c| self.child(node, c))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&T> {
+        self.nodes[self.find(key)?].value.as_ref()
+    }
+
+    /// Value for `key`, inserting `f()` if there is none
+    pub fn get_or_insert_with(&mut self, key: &str, f: impl FnOnce() -> T) -> &mut T {
+        let mut node = 0;
+        for c in key.chars() {
+            let children = &self.nodes[node].children;
+            node = match children.binary_search_by_key(&c, |&(k, _)| k) {
+                Ok(i) => children[i].1,
+                Err(i) => {
+                    let id = self.alloc();
+                    self.nodes[node].children.insert(i, (c, id));
+                    id
+                }
+            };
+        }
+        let slot = &mut self.nodes[node].value;
+        if slot.is_none() {
+            self.len += 1;
+        }
+        slot.get_or_insert_with(f)
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<T> {
+        // (parent, char) of each node on the path
+        let mut path = Vec::new();
+        let mut node = 0;
+        for c in key.chars() {
+            path.push((node, c));
+            node = self.child(node, c)?;
+        }
+        let value = self.nodes[node].value.take()?;
+        self.len -= 1;
+
+        // Prune nodes left with no value and no children, bottom up
+        for (parent, c) in path.into_iter().rev() {
+            let n = &self.nodes[node];
+            if n.value.is_some() || !n.children.is_empty() {
+                break;
+            }
+            let children = &mut self.nodes[parent].children;
+            if let Ok(i) = children.binary_search_by_key(&c, |&(k, _)| k) {
+                children.remove(i);
+            }
+            self.free.push(node);
+            node = parent;
+        }
+        Some(value)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.nodes.iter().filter_map(|n| n.value.as_ref())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Values whose key starts with something within `max` edits of `query`
+    ///
+    /// Returns each value with the smallest such edit distance (Levenshtein,
+    /// computed row by row while walking the trie).
+    pub fn fuzzy_prefix(&self, query: &[char], max: usize) -> Vec<(&T, usize)> {
+        let mut out = Vec::new();
+        let row: Vec<usize> = (0..=query.len()).collect();
+        self.walk(0, query, &row, query.len(), max, &mut out);
+        out
+    }
+
+    fn walk<'a>(
+        &'a self,
+        node: usize,
+        query: &[char],
+        row: &[usize],
+        best: usize,
+        max: usize,
+        out: &mut Vec<(&'a T, usize)>,
+    ) {
+        if best <= max {
+            if let Some(value) = &self.nodes[node].value {
+                out.push((value, best));
+            }
+        }
+        for &(c, child) in &self.nodes[node].children {
+            let mut next = Vec::with_capacity(row.len());
+            next.push(row[0] + 1);
+            for j in 1..row.len() {
+                let replace = row[j - 1] + usize::from(query[j - 1] != c);
+                next.push((row[j] + 1).min(next[j - 1] + 1).min(replace));
+            }
+            // Keep going while the whole query already matched above, or
+            // some alignment can still end within `max`
+            if best <= max || next.iter().any(|&d| d <= max) {
+                let best = best.min(next[query.len()]);
+                self.walk(child, query, &next, best, max, out);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trie(keys: &[&str]) -> Trie<String> {
+        let mut t = Trie::default();
+        for k in keys {
+            t.get_or_insert_with(k, || k.to_string());
+        }
+        t
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut t = trie(&["vn", "vnd", "hn", "đc"]);
+        assert_eq!(t.values().count(), 4);
+        assert_eq!(t.get("vn").map(String::as_str), Some("vn"));
+        assert_eq!(t.get("đc").map(String::as_str), Some("đc"));
+        assert_eq!(t.get("v"), None);
+        assert_eq!(t.get("vnx"), None);
+
+        assert_eq!(t.remove("vn").as_deref(), Some("vn"));
+        assert_eq!(t.remove("vn"), None);
+        assert_eq!(t.get("vnd").map(String::as_str), Some("vnd"));
+        assert_eq!(t.values().count(), 3);
+
+        t.clear();
+        assert!(t.is_empty());
+        assert_eq!(t.values().count(), 0);
+    }
+
+    #[test]
+    fn test_remove_prunes() {
+        let mut t = trie(&["vn", "hn"]);
+        let nodes = t.node_count();
+        let mut peak = None;
+        // Remove and re-add in a loop: nodes are pruned and reused
+        for _ in 0..3 {
+            for k in ["vnd", "vnđ", "tphcm", "v"] {
+                t.get_or_insert_with(k, || k.to_string());
+            }
+            assert_eq!(*peak.get_or_insert(t.nodes.len()), t.nodes.len());
+            for k in ["vnd", "vnđ", "tphcm", "v"] {
+                assert_eq!(t.remove(k).as_deref(), Some(k));
+            }
+            assert_eq!(t.node_count(), nodes);
+        }
+        assert_eq!(t.get("vn").map(String::as_str), Some("vn"));
+        assert_eq!(t.get("hn").map(String::as_str), Some("hn"));
+
+        // A key still in use as a prefix keeps its node
+        t.get_or_insert_with("vnd", || "vnd".to_string());
+        assert_eq!(t.remove("vn").as_deref(), Some("vn"));
+        assert_eq!(t.get("vnd").map(String::as_str), Some("vnd"));
+        assert_eq!(t.remove("vnd").as_deref(), Some("vnd"));
+        assert_eq!(t.remove("hn").as_deref(), Some("hn"));
+        assert_eq!(t.node_count(), 1);
+    }
+
+    #[test]
+    fn test_fuzzy_prefix() {
+        let t = trie(&["sig", "signature", "sign", "sgn", "xin", "tks"]);
+        let found = |q: &str, max| {
+            let q: Vec<char> = q.chars().collect();
+            let mut r: Vec<(String, usize)> = t
+                .fuzzy_prefix(&q, max)
+                .into_iter()
+                .map(|(v, d)| (v.clone(), d))
+                .collect();
+            r.sort();
+            r
+        };
+        let pairs = |v: &[(&str, usize)]| -> Vec<(String, usize)> {
+            v.iter().map(|&(s, d)| (s.to_string(), d)).collect()
+        };
+
+        assert_eq!(
+            found("sig", 0),
+            pairs(&[("sig", 0), ("sign", 0), ("signature", 0)])
+        );
+        // "sgn" is one deletion away; "sign" matches "sig" exactly
+        assert_eq!(
+            found("sig", 1),
+            pairs(&[("sgn", 1), ("sig", 0), ("sign", 0), ("signature", 0)])
+        );
+        assert_eq!(found("sihn", 1), pairs(&[("sign", 1), ("signature", 1)]));
+        assert!(found("zzz", 1).is_empty());
+    }
+}
@@ -0,0 +1,338 @@
+//! Shortcut Table Files
+//!
+//! - JSON (feature `serde`): versioned, carries every `Shortcut` field
+//! - Plain-text macros as used by UniKey/EVKey: `trigger:replacement` per line,
+//!   `;` or `#` starts a comment line
+//!
+//! JSON format (missing fields take the `Shortcut::new` defaults):
+//! ```json
+//! {
+//!   "version": 1,
+//!   "shortcuts": [
+//!     { "trigger": "vn", "replacement": "Việt Nam", "condition": "on_word_boundary",
+//!       "case_mode": "match_case", "enabled": true, "input_method": "all" },
+//!     { "trigger": "fn", "replacement": "function", "contexts": ["code.exe"] }
+//!   ]
+//! }
+//! ```
+
+use super::shortcut::{Shortcut, ShortcutTable};
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use super::shortcut::{CaseMode, InputMethod, TriggerCondition};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+/// Current JSON format version
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Error loading or saving a shortcut file
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShortcutFileError {
+    /// Macro line without `trigger:replacement` (1-based line number)
+    InvalidLine(usize),
+    /// Macro line with an empty trigger (1-based line number)
+    EmptyTrigger(usize),
+    /// JSON syntax or schema error
+    Parse(String),
+    /// File written by a newer format version
+    UnsupportedVersion(u32),
+    /// Reading or writing the file failed
+    Io(String),
+}
+
+impl fmt::Display for ShortcutFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLine(n) => write!(f, "line {}: expected trigger:replacement", n),
+            Self::EmptyTrigger(n) => write!(f, "line {}: empty trigger", n),
+            Self::Parse(e) => write!(f, "invalid shortcut file: {}", e),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported format version {}", v),
+            Self::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ShortcutFileError {}
+
+impl ShortcutTable {
+    /// Import a UniKey/EVKey macro file (`trigger:replacement` per line)
+    ///
+    /// Entries become word-boundary shortcuts with smart case. A later line
+    /// with the same trigger replaces an earlier one.
+    pub fn from_macro_text(text: &str) -> Result<Self, ShortcutFileError> {
+        let mut table = Self::new();
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            if line.trim().is_empty() || line.starts_with(';') || line.starts_with('#') {
+                continue;
+            }
+            let (trigger, replacement) = line
+                .split_once(':')
+                .ok_or(ShortcutFileError::InvalidLine(line_no))?;
+            let trigger = trigger.trim();
+            if trigger.is_empty() {
+                return Err(ShortcutFileError::EmptyTrigger(line_no));
+            }
+            table.add(Shortcut::new(trigger, replacement));
+        }
+        Ok(table)
+    }
+
+    /// Export enabled shortcuts as UniKey/EVKey macro text
+    ///
+    /// The format has no room for the other fields; entries it cannot hold
+    /// (context-scoped, trigger with ':' or multi-line replacement) are skipped.
+    pub fn to_macro_text(&self) -> String {
+        let mut out = String::new();
+        for s in self.iter() {
+            if !s.enabled
+                || !s.contexts.is_empty()
+                || s.trigger.contains(':')
+                || s.replacement.contains('\n')
+            {
+                continue;
+            }
+            out.push_str(&s.trigger);
+            out.push(':');
+            out.push_str(&s.replacement);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// JSON file root
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct ShortcutFile {
+    version: u32,
+    shortcuts: Vec<ShortcutEntry>,
+}
+
+/// One shortcut in a JSON file
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct ShortcutEntry {
+    trigger: String,
+    replacement: String,
+    #[serde(default = "default_condition")]
+    condition: TriggerCondition,
+    #[serde(default = "default_case_mode")]
+    case_mode: CaseMode,
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+    #[serde(default)]
+    input_method: InputMethod,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    contexts: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+fn default_condition() -> TriggerCondition {
+    TriggerCondition::OnWordBoundary
+}
+
+#[cfg(feature = "serde")]
+fn default_case_mode() -> CaseMode {
+    CaseMode::MatchCase
+}
+
+#[cfg(feature = "serde")]
+fn default_enabled() -> bool {
+    true
+}
+
+#[cfg(feature = "serde")]
+impl ShortcutTable {
+    /// Parse a JSON shortcut file
+    pub fn from_json(json: &str) -> Result<Self, ShortcutFileError> {
+        let file: ShortcutFile =
+            serde_json::from_str(json).map_err(|e| ShortcutFileError::Parse(e.to_string()))?;
+        if file.version > FORMAT_VERSION {
+            return Err(ShortcutFileError::UnsupportedVersion(file.version));
+        }
+        let mut table = Self::new();
+        for e in file.shortcuts {
+            if e.trigger.is_empty() {
+                return Err(ShortcutFileError::Parse("empty trigger".to_string()));
+            }
+            let contexts: Vec<&str> = e.contexts.iter().map(String::as_str).collect();
+            let mut s = Shortcut::new(&e.trigger, &e.replacement).in_contexts(&contexts);
+            s.condition = e.condition;
+            s.case_mode = e.case_mode;
+            s.enabled = e.enabled;
+            s.input_method = e.input_method;
+            table.add(s);
+        }
+        Ok(table)
+    }
+
+    /// Serialize to JSON (shortcuts sorted by trigger)
+    pub fn to_json(&self) -> String {
+        let file = ShortcutFile {
+            version: FORMAT_VERSION,
+            shortcuts: self
+                .iter()
+                .map(|s| ShortcutEntry {
+                    trigger: s.trigger.clone(),
+                    replacement: s.replacement.clone(),
+                    condition: s.condition,
+                    case_mode: s.case_mode,
+                    enabled: s.enabled,
+                    input_method: s.input_method,
+                    contexts: s.contexts.clone(),
+                })
+                .collect(),
+        };
+        serde_json::to_string_pretty(&file).unwrap_or_default()
+    }
+
+    /// Load a JSON shortcut file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ShortcutFileError> {
+        let json =
+            std::fs::read_to_string(path).map_err(|e| ShortcutFileError::Io(e.to_string()))?;
+        Self::from_json(&json)
+    }
+
+    /// Save as a JSON shortcut file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ShortcutFileError> {
+        std::fs::write(path, self.to_json()).map_err(|e| ShortcutFileError::Io(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_macro_import() {
+        let text = "\u{feff};DO NOT DELETE THIS LINE*** version=1 ***\r\n\
+                    vn:Việt Nam\r\n\
+                    \r\n\
+                    # comment\n\
+                    hcm:Hồ Chí Minh\n\
+                    time: 10:30 \n\
+                    vn:Việt Nam!\n";
+        let table = ShortcutTable::from_macro_text(text).unwrap();
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.lookup("vn").unwrap().1.replacement, "Việt Nam!");
+        // Only the first ':' separates; replacement is kept as is
+        assert_eq!(table.lookup("time").unwrap().1.replacement, " 10:30 ");
+        assert_eq!(table.lookup("HCM").unwrap().1.replacement, "Hồ Chí Minh");
+    }
+
+    #[test]
+    fn test_macro_malformed() {
+        assert_eq!(
+            ShortcutTable::from_macro_text("vn:Việt Nam\nno separator\n").unwrap_err(),
+            ShortcutFileError::InvalidLine(2)
+        );
+        assert_eq!(
+            ShortcutTable::from_macro_text("  :x").unwrap_err(),
+            ShortcutFileError::EmptyTrigger(1)
+        );
+        assert!(ShortcutTable::from_macro_text("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_macro_round_trip() {
+        let mut table = ShortcutTable::new();
+        table.add(Shortcut::new("vn", "Việt Nam"));
+        table.add(Shortcut::new("ko", "không"));
+        let mut off = Shortcut::new("off", "disabled");
+        off.enabled = false;
+        table.add(off);
+        table.add(Shortcut::new("sig", "line 1\nline 2"));
+        table.add(Shortcut::new("fn", "function").in_contexts(&["code.exe"]));
+
+        let text = table.to_macro_text();
+        assert_eq!(text, "ko:không\nvn:Việt Nam\n");
+        let back = ShortcutTable::from_macro_text(&text).unwrap();
+        assert_eq!(back.to_macro_text(), text);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_round_trip() {
+        let mut table = ShortcutTable::new();
+        table.add(Shortcut::new("vn", "Việt Nam"));
+        table.add(Shortcut::telex("->", "→"));
+        let mut exact = Shortcut::vni("hn", "Hà Nội").for_method(InputMethod::Vni);
+        exact.case_mode = CaseMode::Exact;
+        exact.enabled = false;
+        table.add(exact);
+        table.add(Shortcut::new("fn", "function").in_contexts(&["Code.exe", "devenv.exe"]));
+
+        let json = table.to_json();
+        assert!(json.contains("\"version\": 1"), "{}", json);
+        let back = ShortcutTable::from_json(&json).unwrap();
+        assert_eq!(back.to_json(), json);
+
+        let hn = back.iter().find(|s| s.trigger == "hn").unwrap();
+        assert_eq!(hn.condition, TriggerCondition::Immediate);
+        assert_eq!(hn.case_mode, CaseMode::Exact);
+        assert!(!hn.enabled);
+        assert_eq!(hn.input_method, InputMethod::Vni);
+        assert!(hn.contexts.is_empty());
+        let f = back.iter().find(|s| s.trigger == "fn").unwrap();
+        assert_eq!(f.contexts, ["code.exe", "devenv.exe"]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_defaults_and_errors() {
+        let table = ShortcutTable::from_json(
+            r#"{"version":1,"shortcuts":[{"trigger":"VN","replacement":"Việt Nam"}]}"#,
+        )
+        .unwrap();
+        let vn = table.iter().next().unwrap();
+        assert_eq!(vn.trigger, "vn");
+        assert_eq!(vn.condition, TriggerCondition::OnWordBoundary);
+        assert_eq!(vn.case_mode, CaseMode::MatchCase);
+        assert!(vn.enabled);
+
+        assert_eq!(
+            ShortcutTable::from_json(r#"{"version":2,"shortcuts":[]}"#).unwrap_err(),
+            ShortcutFileError::UnsupportedVersion(2)
+        );
+        for bad in [
+            "",
+            "[]",
+            r#"{"shortcuts":[]}"#,
+            r#"{"version":1,"shortcuts":[{"trigger":"vn"}]}"#,
+            r#"{"version":1,"shortcuts":[{"trigger":"","replacement":"x"}]}"#,
+            r#"{"version":1,"shortcuts":[{"trigger":"a","replacement":"b","condition":"later"}]}"#,
+        ] {
+            assert!(
+                matches!(
+                    ShortcutTable::from_json(bad),
+                    Err(ShortcutFileError::Parse(_))
+                ),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_load() {
+        let path =
+            std::env::temp_dir().join(format!("gonhanh-shortcuts-{}.json", std::process::id()));
+        let mut table = ShortcutTable::new();
+        table.add(Shortcut::new("vn", "Việt Nam"));
+        table.save(&path).unwrap();
+        let back = ShortcutTable::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(back.to_json(), table.to_json());
+        assert!(matches!(
+            ShortcutTable::load(&path),
+            Err(ShortcutFileError::Io(_))
+        ));
+    }
+}
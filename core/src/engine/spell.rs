@@ -0,0 +1,376 @@
+//! Vietnamese Spell Checking
+//!
+//! `validation` accepts any syllable with a valid structure; this module
+//! also checks it against a list of ~5.8k attested syllables, so forms that
+//! are well built but not words (e.g. "nghẻ", "khọp") can be flagged.
+//!
+//! The list (`data/vietnamese_syllables.txt`) was compiled by hand for this
+//! project from everyday, literary and Sino-Vietnamese vocabulary, including
+//! the halves of reduplicatives ("khúc khích", "lủng củng"), then checked
+//! against the Vietnamese gettext catalogues of a desktop distribution:
+//! every well-formed word there that the list lacked was reviewed and the
+//! real ones added ("dãy", "tệp", "hẳn"). Loan rhymes with a double o
+//! ("xoong", "boong", "soóc") are listed even though `validation` rejects
+//! them as foreign. It has no upstream source and is covered by the crate
+//! licence (GPL-3.0-or-later).
+//! Only attested forms belong in it: a form listed just to complete a tone
+//! series ("khọc" next to "khóc") would stop spell check restoring that typo.
+//!
+//! Lookups ignore case and tone placement: "hoà" and "hòa" are the same
+//! syllable. `suggest_corrections` ranks the list by closeness to a
+//! mistyped word.
+
+use super::{syllable, validation};
+use crate::data::chars::{self, mark, tone, ParsedChar};
+use crate::utils;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+/// Embedded syllable list (one lowercase syllable per line)
+const SYLLABLES: &str = include_str!("../data/vietnamese_syllables.txt");
+
+/// Normalized keys of all attested syllables
+static KNOWN: LazyLock<HashSet<String>> =
+    LazyLock::new(|| SYLLABLES.lines().filter_map(normalize).collect());
+
+/// Max suggestions returned by `suggest_corrections`
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Max correction cost (two letter edits)
+const MAX_COST: u32 = 4;
+
+/// Attested syllables split into parts, one spelling per syllable
+static CANDIDATES: LazyLock<Vec<(&str, Parts)>> = LazyLock::new(|| {
+    let mut seen = HashSet::new();
+    SYLLABLES
+        .lines()
+        .filter(|s| normalize(s).is_some_and(|key| seen.insert(key)))
+        .filter_map(|s| Some((s, Parts::of(s)?)))
+        .collect()
+});
+
+/// Spelling of one syllable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Spelling {
+    /// Attested Vietnamese syllable
+    Known,
+    /// Valid structure, but not a Vietnamese syllable ("nghẻ")
+    Unattested,
+    /// Not Vietnamese at all (foreign word, typo, digits)
+    Invalid,
+}
+
+/// Syllable without tone mark followed by the mark id, e.g. "hoa2"
+///
+/// Returns None for chars outside Vietnamese letters or more than one mark.
+fn normalize(syllable: &str) -> Option<String> {
+    let mut key = String::with_capacity(syllable.len() + 1);
+    let mut tone_mark = mark::NONE;
+    for c in syllable.chars() {
+        let p = chars::parse_char(c)?;
+        if p.mark != mark::NONE {
+            if tone_mark != mark::NONE {
+                return None;
+            }
+            tone_mark = p.mark;
+        }
+        key.push(if p.stroke {
+            chars::get_d(false)
+        } else {
+            chars::to_char(p.key, false, p.tone, mark::NONE)
+                .or_else(|| utils::key_to_char(p.key, false))?
+        });
+    }
+    key.push(char::from(b'0' + tone_mark));
+    Some(key)
+}
+
+/// Check if `syllable` is an attested Vietnamese syllable (any case)
+pub fn is_known_syllable(syllable: &str) -> bool {
+    normalize(syllable).is_some_and(|key| KNOWN.contains(&key))
+}
+
+/// Spell-check one word (a single syllable, as the engine commits it)
+pub fn check_word(word: &str) -> Spelling {
+    let parsed: Option<Vec<_>> = word.chars().map(chars::parse_char).collect();
+    let Some(parsed) = parsed.filter(|p| !p.is_empty()) else {
+        return Spelling::Invalid;
+    };
+    if is_known_syllable(word) {
+        return Spelling::Known;
+    }
+    let keys: Vec<u16> = parsed.iter().map(|p| p.key).collect();
+    let tones: Vec<u8> = parsed.iter().map(|p| p.tone).collect();
+    if validation::is_valid_with_tones(&keys, &tones) {
+        Spelling::Unattested
+    } else {
+        Spelling::Invalid
+    }
+}
+
+/// Closest attested syllables to a mistyped `word`, best first
+///
+/// Cost is a weighted edit distance over initial, glide, nucleus and final
+/// (letters past the syllable count as deletions) plus 2 for a different
+/// tone mark (a missing diacritic is a likelier slip than a wrong tone);
+/// tone placement never counts. A doubled letter in a word missing a
+/// diacritic is read as a misplaced Telex double ("khongg" for "khoong"),
+/// so the extra letter is free when the candidate adds a vowel mark or
+/// stroke. Ties keep list order, which makes results deterministic. Empty
+/// for known syllables and for words with chars outside Vietnamese letters.
+pub fn suggest_corrections(word: &str) -> Vec<String> {
+    if is_known_syllable(word) {
+        return Vec::new();
+    }
+    let Some(parts) = Parts::of(word) else {
+        return Vec::new();
+    };
+    let mut ranked: Vec<(u32, &str)> = CANDIDATES
+        .iter()
+        .map(|(s, p)| (parts.cost(p), *s))
+        .filter(|&(cost, _)| cost <= MAX_COST)
+        .collect();
+    ranked.sort_by_key(|&(cost, _)| cost);
+    ranked
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, s)| match_case(word, s))
+        .collect()
+}
+
+/// Letter without its tone mark: key, vowel modifier, stroke
+type Letter = (u16, u8, bool);
+
+/// Syllable as (initial, glide, nucleus, final) letters plus tone mark
+#[derive(Debug, Default)]
+struct Parts {
+    groups: [Vec<Letter>; 4],
+    mark: u8,
+    /// Letters past the end of the syllable ("khongg"), each a deletion
+    extra: u32,
+    /// Letters with a vowel mark or stroke (â, ơ, đ)
+    modified: usize,
+    /// Two identical letters in a row ("gg", "oo")
+    doubled: bool,
+}
+
+impl Parts {
+    /// Split a word with `syllable::parse`; a word without vowels is all
+    /// initial
+    fn of(word: &str) -> Option<Self> {
+        let parsed: Vec<ParsedChar> = word.chars().map(chars::parse_char).collect::<Option<_>>()?;
+        if parsed.is_empty() {
+            return None;
+        }
+        let keys: Vec<u16> = parsed.iter().map(|p| p.key).collect();
+        let letters = |idx: &mut dyn Iterator<Item = usize>| -> Vec<Letter> {
+            idx.map(|i| (parsed[i].key, parsed[i].tone, parsed[i].stroke))
+                .collect()
+        };
+        let s = syllable::parse(&keys);
+        let groups = if s.is_empty() {
+            [
+                letters(&mut (0..parsed.len())),
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+            ]
+        } else {
+            [
+                letters(&mut s.initial.iter().copied()),
+                letters(&mut s.glide.into_iter()),
+                letters(&mut s.vowel.iter().copied()),
+                letters(&mut s.final_c.iter().copied()),
+            ]
+        };
+        // A typo may carry two marks; the last one typed wins
+        let mark = parsed
+            .iter()
+            .rev()
+            .map(|p| p.mark)
+            .find(|&m| m != mark::NONE)
+            .unwrap_or(mark::NONE);
+        let extra = parsed.len() - groups.iter().map(Vec::len).sum::<usize>();
+        let modified = parsed
+            .iter()
+            .filter(|p| p.tone != tone::NONE || p.stroke)
+            .count();
+        let doubled = parsed
+            .windows(2)
+            .any(|w| (w[0].key, w[0].tone, w[0].stroke) == (w[1].key, w[1].tone, w[1].stroke));
+        Some(Self {
+            groups,
+            mark,
+            extra: extra as u32,
+            modified,
+            doubled,
+        })
+    }
+
+    fn cost(&self, other: &Parts) -> u32 {
+        let parts: u32 = self
+            .groups
+            .iter()
+            .zip(&other.groups)
+            .map(|(a, b)| edit_cost(a, b))
+            .sum();
+        let cost = parts + 2 * (self.extra + other.extra) + 2 * u32::from(self.mark != other.mark);
+        // Misplaced Telex double: the extra letter was the diacritic key
+        if self.doubled && other.modified > self.modified {
+            cost.saturating_sub(2)
+        } else {
+            cost
+        }
+    }
+}
+
+/// Weighted edit distance: changing only a diacritic (o → ô, d → đ) costs
+/// 1, any other substitution, insertion or deletion costs 2
+fn edit_cost(a: &[Letter], b: &[Letter]) -> u32 {
+    let mut row: Vec<u32> = (0..=b.len() as u32).map(|j| 2 * j).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut diag = row[0];
+        row[0] = 2 * (i as u32 + 1);
+        for (j, y) in b.iter().enumerate() {
+            let sub = if x == y {
+                0
+            } else if x.0 == y.0 {
+                1
+            } else {
+                2
+            };
+            let next = (diag + sub).min(row[j] + 2).min(row[j + 1] + 2);
+            diag = row[j + 1];
+            row[j + 1] = next;
+        }
+    }
+    row[b.len()]
+}
+
+/// Apply the case of `word` ("Khongg", "KHONGG") to a lowercase syllable
+fn match_case(word: &str, syllable: &str) -> String {
+    let mut word_chars = word.chars();
+    let first_upper = word_chars.next().is_some_and(char::is_uppercase);
+    if first_upper && word_chars.clone().count() > 0 && word_chars.all(char::is_uppercase) {
+        return syllable.to_uppercase();
+    }
+    let mut out = syllable.chars();
+    match out.next() {
+        Some(c) if first_upper => c.to_uppercase().chain(out).collect(),
+        _ => syllable.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Opening of Truyện Kiều and everyday sentences
+    const CORPUS: &str = "Trăm năm trong cõi người ta, chữ tài chữ mệnh khéo là ghét nhau. \
+        Trải qua một cuộc bể dâu, những điều trông thấy mà đau đớn lòng. \
+        Lạ gì bỉ sắc tư phong, trời xanh quen thói má hồng đánh ghen. \
+        Hôm nay trời đẹp quá, chúng tôi đi dạo quanh hồ Hoàn Kiếm rồi ghé quán phở. \
+        Người Việt Nam nói tiếng Việt, viết chữ quốc ngữ với đủ dấu thanh. \
+        Khuya rồi, con nghỉ ngơi đi, mai còn phải dậy sớm để thi cử. \
+        Thuở nhỏ tôi thường nghịch ngợm, khuỷu tay đầy những vết xước. \
+        Giường này của ông bà ngoại, gió thổi nhè nhẹ ngoài hiên nhà.";
+
+    #[test]
+    fn test_corpus_known() {
+        let unknown: Vec<&str> = CORPUS
+            .split(|c: char| !c.is_alphabetic())
+            .filter(|w| !w.is_empty())
+            .filter(|w| check_word(w) != Spelling::Known)
+            .collect();
+        assert!(unknown.is_empty(), "{:?}", unknown);
+    }
+
+    #[test]
+    fn test_list_structurally_valid() {
+        let bad: Vec<&str> = SYLLABLES
+            .lines()
+            .filter(|s| {
+                let parsed: Vec<_> = s.chars().filter_map(chars::parse_char).collect();
+                let keys: Vec<u16> = parsed.iter().map(|p| p.key).collect();
+                let tones: Vec<u8> = parsed.iter().map(|p| p.tone).collect();
+                // Loan rhymes with a double o ("xoong", "soóc") are outside
+                // the validator's native patterns but attested
+                match normalize(s) {
+                    Some(key) => {
+                        !key.contains("oo") && !validation::is_valid_with_tones(&keys, &tones)
+                    }
+                    None => true,
+                }
+            })
+            .collect();
+        assert!(bad.is_empty(), "{:?}", bad);
+        assert!(KNOWN.len() > 5500, "{}", KNOWN.len());
+    }
+
+    #[test]
+    fn test_check_word() {
+        let cases = [
+            ("việt", Spelling::Known),
+            ("Việt", Spelling::Known),
+            ("NGƯỜI", Spelling::Known),
+            // Tone placement does not matter
+            ("hoà", Spelling::Known),
+            ("hòa", Spelling::Known),
+            ("thuý", Spelling::Known),
+            ("thúy", Spelling::Known),
+            // Double-o loan rhymes
+            ("xoong", Spelling::Known),
+            ("soóc", Spelling::Known),
+            ("nghẻ", Spelling::Unattested),
+            ("tróp", Spelling::Unattested),
+            ("bưởng", Spelling::Unattested),
+            // Tone series of a real syllable, not words themselves
+            ("khọp", Spelling::Unattested),
+            ("khót", Spelling::Unattested),
+            ("khọc", Spelling::Unattested),
+            ("khóp", Spelling::Unattested),
+            ("khộc", Spelling::Unattested),
+            ("khong", Spelling::Unattested),
+            ("hello", Spelling::Invalid),
+            ("tiếngg", Spelling::Invalid),
+            ("viêt1", Spelling::Invalid),
+            ("", Spelling::Invalid),
+        ];
+        for (word, expected) in cases {
+            assert_eq!(check_word(word), expected, "{}", word);
+        }
+        // Two tone marks are never a syllable
+        assert!(!is_known_syllable("áà"));
+        assert!(is_known_syllable("đường"));
+        assert!(!is_known_syllable("duong"));
+    }
+
+    #[test]
+    fn test_suggest_corrections() {
+        let cases = [
+            // Doubled letter for a missing circumflex beats "khong"
+            ("khongg", "không"),
+            // Stray tone, circumflex missing ("nghiếng" is not a word)
+            ("nghíeng", "nghiêng"),
+            ("nghieng", "nghiêng"),
+            ("Nguoi", "Nguôi"),
+            ("ĐUỜNG", "ĐƯỜNG"),
+            ("tiéng", "tiếng"),
+        ];
+        for (word, best) in cases {
+            let suggestions = suggest_corrections(word);
+            assert_eq!(
+                suggestions.first().map(String::as_str),
+                Some(best),
+                "{}",
+                word
+            );
+            assert!(suggestions.len() <= MAX_SUGGESTIONS);
+            assert_eq!(suggestions, suggest_corrections(word));
+        }
+        assert!(suggest_corrections("Nguoi").contains(&"Ngươi".to_string()));
+        assert!(suggest_corrections("việt").is_empty());
+        assert!(suggest_corrections("viet1").is_empty());
+        assert!(suggest_corrections("").is_empty());
+    }
+}
@@ -0,0 +1,238 @@
+//! Personal Dictionary
+//!
+//! User word lists for English auto-restore, on top of the built-in
+//! `english_dict` and `telex_doubles`:
+//! - English: words restored to what was typed on commit ("gitlab",
+//!   "kubectl", company names), even with auto-restore off
+//! - Vietnamese: words never restored, for Vietnamese the heuristics
+//!   wrongly revert
+//!
+//! Matching ignores case. File format, `#` starts a comment line:
+//! ```text
+//! [english]
+//! samsung
+//! [vietnamese]
+//! pác
+//! ```
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::Path;
+
+/// One of the two word lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum UserList {
+    /// Restore to the typed keys
+    English = 0,
+    /// Never restore
+    Vietnamese = 1,
+}
+
+impl UserList {
+    /// List from its FFI id (see `ime_user_word_add`)
+    pub fn from_u8(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::English),
+            1 => Some(Self::Vietnamese),
+            _ => None,
+        }
+    }
+
+    fn section(self) -> &'static str {
+        match self {
+            Self::English => "[english]",
+            Self::Vietnamese => "[vietnamese]",
+        }
+    }
+}
+
+/// Error loading or saving a dictionary file
+#[derive(Debug, Clone, PartialEq)]
+pub enum UserDictError {
+    /// Word outside a section, unknown section, or word with spaces
+    /// (1-based line number)
+    InvalidLine(usize),
+    /// Reading or writing the file failed
+    Io(String),
+}
+
+impl fmt::Display for UserDictError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLine(n) => write!(f, "line {}: expected [section] or a word", n),
+            Self::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for UserDictError {}
+
+/// English and Vietnamese word lists, stored lowercase
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UserDictionary {
+    english: BTreeSet<String>,
+    vietnamese: BTreeSet<String>,
+}
+
+impl UserDictionary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn list(&self, list: UserList) -> &BTreeSet<String> {
+        match list {
+            UserList::English => &self.english,
+            UserList::Vietnamese => &self.vietnamese,
+        }
+    }
+
+    fn list_mut(&mut self, list: UserList) -> &mut BTreeSet<String> {
+        match list {
+            UserList::English => &mut self.english,
+            UserList::Vietnamese => &mut self.vietnamese,
+        }
+    }
+
+    /// Add a word, removing it from the other list
+    ///
+    /// Returns false for empty words and words with whitespace.
+    pub fn add(&mut self, list: UserList, word: &str) -> bool {
+        let word = word.trim().to_lowercase();
+        if word.is_empty() || word.contains(char::is_whitespace) {
+            return false;
+        }
+        let other = match list {
+            UserList::English => UserList::Vietnamese,
+            UserList::Vietnamese => UserList::English,
+        };
+        self.list_mut(other).remove(&word);
+        self.list_mut(list).insert(word);
+        true
+    }
+
+    /// Remove a word; returns whether it was in the list
+    pub fn remove(&mut self, list: UserList, word: &str) -> bool {
+        self.list_mut(list).remove(&word.trim().to_lowercase())
+    }
+
+    pub fn contains(&self, list: UserList, word: &str) -> bool {
+        let set = self.list(list);
+        !set.is_empty() && set.contains(&word.to_lowercase())
+    }
+
+    /// Words of a list in alphabetical order
+    pub fn words(&self, list: UserList) -> impl Iterator<Item = &str> {
+        self.list(list).iter().map(String::as_str)
+    }
+
+    pub fn clear(&mut self, list: UserList) {
+        self.list_mut(list).clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.english.is_empty() && self.vietnamese.is_empty()
+    }
+
+    /// Parse a dictionary file (see module docs)
+    pub fn from_text(text: &str) -> Result<Self, UserDictError> {
+        let mut dict = Self::new();
+        let mut section = None;
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = UserDictError::InvalidLine(i + 1);
+            if line.starts_with('[') {
+                section = [UserList::English, UserList::Vietnamese]
+                    .into_iter()
+                    .find(|l| line.eq_ignore_ascii_case(l.section()));
+                if section.is_none() {
+                    return Err(invalid);
+                }
+            } else if !section.is_some_and(|list| dict.add(list, line)) {
+                return Err(invalid);
+            }
+        }
+        Ok(dict)
+    }
+
+    /// Dictionary file text
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for list in [UserList::English, UserList::Vietnamese] {
+            text.push_str(list.section());
+            text.push('\n');
+            for word in self.words(list) {
+                text.push_str(word);
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    /// Load a dictionary file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, UserDictError> {
+        let text = std::fs::read_to_string(path).map_err(|e| UserDictError::Io(e.to_string()))?;
+        Self::from_text(&text)
+    }
+
+    /// Save as a dictionary file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), UserDictError> {
+        std::fs::write(path, self.to_text()).map_err(|e| UserDictError::Io(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_remove() {
+        let mut d = UserDictionary::new();
+        assert!(d.add(UserList::English, " GitLab "));
+        assert!(d.add(UserList::English, "kubectl"));
+        assert!(!d.add(UserList::English, "two words"));
+        assert!(!d.add(UserList::English, ""));
+        assert!(d.contains(UserList::English, "gitlab"));
+        assert!(d.contains(UserList::English, "GITLAB"));
+        assert_eq!(
+            d.words(UserList::English).collect::<Vec<_>>(),
+            ["gitlab", "kubectl"]
+        );
+
+        // A word lives in one list only
+        assert!(d.add(UserList::Vietnamese, "kubectl"));
+        assert!(!d.contains(UserList::English, "kubectl"));
+        assert!(d.remove(UserList::Vietnamese, "Kubectl"));
+        assert!(!d.remove(UserList::Vietnamese, "kubectl"));
+
+        d.clear(UserList::English);
+        assert!(d.is_empty());
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let text = "\u{feff}# mine\n[English]\nDior\n\n[vietnamese]\nmít\n";
+        let d = UserDictionary::from_text(text).unwrap();
+        assert_eq!(d.to_text(), "[english]\ndior\n[vietnamese]\nmít\n");
+        assert_eq!(UserDictionary::from_text(&d.to_text()).unwrap(), d);
+
+        for (bad, line) in [
+            ("dior", 1),
+            ("[english]\n[french]", 2),
+            ("[english]\na b", 2),
+        ] {
+            assert_eq!(
+                UserDictionary::from_text(bad).unwrap_err(),
+                UserDictError::InvalidLine(line)
+            );
+        }
+        assert!(matches!(
+            UserDictionary::load("/nonexistent/words.txt"),
+            Err(UserDictError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,307 @@
+//! Diacritic Folding
+//!
+//! Strips Vietnamese diacritics for slugs and search indexing:
+//! "Tiếng Việt" → "Tieng Viet". Uses `data::chars::parse_char`, so every
+//! precomposed Vietnamese letter folds to its base; other text is untouched.
+//! The combining diacritics of decomposed text (NFD or Windows-1258 style,
+//! see `charset::Form`) are dropped after Vietnamese vowels; elsewhere
+//! ("n\u{303}" = ñ, Greek, Cyrillic) they are kept.
+
+use crate::data::{chars, keys};
+use crate::utils::key_to_char;
+use std::borrow::Cow;
+
+/// Folding options
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FoldOptions {
+    /// Keep đ/Đ instead of folding to d/D
+    pub keep_d: bool,
+    /// Keep letter case; when false, output is lowercase
+    pub preserve_case: bool,
+}
+
+impl Default for FoldOptions {
+    fn default() -> Self {
+        Self {
+            keep_d: false,
+            preserve_case: true,
+        }
+    }
+}
+
+/// Combining tone marks (grave, acute, tilde, hook, dot below) and vowel
+/// marks (circumflex, breve, horn) used by Vietnamese
+const COMBINING_MARKS: [char; 8] = [
+    '\u{300}', '\u{301}', '\u{303}', '\u{309}', '\u{323}', '\u{302}', '\u{306}', '\u{31B}',
+];
+
+fn is_combining_mark(c: char) -> bool {
+    COMBINING_MARKS.contains(&c)
+}
+
+/// Whether combining marks after `c` are Vietnamese diacritics
+fn takes_marks(c: char) -> bool {
+    chars::parse_char(c).is_some_and(|p| keys::is_vowel(p.key))
+}
+
+/// ASCII base of a Vietnamese letter, or None if `c` stays as is
+fn base_letter(c: char, options: &FoldOptions) -> Option<char> {
+    if c.is_ascii() {
+        return None;
+    }
+    let p = chars::parse_char(c)?;
+    if p.stroke && options.keep_d {
+        return None;
+    }
+    key_to_char(p.key, p.caps)
+}
+
+/// Fold one character into `out`
+fn fold_into(out: &mut String, c: char, options: &FoldOptions) {
+    let folded = base_letter(c, options).unwrap_or(c);
+    if options.preserve_case {
+        out.push(folded);
+    } else {
+        out.extend(folded.to_lowercase());
+    }
+}
+
+/// Chars of `s` that folding keeps, i.e. all but combining marks after a
+/// Vietnamese vowel
+fn kept_chars(s: &str) -> impl Iterator<Item = char> + '_ {
+    let mut after_vowel = false;
+    s.chars().filter(move |&c| {
+        if is_combining_mark(c) {
+            return !after_vowel;
+        }
+        after_vowel = takes_marks(c);
+        true
+    })
+}
+
+/// Whether folding changes `s` (lets the streaming variant borrow)
+fn needs_fold(s: &str, options: &FoldOptions) -> bool {
+    kept_chars(s).count() != s.chars().count()
+        || s.chars().any(|c| {
+            (!options.preserve_case && c.is_uppercase()) || base_letter(c, options).is_some()
+        })
+}
+
+/// Remove Vietnamese diacritics
+///
+/// # Example
+/// ```
+/// use gonhanh_core::fold::{fold, FoldOptions};
+/// assert_eq!(fold("Tiếng Việt", &FoldOptions::default()), "Tieng Viet");
+/// let search = FoldOptions { keep_d: true, preserve_case: false };
+/// assert_eq!(fold("Đường Phố", &search), "đuong pho");
+/// ```
+pub fn fold(text: &str, options: &FoldOptions) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in kept_chars(text) {
+        fold_into(&mut out, c, options);
+    }
+    out
+}
+
+/// Streaming fold over string chunks (lines, tokens, reader output)
+///
+/// Chunks that need no change are yielded borrowed.
+pub struct Fold<I> {
+    iter: I,
+    options: FoldOptions,
+}
+
+impl<'a, I: Iterator<Item = &'a str>> Iterator for Fold<I> {
+    type Item = Cow<'a, str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let s = self.iter.next()?;
+        if needs_fold(s, &self.options) {
+            Some(Cow::Owned(fold(s, &self.options)))
+        } else {
+            Some(Cow::Borrowed(s))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Fold each chunk of an iterator of `&str`
+///
+/// # Example
+/// ```
+/// use gonhanh_core::fold::{fold_iter, FoldOptions};
+/// let text = "Xin chào\nViệt Nam";
+/// let lines: Vec<_> = fold_iter(text.lines(), FoldOptions::default()).collect();
+/// assert_eq!(lines, ["Xin chao", "Viet Nam"]);
+/// ```
+pub fn fold_iter<'a, I>(iter: I, options: FoldOptions) -> Fold<I::IntoIter>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    Fold {
+        iter: iter.into_iter(),
+        options,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::chars::{mark, to_char, tone};
+
+    const VOWELS: [(u16, char); 6] = [
+        (keys::A, 'a'),
+        (keys::E, 'e'),
+        (keys::I, 'i'),
+        (keys::O, 'o'),
+        (keys::U, 'u'),
+        (keys::Y, 'y'),
+    ];
+
+    /// Every vowel form (12 vowels × 6 marks × 2 cases) with its ASCII base
+    fn vowel_forms() -> Vec<(char, char)> {
+        let mut out = Vec::new();
+        for (key, base) in VOWELS {
+            for t in [tone::NONE, tone::CIRCUMFLEX, tone::HORN] {
+                for m in mark::NONE..=mark::NANG {
+                    for caps in [false, true] {
+                        // to_char ignores tones a vowel can't take (i + circumflex)
+                        let Some(c) = to_char(key, caps, t, m) else {
+                            continue;
+                        };
+                        if chars::parse_char(c).is_some_and(|p| p.tone == t) {
+                            let b = if caps {
+                                base.to_ascii_uppercase()
+                            } else {
+                                base
+                            };
+                            out.push((c, b));
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_all_vowel_forms() {
+        let forms = vowel_forms();
+        // 132 accented vowels (66 per case); đ/Đ is covered by test_d
+        assert_eq!(forms.iter().filter(|(c, _)| !c.is_ascii()).count(), 132);
+        for (c, base) in forms {
+            let s = c.to_string();
+            let lower = base.to_ascii_lowercase().to_string();
+            assert_eq!(fold(&s, &FoldOptions::default()), base.to_string(), "{}", c);
+            let no_case = FoldOptions {
+                preserve_case: false,
+                ..Default::default()
+            };
+            assert_eq!(fold(&s, &no_case), lower, "{}", c);
+            let keep_d = FoldOptions {
+                keep_d: true,
+                ..Default::default()
+            };
+            assert_eq!(fold(&s, &keep_d), base.to_string(), "{}", c);
+        }
+    }
+
+    #[test]
+    fn test_d() {
+        let keep = FoldOptions {
+            keep_d: true,
+            ..Default::default()
+        };
+        let lower = FoldOptions {
+            keep_d: true,
+            preserve_case: false,
+        };
+        assert_eq!(fold("đĐ", &FoldOptions::default()), "dD");
+        assert_eq!(fold("đĐ", &keep), "đĐ");
+        assert_eq!(fold("đĐ", &lower), "đđ");
+    }
+
+    #[test]
+    fn test_sentences() {
+        let cases = [
+            ("Tiếng Việt", "Tieng Viet"),
+            ("Đường Nguyễn Thị Minh Khai", "Duong Nguyen Thi Minh Khai"),
+            ("ỨNG DỤNG GÕ NHANH", "UNG DUNG GO NHANH"),
+            ("café 日本 😀", "cafe 日本 😀"),
+            ("", ""),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(fold(input, &FoldOptions::default()), expected);
+        }
+    }
+
+    #[test]
+    fn test_decomposed() {
+        let cases = [
+            // NFD
+            ("Vie\u{323}\u{302}t", "Viet"),
+            ("đu\u{31B}o\u{31B}\u{300}ng", "duong"),
+            ("A\u{302}\u{301}U", "AU"),
+            ("nghi\u{303}a ca\u{309}", "nghia ca"),
+            ("a\u{323}\u{306}", "a"),
+            // Windows-1258 style
+            ("Viê\u{323}t Nam", "Viet Nam"),
+            ("Ă\u{323}n ơ\u{301}t", "An ot"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                fold(input, &FoldOptions::default()),
+                expected,
+                "{:?}",
+                input
+            );
+        }
+        let keep_d = FoldOptions {
+            keep_d: true,
+            preserve_case: false,
+        };
+        assert_eq!(fold("Đu\u{31B}o\u{31B}\u{300}ng", &keep_d), "đuong");
+        // Marks after other bases are not Vietnamese diacritics
+        let kept = [
+            "n\u{303}o",
+            "Espan\u{303}a",
+            "c\u{301}",
+            "ε\u{301}",
+            "и\u{306}",
+            "\u{301}a",
+        ];
+        for input in kept {
+            assert_eq!(fold(input, &FoldOptions::default()), input, "{:?}", input);
+        }
+        assert_eq!(
+            fold("Vie\u{323}\u{302}t n\u{303}", &FoldOptions::default()),
+            "Viet n\u{303}"
+        );
+        let chunks: Vec<_> =
+            fold_iter(["Vie\u{323}\u{302}t", "Nam"], FoldOptions::default()).collect();
+        assert_eq!(chunks, ["Viet", "Nam"]);
+        assert!(matches!(chunks[0], Cow::Owned(_)));
+    }
+
+    #[test]
+    fn test_fold_iter() {
+        let text = "Chào buổi sáng, Hà Nội! hello world";
+        let options = FoldOptions::default();
+        let chunks: Vec<_> = fold_iter(text.split(' '), options).collect();
+        assert_eq!(chunks.join(" "), fold(text, &options));
+        assert!(matches!(chunks[0], Cow::Owned(_)));
+        assert!(matches!(chunks[5], Cow::Borrowed("hello")));
+
+        let lower = FoldOptions {
+            preserve_case: false,
+            ..Default::default()
+        };
+        let words: Vec<_> = fold_iter(["Hà", "nội", "abc"], lower).collect();
+        assert_eq!(words, ["ha", "noi", "abc"]);
+        assert!(matches!(words[2], Cow::Borrowed(_)));
+    }
+}
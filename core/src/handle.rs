@@ -0,0 +1,820 @@
+//! Handle-based FFI
+//!
+//! One engine per `ime_create` handle, so a host can run several engines
+//! with different settings (one per document or text field). Every
+//! `ime_engine_*` function returns an `ImeStatus` code and writes results
+//! to caller-provided memory; nothing needs `ime_free`.
+//!
+//! ```c
+//! if (ime_abi_version() != 1) { /* incompatible core */ }
+//! ImeEngine* h = ime_create();
+//! ime_engine_method(h, 1);  // VNI for this document only
+//! ImeResult r;
+//! if (ime_engine_key_ext(h, keycode, caps, ctrl, shift, &r) == 0 && r.action == 1) {
+//!     // Send r.backspace deletes, then r.chars
+//! }
+//! ime_destroy(h);
+//! ```
+//!
+//! Handles are thread-safe (each has its own mutex); the global `ime_*`
+//! functions share the same operations on the `ime_init` engine.
+
+use crate::engine::config::FfiEngineConfig;
+use crate::engine::shortcut::Shortcut;
+use crate::engine::user_dict::{UserDictionary, UserList};
+use crate::engine::{self, Engine, EngineConfig};
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::sync::{Mutex, MutexGuard};
+
+/// Version of the exported functions and `#[repr(C)]` structs; bumped on
+/// any incompatible change
+pub const ABI_VERSION: u32 = 1;
+
+/// Status code returned by `ime_engine_*` functions
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImeStatus {
+    Ok = 0,
+    /// Handle is null
+    NullHandle = -1,
+    /// A required pointer argument is null
+    NullPointer = -2,
+    /// String argument is not valid UTF-8
+    InvalidUtf8 = -3,
+    /// Unknown id or rejected value (e.g. a list id, an empty word)
+    InvalidArgument = -4,
+    /// A file could not be read, parsed or written
+    Io = -5,
+    /// Output did not fit; the full size is still reported
+    BufferTooSmall = -6,
+    /// Nothing to remove
+    NotFound = -7,
+}
+
+/// Engine behind an `ime_create` handle
+pub struct ImeEngine {
+    engine: Mutex<Engine>,
+}
+
+impl ImeEngine {
+    fn lock(&self) -> MutexGuard<'_, Engine> {
+        self.engine.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+type Status = std::result::Result<(), ImeStatus>;
+
+// ============================================================
+// Shared operations (also used by the global functions)
+// ============================================================
+
+/// Borrow a C string argument
+///
+/// # Safety
+/// `s` must be null or a valid null-terminated string.
+pub(crate) unsafe fn c_str<'a>(s: *const c_char) -> std::result::Result<&'a str, ImeStatus> {
+    if s.is_null() {
+        return Err(ImeStatus::NullPointer);
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|_| ImeStatus::InvalidUtf8)
+}
+
+/// Split a comma-separated tag list, dropping empty tags
+pub(crate) fn split_tags(tags: &str) -> Vec<&str> {
+    tags.split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+pub(crate) fn add_shortcut(e: &mut Engine, trigger: &str, replacement: &str, contexts: &[&str]) {
+    // Auto-detect shortcut type:
+    // - If trigger contains only non-letter chars (like "->", "=>"), use immediate trigger
+    // - Otherwise use word boundary trigger (traditional abbreviations like "vn" → "Việt Nam")
+    let is_symbol_trigger = trigger.chars().all(|c| !c.is_alphabetic());
+    let shortcut = if is_symbol_trigger {
+        Shortcut::immediate(trigger, replacement)
+    } else {
+        Shortcut::new(trigger, replacement)
+    };
+    e.shortcuts_mut().add(shortcut.in_contexts(contexts));
+}
+
+pub(crate) fn user_list(list: u8) -> std::result::Result<UserList, ImeStatus> {
+    UserList::from_u8(list).ok_or(ImeStatus::InvalidArgument)
+}
+
+/// Words of a personal list, one per line
+pub(crate) fn user_words_text(e: &Engine, list: UserList) -> String {
+    e.user_dict().words(list).flat_map(|w| [w, "\n"]).collect()
+}
+
+pub(crate) fn load_user_dict(e: &mut Engine, path: &str) -> Status {
+    *e.user_dict_mut() = UserDictionary::load(path).map_err(|_| ImeStatus::Io)?;
+    Ok(())
+}
+
+/// Copy as much of `data` as fits; returns whether all of it did
+///
+/// # Safety
+/// `out` must be null or point to writable memory for `max_len` items.
+pub(crate) unsafe fn copy_out<T: Copy>(data: &[T], out: *mut T, max_len: i64) -> bool {
+    let max_len = max_len.max(0) as usize;
+    if !out.is_null() {
+        std::ptr::copy_nonoverlapping(data.as_ptr(), out, data.len().min(max_len));
+    }
+    data.len() <= max_len
+}
+
+// ============================================================
+// Handle FFI
+// ============================================================
+
+/// Run `f` on the handle's engine and turn its outcome into a status code
+///
+/// # Safety
+/// `h` must be null or a live handle from `ime_create`.
+unsafe fn with_handle(h: *const ImeEngine, f: impl FnOnce(&mut Engine) -> Status) -> i32 {
+    let Some(h) = h.as_ref() else {
+        return ImeStatus::NullHandle as i32;
+    };
+    let status = f(&mut h.lock());
+    status.err().unwrap_or(ImeStatus::Ok) as i32
+}
+
+/// Run `f` on the handle's engine and write its result to `out`
+///
+/// # Safety
+/// `h` must be null or a live handle, `out` null or writable for one
+/// `Result`.
+unsafe fn with_result(
+    h: *const ImeEngine,
+    out: *mut engine::Result,
+    f: impl FnOnce(&mut Engine) -> engine::Result,
+) -> i32 {
+    with_handle(h, |e| {
+        if out.is_null() {
+            return Err(ImeStatus::NullPointer);
+        }
+        std::ptr::write(out, f(e));
+        Ok(())
+    })
+}
+
+/// ABI version of this library (see `ABI_VERSION`).
+///
+/// Frontends should refuse to run when it differs from the version they
+/// were built against.
+#[no_mangle]
+pub extern "C" fn ime_abi_version() -> u32 {
+    ABI_VERSION
+}
+
+/// Create an engine with default options and shortcuts.
+///
+/// # Returns
+/// A handle to pass to `ime_engine_*` functions; free it with `ime_destroy`.
+#[no_mangle]
+pub extern "C" fn ime_create() -> *mut ImeEngine {
+    Box::into_raw(Box::new(ImeEngine {
+        engine: Mutex::new(Engine::new()),
+    }))
+}
+
+/// Destroy a handle from `ime_create` (null is ignored).
+///
+/// # Safety
+/// `h` must be null or a live handle; do not use it afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn ime_destroy(h: *mut ImeEngine) {
+    if !h.is_null() {
+        drop(Box::from_raw(h));
+    }
+}
+
+/// Process a key event (see `ime_key_ext` for the arguments).
+///
+/// # Safety
+/// `h` must be null or a live handle, `out` null or writable for one
+/// `Result`.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_key_ext(
+    h: *const ImeEngine,
+    key: u16,
+    caps: bool,
+    ctrl: bool,
+    shift: bool,
+    out: *mut engine::Result,
+) -> i32 {
+    with_result(h, out, |e| e.on_key_ext(key, caps, ctrl, shift))
+}
+
+/// Process a typed character (see `ime_char`).
+///
+/// Returns `InvalidArgument` when `ch` is not a Unicode scalar value.
+///
+/// # Safety
+/// Same as `ime_engine_key_ext`.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_char(
+    h: *const ImeEngine,
+    ch: u32,
+    modifiers: u8,
+    out: *mut engine::Result,
+) -> i32 {
+    with_handle(h, |e| {
+        let ch = char::from_u32(ch).ok_or(ImeStatus::InvalidArgument)?;
+        if out.is_null() {
+            return Err(ImeStatus::NullPointer);
+        }
+        std::ptr::write(out, e.on_char(ch, modifiers));
+        Ok(())
+    })
+}
+
+/// Fetch the next chunk of a long output (see `ime_next_chunk`).
+///
+/// # Safety
+/// Same as `ime_engine_key_ext`.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_next_chunk(
+    h: *const ImeEngine,
+    out: *mut engine::Result,
+) -> i32 {
+    with_result(h, out, |e| e.next_chunk())
+}
+
+/// Revert the last IME rewrite (see `ime_undo`).
+///
+/// # Safety
+/// Same as `ime_engine_key_ext`.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_undo(h: *const ImeEngine, out: *mut engine::Result) -> i32 {
+    with_result(h, out, |e| e.undo_last_transform())
+}
+
+/// Re-apply the rewrite reverted by the last undo (see `ime_redo`).
+///
+/// # Safety
+/// Same as `ime_engine_key_ext`.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_redo(h: *const ImeEngine, out: *mut engine::Result) -> i32 {
+    with_result(h, out, |e| e.redo_last_transform())
+}
+
+/// Set the input method (see `ime_method`).
+///
+/// # Safety
+/// `h` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_method(h: *const ImeEngine, method: u8) -> i32 {
+    with_handle(h, |e| {
+        e.set_method(method);
+        Ok(())
+    })
+}
+
+/// Enable or disable Vietnamese transforms.
+///
+/// # Safety
+/// `h` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_enabled(h: *const ImeEngine, enabled: bool) -> i32 {
+    with_handle(h, |e| {
+        e.set_enabled(enabled);
+        Ok(())
+    })
+}
+
+/// Apply all engine options at once (see `ime_apply_config`).
+///
+/// # Safety
+/// `h` must be null or a live handle, `config` null or a valid
+/// `FfiEngineConfig`.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_apply_config(
+    h: *const ImeEngine,
+    config: *const FfiEngineConfig,
+) -> i32 {
+    with_handle(h, |e| {
+        if config.is_null() {
+            return Err(ImeStatus::NullPointer);
+        }
+        e.apply_config(&EngineConfig::from(std::ptr::read_unaligned(config)));
+        Ok(())
+    })
+}
+
+/// Read the current engine options.
+///
+/// # Safety
+/// `h` must be null or a live handle, `out` null or writable for one
+/// `FfiEngineConfig`.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_get_config(
+    h: *const ImeEngine,
+    out: *mut FfiEngineConfig,
+) -> i32 {
+    with_handle(h, |e| {
+        if out.is_null() {
+            return Err(ImeStatus::NullPointer);
+        }
+        std::ptr::write_unaligned(out, e.config().into());
+        Ok(())
+    })
+}
+
+/// Clear the current word, keeping word history (see `ime_clear`).
+///
+/// # Safety
+/// `h` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_clear(h: *const ImeEngine) -> i32 {
+    with_handle(h, |e| {
+        e.clear();
+        Ok(())
+    })
+}
+
+/// Clear everything including word history (see `ime_clear_all`).
+///
+/// # Safety
+/// `h` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_clear_all(h: *const ImeEngine) -> i32 {
+    with_handle(h, |e| {
+        e.clear_all();
+        Ok(())
+    })
+}
+
+/// Get the composed word as UTF-32 codepoints.
+///
+/// Writes up to `max_len` codepoints to `out` and the full length to `len`
+/// (if not null); `BufferTooSmall` when they did not all fit.
+///
+/// # Safety
+/// `h` must be null or a live handle, `out` null or writable for `max_len`
+/// codepoints, `len` null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_get_buffer(
+    h: *const ImeEngine,
+    out: *mut u32,
+    max_len: i64,
+    len: *mut i64,
+) -> i32 {
+    with_handle(h, |e| {
+        let utf32: Vec<u32> = e.get_buffer_string().chars().map(|c| c as u32).collect();
+        if let Some(len) = len.as_mut() {
+            *len = utf32.len() as i64;
+        }
+        if copy_out(&utf32, out, max_len) {
+            Ok(())
+        } else {
+            Err(ImeStatus::BufferTooSmall)
+        }
+    })
+}
+
+/// Restore the current word from text (see `ime_restore_word`).
+///
+/// # Safety
+/// `h` must be null or a live handle, `word` a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_restore_word(h: *const ImeEngine, word: *const c_char) -> i32 {
+    with_handle(h, |e| {
+        e.restore_word(c_str(word)?);
+        Ok(())
+    })
+}
+
+/// Resynchronize with the text around the caret (see
+/// `ime_set_surrounding_text`).
+///
+/// # Safety
+/// `h` must be null or a live handle, `text` a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_set_surrounding_text(
+    h: *const ImeEngine,
+    text: *const c_char,
+    cursor: u32,
+) -> i32 {
+    with_handle(h, |e| {
+        e.set_surrounding_text(c_str(text)?, cursor as usize);
+        Ok(())
+    })
+}
+
+/// Add a shortcut (see `ime_add_shortcut`).
+///
+/// # Safety
+/// `h` must be null or a live handle, strings valid C strings.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_add_shortcut(
+    h: *const ImeEngine,
+    trigger: *const c_char,
+    replacement: *const c_char,
+) -> i32 {
+    with_handle(h, |e| {
+        add_shortcut(e, c_str(trigger)?, c_str(replacement)?, &[]);
+        Ok(())
+    })
+}
+
+/// Add a shortcut scoped to comma-separated context tags (see
+/// `ime_add_shortcut_in_context`).
+///
+/// # Safety
+/// `h` must be null or a live handle, strings valid C strings.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_add_shortcut_in_context(
+    h: *const ImeEngine,
+    trigger: *const c_char,
+    replacement: *const c_char,
+    contexts: *const c_char,
+) -> i32 {
+    with_handle(h, |e| {
+        let contexts = split_tags(c_str(contexts)?);
+        add_shortcut(e, c_str(trigger)?, c_str(replacement)?, &contexts);
+        Ok(())
+    })
+}
+
+/// Remove a shortcut; `NotFound` if no shortcut has this trigger.
+///
+/// # Safety
+/// `h` must be null or a live handle, `trigger` a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_remove_shortcut(
+    h: *const ImeEngine,
+    trigger: *const c_char,
+) -> i32 {
+    with_handle(h, |e| {
+        e.shortcuts_mut()
+            .remove(c_str(trigger)?)
+            .map(|_| ())
+            .ok_or(ImeStatus::NotFound)
+    })
+}
+
+/// Remove all shortcuts.
+///
+/// # Safety
+/// `h` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_clear_shortcuts(h: *const ImeEngine) -> i32 {
+    with_handle(h, |e| {
+        e.shortcuts_mut().clear();
+        Ok(())
+    })
+}
+
+/// Set comma-separated context tags for scoped shortcuts (null clears).
+///
+/// # Safety
+/// `h` must be null or a live handle, `tags` null or a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_set_context(h: *const ImeEngine, tags: *const c_char) -> i32 {
+    with_handle(h, |e| {
+        let tags = if tags.is_null() { "" } else { c_str(tags)? };
+        e.set_context(&split_tags(tags));
+        Ok(())
+    })
+}
+
+/// Set the UTC offset of shortcut dates and times (see `ime_set_utc_offset`).
+///
+/// Returns `InvalidArgument` for offsets beyond ±14 hours.
+///
+/// # Safety
+/// `h` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_set_utc_offset(h: *const ImeEngine, minutes: i32) -> i32 {
+    with_handle(h, |e| {
+        if e.shortcuts_mut().set_utc_offset(minutes) {
+            Ok(())
+        } else {
+            Err(ImeStatus::InvalidArgument)
+        }
+    })
+}
+
+/// Add a word to a personal list (see `ime_user_word_add`).
+///
+/// # Safety
+/// `h` must be null or a live handle, `word` a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_user_word_add(
+    h: *const ImeEngine,
+    list: u8,
+    word: *const c_char,
+) -> i32 {
+    with_handle(h, |e| {
+        let (list, word) = (user_list(list)?, c_str(word)?);
+        if e.user_dict_mut().add(list, word) {
+            Ok(())
+        } else {
+            Err(ImeStatus::InvalidArgument)
+        }
+    })
+}
+
+/// Remove a word from a personal list; `NotFound` if it was not there.
+///
+/// # Safety
+/// `h` must be null or a live handle, `word` a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_user_word_remove(
+    h: *const ImeEngine,
+    list: u8,
+    word: *const c_char,
+) -> i32 {
+    with_handle(h, |e| {
+        let (list, word) = (user_list(list)?, c_str(word)?);
+        if e.user_dict_mut().remove(list, word) {
+            Ok(())
+        } else {
+            Err(ImeStatus::NotFound)
+        }
+    })
+}
+
+/// Clear a personal list (0=English, 1=Vietnamese).
+///
+/// # Safety
+/// `h` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_user_words_clear(h: *const ImeEngine, list: u8) -> i32 {
+    with_handle(h, |e| {
+        e.user_dict_mut().clear(user_list(list)?);
+        Ok(())
+    })
+}
+
+/// Get the words of a personal list as UTF-8, one per line.
+///
+/// Writes up to `max_len` bytes to `out` and the full length to `len` (if
+/// not null); `BufferTooSmall` when they did not all fit.
+///
+/// # Safety
+/// `h` must be null or a live handle, `out` null or writable for `max_len`
+/// bytes, `len` null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_user_words(
+    h: *const ImeEngine,
+    list: u8,
+    out: *mut u8,
+    max_len: i64,
+    len: *mut i64,
+) -> i32 {
+    with_handle(h, |e| {
+        let text = user_words_text(e, user_list(list)?);
+        if let Some(len) = len.as_mut() {
+            *len = text.len() as i64;
+        }
+        if copy_out(text.as_bytes(), out, max_len) {
+            Ok(())
+        } else {
+            Err(ImeStatus::BufferTooSmall)
+        }
+    })
+}
+
+/// Replace both personal lists with a dictionary file.
+///
+/// # Safety
+/// `h` must be null or a live handle, `path` a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_user_dict_load(
+    h: *const ImeEngine,
+    path: *const c_char,
+) -> i32 {
+    with_handle(h, |e| load_user_dict(e, c_str(path)?))
+}
+
+/// Save both personal lists to a dictionary file.
+///
+/// # Safety
+/// `h` must be null or a live handle, `path` a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_engine_user_dict_save(
+    h: *const ImeEngine,
+    path: *const c_char,
+) -> i32 {
+    with_handle(h, |e| {
+        e.user_dict().save(c_str(path)?).map_err(|_| ImeStatus::Io)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::keys;
+    use std::ffi::CString;
+    use std::mem::MaybeUninit;
+
+    fn key(h: *const ImeEngine, key: u16) -> engine::Result {
+        let mut r = MaybeUninit::uninit();
+        unsafe {
+            assert_eq!(
+                ime_engine_key_ext(h, key, false, false, false, r.as_mut_ptr()),
+                0
+            );
+            r.assume_init()
+        }
+    }
+
+    #[test]
+    fn test_independent_engines() {
+        assert_eq!(ime_abi_version(), ABI_VERSION);
+        let telex = ime_create();
+        let vni = ime_create();
+        unsafe { assert_eq!(ime_engine_method(vni, 1), 0) };
+
+        key(telex, keys::A);
+        let r = key(telex, keys::S);
+        assert_eq!((r.action, r.chars[0]), (1, 'á' as u32));
+        key(vni, keys::A);
+        let r = key(vni, keys::N1);
+        assert_eq!((r.action, r.chars[0]), (1, 'á' as u32));
+
+        // Telex keeps its own word and options
+        let mut buf = [0u32; 4];
+        let mut len = 0;
+        unsafe {
+            assert_eq!(
+                ime_engine_get_buffer(telex, buf.as_mut_ptr(), 4, &mut len),
+                0
+            );
+            assert_eq!((len, buf[0]), (1, 'á' as u32));
+            let mut config = MaybeUninit::uninit();
+            assert_eq!(ime_engine_get_config(telex, config.as_mut_ptr()), 0);
+            assert_eq!(EngineConfig::from(config.assume_init()).method, 0);
+            ime_destroy(telex);
+            ime_destroy(vni);
+        }
+    }
+
+    #[test]
+    fn test_status_codes() {
+        let h = ime_create();
+        let word = CString::new("vn").unwrap();
+        let bad = [0xffu8, 0];
+        unsafe {
+            assert_eq!(
+                ime_engine_clear(std::ptr::null()),
+                ImeStatus::NullHandle as i32
+            );
+            assert_eq!(
+                ime_engine_key_ext(h, keys::A, false, false, false, std::ptr::null_mut()),
+                ImeStatus::NullPointer as i32
+            );
+            let mut r = MaybeUninit::uninit();
+            assert_eq!(
+                ime_engine_char(h, 0xd800, 0, r.as_mut_ptr()),
+                ImeStatus::InvalidArgument as i32
+            );
+            assert_eq!(
+                ime_engine_restore_word(h, bad.as_ptr() as *const c_char),
+                ImeStatus::InvalidUtf8 as i32
+            );
+            assert_eq!(
+                ime_engine_set_surrounding_text(h, std::ptr::null(), 0),
+                ImeStatus::NullPointer as i32
+            );
+            assert_eq!(
+                ime_engine_user_word_add(h, 9, word.as_ptr()),
+                ImeStatus::InvalidArgument as i32
+            );
+            assert_eq!(
+                ime_engine_remove_shortcut(h, word.as_ptr()),
+                ImeStatus::NotFound as i32
+            );
+            let missing = CString::new("/nonexistent/words.txt").unwrap();
+            assert_eq!(
+                ime_engine_user_dict_load(h, missing.as_ptr()),
+                ImeStatus::Io as i32
+            );
+
+            // Too small: partial copy, full length reported
+            assert_eq!(ime_engine_user_word_add(h, 0, word.as_ptr()), 0);
+            let mut out = [0u8; 1];
+            let mut len = 0;
+            assert_eq!(
+                ime_engine_user_words(h, 0, out.as_mut_ptr(), 1, &mut len),
+                ImeStatus::BufferTooSmall as i32
+            );
+            assert_eq!((out[0], len), (b'v', 3));
+
+            assert_eq!(
+                ime_engine_user_words_clear(h, 9),
+                ImeStatus::InvalidArgument as i32
+            );
+            assert_eq!(ime_engine_user_words_clear(h, 0), 0);
+            assert_eq!(
+                ime_engine_user_words(h, 0, std::ptr::null_mut(), 0, &mut len),
+                0
+            );
+            assert_eq!(len, 0);
+            ime_destroy(h);
+        }
+    }
+
+    /// Global functions that have no `ime_engine_*` twin by design
+    const GLOBAL_ONLY: [&str; 13] = [
+        // ime_create / ime_destroy
+        "init",
+        "free",
+        // ime_engine_key_ext
+        "key",
+        // Option setters: ime_engine_apply_config
+        "skip_w_shortcut",
+        "bracket_shortcut",
+        "esc_restore",
+        "free_tone",
+        "modern",
+        "english_auto_restore",
+        "spell_check",
+        "auto_capitalize",
+        "charset",
+        "output_form",
+    ];
+
+    #[test]
+    fn test_every_global_has_handle_variant() {
+        let globals: Vec<&str> = include_str!("lib.rs")
+            .lines()
+            .filter_map(|line| line.split("extern \"C\" fn ime_").nth(1))
+            .filter_map(|rest| rest.split('(').next())
+            .collect();
+        assert!(globals.len() > 30, "{:?}", globals);
+        let handle = include_str!("handle.rs");
+        let missing: Vec<&&str> = globals
+            .iter()
+            .filter(|name| !GLOBAL_ONLY.contains(name))
+            .filter(|name| !handle.contains(&format!("extern \"C\" fn ime_engine_{}(", name)))
+            .collect();
+        assert!(missing.is_empty(), "no ime_engine_* for {:?}", missing);
+    }
+
+    #[test]
+    fn test_shortcuts_per_engine() {
+        let a = ime_create();
+        let b = ime_create();
+        let trigger = CString::new("zz").unwrap();
+        let replacement = CString::new("xin chào").unwrap();
+        unsafe {
+            assert_eq!(ime_engine_clear_shortcuts(a), 0);
+            assert_eq!(ime_engine_clear_shortcuts(b), 0);
+            assert_eq!(
+                ime_engine_add_shortcut(a, trigger.as_ptr(), replacement.as_ptr()),
+                0
+            );
+        }
+        for h in [a, b] {
+            key(h, keys::Z);
+            key(h, keys::Z);
+        }
+        assert_eq!(key(a, keys::SPACE).action, 1);
+        assert_eq!(key(b, keys::SPACE).action, 0);
+        unsafe {
+            assert_eq!(ime_engine_remove_shortcut(a, trigger.as_ptr()), 0);
+            ime_destroy(a);
+            ime_destroy(b);
+        }
+    }
+
+    #[test]
+    fn test_utc_offset_per_engine() {
+        use crate::engine::shortcut::Shortcut;
+        use crate::utils::is_system_hour;
+
+        let a = ime_create();
+        let b = ime_create();
+        let expand = |h: *mut ImeEngine| {
+            let e = unsafe { &*h }.lock();
+            e.shortcuts().try_match("gio", None, true).unwrap().output
+        };
+        for h in [a, b] {
+            let mut e = unsafe { &*h }.lock();
+            e.shortcuts_mut()
+                .add(Shortcut::new("gio", "{date:%Y-%m-%d %H}"));
+        }
+        unsafe {
+            assert_eq!(ime_engine_set_utc_offset(a, 420), 0);
+            assert_eq!(
+                ime_engine_set_utc_offset(b, -15 * 60),
+                ImeStatus::InvalidArgument as i32
+            );
+        }
+        assert!(is_system_hour(420, || expand(a)));
+        assert!(is_system_hour(0, || expand(b)));
+        unsafe {
+            ime_destroy(a);
+            ime_destroy(b);
+        }
+    }
+}
@@ -0,0 +1,403 @@
+//! Data-Driven Input Method
+//!
+//! Builds a `Method` from a declarative description instead of code, so
+//! layouts like "Simple Telex" or in-house mappings can be added without
+//! forking the crate.
+//!
+//! Description format (JSON, requires the `serde` feature):
+//!
+//! ```json
+//! {
+//!   "name": "simple-telex",
+//!   "family": "telex",
+//!   "marks": { "sac": "s", "huyen": "f", "hoi": "r", "nga": "x", "nang": "j" },
+//!   "tones": [
+//!     { "key": "a", "tone": "circumflex", "targets": "a" },
+//!     { "key": "w", "tone": "horn", "targets": "aou" }
+//!   ],
+//!   "stroke": "d",
+//!   "remove": "z"
+//! }
+//! ```
+//!
+//! Every key field is a string of characters; each character is one key.
+//! `family` picks the engine heuristics the method behaves like (default: telex).
+
+use super::{Family, Method, ToneType};
+use crate::data::{keys, mark};
+use crate::utils::char_to_key;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Keys for each of the five marks (dấu thanh)
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MarkKeys {
+    pub sac: String,
+    pub huyen: String,
+    pub hoi: String,
+    pub nga: String,
+    pub nang: String,
+}
+
+/// A tone key (dấu phụ) and the vowels it applies to
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ToneKey {
+    pub key: String,
+    pub tone: ToneType,
+    pub targets: String,
+}
+
+/// Declarative description of an input method
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MethodSpec {
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub family: Family,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub marks: MarkKeys,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tones: Vec<ToneKey>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub stroke: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub remove: String,
+}
+
+impl MethodSpec {
+    /// Description equivalent to the built-in Telex method
+    pub fn telex() -> Self {
+        Self {
+            name: "telex".to_string(),
+            family: Family::Telex,
+            marks: MarkKeys {
+                sac: "s".to_string(),
+                huyen: "f".to_string(),
+                hoi: "r".to_string(),
+                nga: "x".to_string(),
+                nang: "j".to_string(),
+            },
+            tones: vec![
+                ToneKey::new('a', ToneType::Circumflex, "a"),
+                ToneKey::new('e', ToneType::Circumflex, "e"),
+                ToneKey::new('o', ToneType::Circumflex, "o"),
+                ToneKey::new('w', ToneType::Horn, "aou"),
+            ],
+            stroke: "d".to_string(),
+            remove: "z".to_string(),
+        }
+    }
+
+    /// Description equivalent to the built-in VNI method
+    pub fn vni() -> Self {
+        Self {
+            name: "vni".to_string(),
+            family: Family::Vni,
+            marks: MarkKeys {
+                sac: "1".to_string(),
+                huyen: "2".to_string(),
+                hoi: "3".to_string(),
+                nga: "4".to_string(),
+                nang: "5".to_string(),
+            },
+            tones: vec![
+                ToneKey::new('6', ToneType::Circumflex, "aeo"),
+                ToneKey::new('7', ToneType::Horn, "ou"),
+                ToneKey::new('8', ToneType::Breve, "a"),
+            ],
+            stroke: "9".to_string(),
+            remove: "0".to_string(),
+        }
+    }
+}
+
+impl ToneKey {
+    pub fn new(key: char, tone: ToneType, targets: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            tone,
+            targets: targets.to_string(),
+        }
+    }
+}
+
+/// Error building a method from its description
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpecError {
+    /// Method name is empty
+    EmptyName,
+    /// Character has no key on the layout
+    UnknownKey(char),
+    /// Key is assigned twice (two marks, or two of mark/tone/stroke/remove)
+    DuplicateKey(char),
+    /// Tone key has a non-vowel (or empty) target list
+    InvalidTarget(char),
+    /// Description could not be parsed
+    Parse(String),
+}
+
+impl std::fmt::Display for SpecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpecError::EmptyName => write!(f, "method name is empty"),
+            SpecError::UnknownKey(c) => write!(f, "unknown key '{}'", c),
+            SpecError::DuplicateKey(c) => write!(f, "key '{}' is assigned twice", c),
+            SpecError::InvalidTarget(c) => write!(f, "invalid tone target '{}'", c),
+            SpecError::Parse(msg) => write!(f, "parse error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SpecError {}
+
+/// Input method built from a `MethodSpec`
+#[derive(Debug, Clone)]
+pub struct CustomMethod {
+    name: String,
+    family: Family,
+    marks: Vec<(u16, u8)>,
+    tones: Vec<(u16, ToneType, Vec<u16>)>,
+    stroke: Vec<u16>,
+    remove: Vec<u16>,
+}
+
+fn parse_key(c: char) -> Result<u16, SpecError> {
+    match char_to_key(c) {
+        255 => Err(SpecError::UnknownKey(c)),
+        key => Ok(key),
+    }
+}
+
+impl CustomMethod {
+    /// Build a method from its description
+    pub fn from_spec(spec: &MethodSpec) -> Result<Self, SpecError> {
+        if spec.name.trim().is_empty() {
+            return Err(SpecError::EmptyName);
+        }
+
+        let mark_slots = [
+            (&spec.marks.sac, mark::SAC),
+            (&spec.marks.huyen, mark::HUYEN),
+            (&spec.marks.hoi, mark::HOI),
+            (&spec.marks.nga, mark::NGA),
+            (&spec.marks.nang, mark::NANG),
+        ];
+        // Each key has one role: mark, tone, stroke or remove
+        let mut used: Vec<u16> = Vec::new();
+        let mut claim = |c: char| {
+            let key = parse_key(c)?;
+            if used.contains(&key) {
+                return Err(SpecError::DuplicateKey(c));
+            }
+            used.push(key);
+            Ok(key)
+        };
+
+        let mut marks: Vec<(u16, u8)> = Vec::new();
+        for (chars, value) in mark_slots {
+            for c in chars.chars() {
+                marks.push((claim(c)?, value));
+            }
+        }
+
+        let mut tones: Vec<(u16, ToneType, Vec<u16>)> = Vec::new();
+        for tone_key in &spec.tones {
+            for c in tone_key.key.chars() {
+                let key = claim(c)?;
+                let mut targets = Vec::new();
+                for t in tone_key.targets.chars() {
+                    let target = parse_key(t)?;
+                    if !keys::is_vowel(target) {
+                        return Err(SpecError::InvalidTarget(t));
+                    }
+                    targets.push(target);
+                }
+                if targets.is_empty() {
+                    return Err(SpecError::InvalidTarget(c));
+                }
+                tones.push((key, tone_key.tone, targets));
+            }
+        }
+
+        let stroke = spec
+            .stroke
+            .chars()
+            .map(&mut claim)
+            .collect::<Result<Vec<_>, _>>()?;
+        let remove = spec
+            .remove
+            .chars()
+            .map(&mut claim)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            name: spec.name.trim().to_string(),
+            family: spec.family,
+            marks,
+            tones,
+            stroke,
+            remove,
+        })
+    }
+
+    /// Build a method from a JSON description
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, SpecError> {
+        let spec: MethodSpec =
+            serde_json::from_str(json).map_err(|e| SpecError::Parse(e.to_string()))?;
+        Self::from_spec(&spec)
+    }
+}
+
+impl Method for CustomMethod {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn family(&self) -> Family {
+        self.family
+    }
+
+    fn mark(&self, key: u16) -> Option<u8> {
+        self.marks.iter().find(|&&(k, _)| k == key).map(|&(_, m)| m)
+    }
+
+    fn tone(&self, key: u16) -> Option<ToneType> {
+        self.tones
+            .iter()
+            .find(|(k, _, _)| *k == key)
+            .map(|&(_, t, _)| t)
+    }
+
+    fn tone_targets(&self, key: u16) -> &[u16] {
+        self.tones
+            .iter()
+            .find(|(k, _, _)| *k == key)
+            .map(|(_, _, targets)| targets.as_slice())
+            .unwrap_or(&[])
+    }
+
+    fn stroke(&self, key: u16) -> bool {
+        self.stroke.contains(&key)
+    }
+
+    fn remove(&self, key: u16) -> bool {
+        self.remove.contains(&key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{Telex, Vni, HORN_TARGETS_TELEX};
+
+    const ALL_KEYS: &str = "abcdefghijklmnopqrstuvwxyz0123456789";
+
+    fn assert_same(custom: &CustomMethod, builtin: &dyn Method) {
+        for c in ALL_KEYS.chars() {
+            let key = char_to_key(c);
+            assert_eq!(custom.mark(key), builtin.mark(key), "mark '{}'", c);
+            assert_eq!(custom.tone(key), builtin.tone(key), "tone '{}'", c);
+            assert_eq!(
+                custom.tone_targets(key),
+                builtin.tone_targets(key),
+                "targets '{}'",
+                c
+            );
+            assert_eq!(custom.stroke(key), builtin.stroke(key), "stroke '{}'", c);
+            assert_eq!(custom.remove(key), builtin.remove(key), "remove '{}'", c);
+        }
+        assert_eq!(custom.family(), builtin.family());
+    }
+
+    #[test]
+    fn test_telex_spec_matches_builtin() {
+        let m = CustomMethod::from_spec(&MethodSpec::telex()).unwrap();
+        assert_same(&m, &Telex);
+        assert_eq!(m.tone_targets(keys::W), HORN_TARGETS_TELEX);
+    }
+
+    #[test]
+    fn test_vni_spec_matches_builtin() {
+        let m = CustomMethod::from_spec(&MethodSpec::vni()).unwrap();
+        assert_same(&m, &Vni);
+    }
+
+    #[test]
+    fn test_multiple_keys_per_role() {
+        let mut spec = MethodSpec::telex();
+        spec.name = "dual".to_string();
+        spec.marks.sac = "s1".to_string();
+        let m = CustomMethod::from_spec(&spec).unwrap();
+        assert_eq!(m.mark(keys::S), Some(mark::SAC));
+        assert_eq!(m.mark(keys::N1), Some(mark::SAC));
+    }
+
+    #[test]
+    fn test_invalid_specs() {
+        type Edit = fn(&mut MethodSpec);
+        let cases: [(Edit, SpecError); 9] = [
+            (|s| s.name = " ".to_string(), SpecError::EmptyName),
+            (
+                |s| s.marks.huyen = "s".to_string(),
+                SpecError::DuplicateKey('s'),
+            ),
+            (|s| s.stroke = "đ".to_string(), SpecError::UnknownKey('đ')),
+            (
+                |s| s.tones.push(ToneKey::new('q', ToneType::Horn, "b")),
+                SpecError::InvalidTarget('b'),
+            ),
+            // Stroke and remove keys are checked against every other role
+            (
+                |s| s.marks.nang = "d".to_string(),
+                SpecError::DuplicateKey('d'),
+            ),
+            (|s| s.stroke = "w".to_string(), SpecError::DuplicateKey('w')),
+            (
+                |s| s.stroke = "dd".to_string(),
+                SpecError::DuplicateKey('d'),
+            ),
+            (|s| s.remove = "s".to_string(), SpecError::DuplicateKey('s')),
+            (|s| s.remove = "d".to_string(), SpecError::DuplicateKey('d')),
+        ];
+        for (i, (edit, expected)) in cases.into_iter().enumerate() {
+            let mut spec = MethodSpec::telex();
+            edit(&mut spec);
+            assert_eq!(
+                CustomMethod::from_spec(&spec).err(),
+                Some(expected),
+                "case {}",
+                i
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_from_json() {
+        let json = r#"{
+            "name": "simple-telex",
+            "marks": { "sac": "s", "huyen": "f", "hoi": "r", "nga": "x", "nang": "j" },
+            "tones": [
+                { "key": "a", "tone": "circumflex", "targets": "a" },
+                { "key": "e", "tone": "circumflex", "targets": "e" },
+                { "key": "o", "tone": "circumflex", "targets": "o" },
+                { "key": "w", "tone": "horn", "targets": "aou" }
+            ],
+            "stroke": "d",
+            "remove": "z"
+        }"#;
+        let m = CustomMethod::from_json(json).unwrap();
+        assert_eq!(m.name(), "simple-telex");
+        assert_same(&m, &Telex);
+
+        assert!(matches!(
+            CustomMethod::from_json("{ \"name\": 1 }"),
+            Err(SpecError::Parse(_))
+        ));
+    }
+}
@@ -2,15 +2,26 @@
 //!
 //! Defines key mappings for Vietnamese input methods.
 //! Engine handles all pattern matching based on buffer scan.
+//!
+//! Besides the built-in Telex (id 0), VNI (id 1) and VIQR (id 2), additional
+//! methods can be registered at runtime and selected by id or by name.
 
+pub mod custom;
 pub mod telex;
+pub mod viqr;
 pub mod vni;
 
+pub use custom::{CustomMethod, MethodSpec};
 pub use telex::Telex;
+pub use viqr::Viqr;
 pub use vni::Vni;
 
 use crate::data::chars::tone;
 use crate::data::keys;
+use std::sync::{Arc, LazyLock, RwLock};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// Shared tone target constants
 pub const CIRCUMFLEX_TARGETS: &[u16] = &[keys::A, keys::E, keys::O];
@@ -20,6 +31,8 @@ pub const BREVE_TARGETS: &[u16] = &[keys::A];
 
 /// Tone modifier type
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum ToneType {
     /// Circumflex: â, ê, ô
     Circumflex,
@@ -39,8 +52,30 @@ impl ToneType {
     }
 }
 
+/// Keystroke family of an input method
+///
+/// Selects the family-specific engine heuristics: Telex letter-key patterns
+/// (w → ư, [ ] → ơ ư, double-key reverts, English auto-restore), VNI
+/// number-key behavior (Shift+number types symbols), or VIQR punctuation
+/// modifiers (a' → á, `\` escapes the next modifier).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Family {
+    #[default]
+    Telex,
+    Vni,
+    Viqr,
+}
+
 /// Input method trait - defines key mappings only
-pub trait Method {
+pub trait Method: Send + Sync {
+    /// Method name used for lookup (case-insensitive)
+    fn name(&self) -> &str;
+
+    /// Keystroke family this method behaves like
+    fn family(&self) -> Family;
+
     /// Check if key is a mark modifier
     /// Returns: 1=sắc, 2=huyền, 3=hỏi, 4=ngã, 5=nặng
     fn mark(&self, key: u16) -> Option<u8>;
@@ -51,23 +86,145 @@ pub trait Method {
 
     /// Get valid targets for tone key
     /// Returns list of vowel keys this tone can apply to
-    fn tone_targets(&self, key: u16) -> &'static [u16];
+    fn tone_targets(&self, key: u16) -> &[u16];
 
     /// Check if key is stroke modifier (d → đ)
     fn stroke(&self, key: u16) -> bool;
 
     /// Check if key removes diacritics
     fn remove(&self, key: u16) -> bool;
+
+    /// Shift-aware `mark`, for methods whose modifiers are shifted symbols
+    /// (VIQR `?` = Shift+/, `~` = Shift+`). Defaults to ignoring shift.
+    fn mark_ext(&self, key: u16, _shift: bool) -> Option<u8> {
+        self.mark(key)
+    }
+
+    /// Shift-aware `tone` (VIQR `^`, `(`, `+`). Defaults to ignoring shift.
+    fn tone_ext(&self, key: u16, _shift: bool) -> Option<ToneType> {
+        self.tone(key)
+    }
+
+    /// Check if key escapes the next modifier so it is typed literally
+    fn escape(&self, _key: u16, _shift: bool) -> bool {
+        false
+    }
+}
+
+/// Number of built-in methods (ids below this are reserved)
+const BUILTIN_COUNT: usize = 3;
+
+/// Built-in methods, indexed by id (allocated once)
+static BUILTINS: LazyLock<[Arc<dyn Method>; BUILTIN_COUNT]> =
+    LazyLock::new(|| [Arc::new(Telex), Arc::new(Vni), Arc::new(Viqr)]);
+
+/// Runtime-registered methods, indexed by `id - BUILTIN_COUNT`
+static REGISTRY: RwLock<Vec<Arc<dyn Method>>> = RwLock::new(Vec::new());
+
+/// Get method by id (shared reference, no allocation)
+///
+/// Unknown ids fall back to Telex.
+pub fn get(id: u8) -> Arc<dyn Method> {
+    if let Some(m) = BUILTINS.get(id as usize) {
+        return Arc::clone(m);
+    }
+    let registry = REGISTRY.read().unwrap_or_else(|e| e.into_inner());
+    match registry.get(id as usize - BUILTIN_COUNT) {
+        Some(m) => Arc::clone(m),
+        None => Arc::clone(&BUILTINS[0]),
+    }
+}
+
+/// Look up a method id by name (case-insensitive)
+pub fn find(name: &str) -> Option<u8> {
+    let name = name.trim();
+    if let Some(i) = BUILTINS
+        .iter()
+        .position(|m| m.name().eq_ignore_ascii_case(name))
+    {
+        return Some(i as u8);
+    }
+    let registry = REGISTRY.read().unwrap_or_else(|e| e.into_inner());
+    registry
+        .iter()
+        .position(|m| m.name().eq_ignore_ascii_case(name))
+        .map(|i| (i + BUILTIN_COUNT) as u8)
 }
 
-/// Static method instances (zero-sized types, no heap allocation)
-static TELEX: Telex = Telex;
-static VNI: Vni = Vni;
+/// Register an input method and return its id
+///
+/// Registering a name that is already registered replaces that method and
+/// keeps its id; the old method is freed once no engine call is using it.
+///
+/// Returns `None` if the name belongs to a built-in method or all ids are used.
+pub fn register(method: Box<dyn Method>) -> Option<u8> {
+    let name = method.name().trim().to_string();
+    if BUILTINS
+        .iter()
+        .any(|m| m.name().eq_ignore_ascii_case(&name))
+    {
+        return None;
+    }
+
+    let method: Arc<dyn Method> = Arc::from(method);
+    let mut registry = REGISTRY.write().unwrap_or_else(|e| e.into_inner());
+    let existing = registry
+        .iter()
+        .position(|m| m.name().eq_ignore_ascii_case(&name));
+    let index = match existing {
+        Some(i) => {
+            registry[i] = method;
+            i
+        }
+        None if registry.len() + BUILTIN_COUNT <= u8::MAX as usize => {
+            registry.push(method);
+            registry.len() - 1
+        }
+        None => return None,
+    };
+    Some((index + BUILTIN_COUNT) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_lookup() {
+        assert_eq!(find("telex"), Some(0));
+        assert_eq!(find("VNI"), Some(1));
+        assert_eq!(find("viqr"), Some(2));
+        assert_eq!(find("missing"), None);
+        assert_eq!(get(0).name(), "telex");
+        assert_eq!(get(1).name(), "vni");
+        assert_eq!(get(2).family(), Family::Viqr);
+        assert_eq!(get(250).name(), "telex");
+    }
+
+    #[test]
+    fn test_register_custom() {
+        let mut spec = MethodSpec::vni();
+        spec.name = "registry-test".to_string();
+        let id = register(Box::new(CustomMethod::from_spec(&spec).unwrap())).unwrap();
+        assert!(id as usize >= BUILTIN_COUNT);
+        assert_eq!(find("Registry-Test"), Some(id));
+        assert_eq!(get(id).family(), Family::Vni);
+
+        // Re-registering the same name keeps the id
+        spec.family = Family::Telex;
+        let again = register(Box::new(CustomMethod::from_spec(&spec).unwrap())).unwrap();
+        assert_eq!(again, id);
+        assert_eq!(get(id).family(), Family::Telex);
+
+        // The replaced method is freed, not leaked
+        let old = Arc::downgrade(&get(id));
+        register(Box::new(CustomMethod::from_spec(&spec).unwrap())).unwrap();
+        assert!(old.upgrade().is_none());
+    }
 
-/// Get method by id (returns static reference, no allocation)
-pub fn get(id: u8) -> &'static dyn Method {
-    match id {
-        1 => &VNI,
-        _ => &TELEX,
+    #[test]
+    fn test_register_builtin_name_rejected() {
+        let m = CustomMethod::from_spec(&MethodSpec::telex()).unwrap();
+        assert_eq!(register(Box::new(m)), None);
     }
 }
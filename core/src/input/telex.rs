@@ -6,12 +6,20 @@
 //! - Stroke: d
 //! - Remove: z
 
-use super::{Method, ToneType, HORN_TARGETS_TELEX};
+use super::{Family, Method, ToneType, HORN_TARGETS_TELEX};
 use crate::data::keys;
 
 pub struct Telex;
 
 impl Method for Telex {
+    fn name(&self) -> &str {
+        "telex"
+    }
+
+    fn family(&self) -> Family {
+        Family::Telex
+    }
+
     fn mark(&self, key: u16) -> Option<u8> {
         match key {
             keys::S => Some(1), // sắc
@@ -31,7 +39,7 @@ impl Method for Telex {
         }
     }
 
-    fn tone_targets(&self, key: u16) -> &'static [u16] {
+    fn tone_targets(&self, key: u16) -> &[u16] {
         match key {
             keys::A => &[keys::A],
             keys::E => &[keys::E],
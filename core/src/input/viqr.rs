@@ -0,0 +1,109 @@
+//! VIQR Input Method
+//!
+//! Key mappings (punctuation typed after the vowel):
+//! - Marks: '=sắc, `=huyền, ?=hỏi, ~=ngã, .=nặng
+//! - Tones: ^=circumflex, +=horn, (=breve
+//! - Stroke: d (dd → đ)
+//! - Escape: \ types the next modifier literally (a\' → a')
+
+use super::{Family, Method, ToneType, BREVE_TARGETS, CIRCUMFLEX_TARGETS, HORN_TARGETS_VNI};
+use crate::data::keys;
+
+pub struct Viqr;
+
+impl Method for Viqr {
+    fn name(&self) -> &str {
+        "viqr"
+    }
+
+    fn family(&self) -> Family {
+        Family::Viqr
+    }
+
+    fn mark(&self, key: u16) -> Option<u8> {
+        self.mark_ext(key, false)
+    }
+
+    fn tone(&self, key: u16) -> Option<ToneType> {
+        self.tone_ext(key, false)
+    }
+
+    fn tone_targets(&self, key: u16) -> &[u16] {
+        match key {
+            keys::N6 => CIRCUMFLEX_TARGETS,
+            keys::EQUAL => HORN_TARGETS_VNI,
+            keys::N9 => BREVE_TARGETS,
+            _ => &[],
+        }
+    }
+
+    fn stroke(&self, key: u16) -> bool {
+        key == keys::D
+    }
+
+    fn remove(&self, _key: u16) -> bool {
+        false
+    }
+
+    fn mark_ext(&self, key: u16, shift: bool) -> Option<u8> {
+        match (key, shift) {
+            (keys::QUOTE, false) => Some(1),     // ' sắc
+            (keys::BACKQUOTE, false) => Some(2), // ` huyền
+            (keys::SLASH, true) => Some(3),      // ? hỏi
+            (keys::BACKQUOTE, true) => Some(4),  // ~ ngã
+            (keys::DOT, false) => Some(5),       // . nặng
+            _ => None,
+        }
+    }
+
+    fn tone_ext(&self, key: u16, shift: bool) -> Option<ToneType> {
+        if !shift {
+            return None;
+        }
+        match key {
+            keys::N6 => Some(ToneType::Circumflex), // ^
+            keys::EQUAL => Some(ToneType::Horn),    // +
+            keys::N9 => Some(ToneType::Breve),      // (
+            _ => None,
+        }
+    }
+
+    fn escape(&self, key: u16, shift: bool) -> bool {
+        key == keys::BACKSLASH && !shift
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marks() {
+        let v = Viqr;
+        assert_eq!(v.mark_ext(keys::QUOTE, false), Some(1));
+        assert_eq!(v.mark_ext(keys::BACKQUOTE, false), Some(2));
+        assert_eq!(v.mark_ext(keys::SLASH, true), Some(3));
+        assert_eq!(v.mark_ext(keys::BACKQUOTE, true), Some(4));
+        assert_eq!(v.mark_ext(keys::DOT, false), Some(5));
+        assert_eq!(v.mark_ext(keys::SLASH, false), None);
+        assert_eq!(v.mark_ext(keys::S, false), None);
+    }
+
+    #[test]
+    fn test_tones() {
+        let v = Viqr;
+        assert_eq!(v.tone_ext(keys::N6, true), Some(ToneType::Circumflex));
+        assert_eq!(v.tone_ext(keys::EQUAL, true), Some(ToneType::Horn));
+        assert_eq!(v.tone_ext(keys::N9, true), Some(ToneType::Breve));
+        assert_eq!(v.tone_ext(keys::N6, false), None);
+        assert_eq!(v.tone(keys::N6), None);
+    }
+
+    #[test]
+    fn test_stroke_and_escape() {
+        let v = Viqr;
+        assert!(v.stroke(keys::D));
+        assert!(v.escape(keys::BACKSLASH, false));
+        assert!(!v.escape(keys::BACKSLASH, true));
+    }
+}
@@ -6,12 +6,20 @@
 //! - Stroke: 9
 //! - Remove: 0
 
-use super::{Method, ToneType, BREVE_TARGETS, CIRCUMFLEX_TARGETS, HORN_TARGETS_VNI};
+use super::{Family, Method, ToneType, BREVE_TARGETS, CIRCUMFLEX_TARGETS, HORN_TARGETS_VNI};
 use crate::data::keys;
 
 pub struct Vni;
 
 impl Method for Vni {
+    fn name(&self) -> &str {
+        "vni"
+    }
+
+    fn family(&self) -> Family {
+        Family::Vni
+    }
+
     fn mark(&self, key: u16) -> Option<u8> {
         match key {
             keys::N1 => Some(1), // sắc
@@ -32,7 +40,7 @@ impl Method for Vni {
         }
     }
 
-    fn tone_targets(&self, key: u16) -> &'static [u16] {
+    fn tone_targets(&self, key: u16) -> &[u16] {
         match key {
             keys::N6 => CIRCUMFLEX_TARGETS,
             keys::N7 => HORN_TARGETS_VNI,
@@ -1,32 +1,46 @@
 //! Gõ Nhanh Vietnamese IME Core
 //!
-//! Simple Vietnamese input method engine supporting Telex and VNI.
+//! Simple Vietnamese input method engine supporting Telex, VNI and VIQR.
 //!
 //! # FFI Usage
 //!
 //! ```c
 //! // Initialize once at app start
 //! ime_init();
-//! ime_method(0);  // 0=Telex, 1=VNI
+//! ime_method(0);  // 0=Telex, 1=VNI, 2=VIQR
 //!
 //! // Process each keystroke
 //! ImeResult* r = ime_key(keycode, is_shift, is_ctrl);
 //! if (r && r->action == 1) {
 //!     // Send r->backspace deletes, then r->chars
 //! }
+//! // Long output (e.g. shortcut expansions) continues in more chunks
+//! while (r && (r->flags & 0x02)) {
+//!     ime_free(r);
+//!     r = ime_next_chunk();  // Send r->chars
+//! }
 //! ime_free(r);
 //!
 //! // Clean up on word boundary
 //! ime_clear();
 //! ```
+//!
+//! Hosts that need several engines (one per document) use the handle API
+//! in `handle` instead: `ime_create`, `ime_engine_*`, `ime_destroy`.
 
+pub mod charset;
+pub mod convert;
 pub mod data;
 pub mod engine;
+pub mod fold;
+pub mod handle;
 pub mod input;
 pub mod updater;
 pub mod utils;
 
 use engine::{Engine, Result};
+use handle::{add_shortcut, c_str, copy_out, split_tags, user_list, user_words_text};
+use std::os::raw::c_char;
 use std::sync::Mutex;
 
 // Global engine instance (thread-safe via Mutex)
@@ -37,6 +51,19 @@ fn lock_engine() -> std::sync::MutexGuard<'static, Option<Engine>> {
     ENGINE.lock().unwrap_or_else(|e| e.into_inner())
 }
 
+/// Run `f` on the global engine; None if not initialized
+///
+/// The global functions below wrap the same operations as the handle API
+/// (`handle`), keeping their original no-op behavior without `ime_init`.
+fn with_engine<T>(f: impl FnOnce(&mut Engine) -> T) -> Option<T> {
+    lock_engine().as_mut().map(f)
+}
+
+/// Heap-allocate a result for the caller to `ime_free` (null if none)
+fn boxed(r: Option<Result>) -> *mut Result {
+    r.map_or(std::ptr::null_mut(), |r| Box::into_raw(Box::new(r)))
+}
+
 // ============================================================
 // FFI Interface
 // ============================================================
@@ -66,23 +93,20 @@ pub extern "C" fn ime_init() {
 /// * `null` if engine not initialized
 ///
 /// # Result struct
-/// * `action`: 0=None (pass through), 1=Send (replace text), 2=Restore
+/// * `action`: 0=None (pass through), 1=Send (replace text), 2=Restore,
+///   3=Undo (from `ime_undo`/`ime_redo`, apply like Send)
 /// * `backspace`: number of characters to delete
 /// * `chars`: UTF-32 codepoints to insert
 /// * `count`: number of valid chars
+/// * `flags`: 0x01 = trigger key consumed, 0x02 = more chunks (`ime_next_chunk`)
+/// * `cursor_left`: left-arrow moves to send after the text (`{cursor}` shortcuts)
 ///
 /// # Note
 /// For VNI mode with Shift+number keys (to type @, #, $ etc.),
 /// use `ime_key_ext` with the shift parameter.
 #[no_mangle]
 pub extern "C" fn ime_key(key: u16, caps: bool, ctrl: bool) -> *mut Result {
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        let r = e.on_key(key, caps, ctrl);
-        Box::into_raw(Box::new(r))
-    } else {
-        std::ptr::null_mut()
-    }
+    boxed(with_engine(|e| e.on_key(key, caps, ctrl)))
 }
 
 /// Process a key event with extended parameters.
@@ -105,27 +129,82 @@ pub extern "C" fn ime_key(key: u16, caps: bool, ctrl: bool) -> *mut Result {
 /// - etc.
 #[no_mangle]
 pub extern "C" fn ime_key_ext(key: u16, caps: bool, ctrl: bool, shift: bool) -> *mut Result {
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        let r = e.on_key_ext(key, caps, ctrl, shift);
-        Box::into_raw(Box::new(r))
-    } else {
-        std::ptr::null_mut()
-    }
+    boxed(with_engine(|e| e.on_key_ext(key, caps, ctrl, shift)))
+}
+
+/// Process a typed character instead of a keycode.
+///
+/// For frontends that get characters from the OS (any keyboard layout,
+/// X11 keysyms, evdev + xkb). See `Engine::on_char`.
+///
+/// # Arguments
+/// * `ch` - Unicode codepoint typed (8 = Backspace, 27 = Esc)
+/// * `modifiers` - bits held: 0x01 Shift, 0x02 Ctrl, 0x04 Alt, 0x08 Cmd/Super;
+///   Ctrl or Cmd/Super bypasses the IME
+///
+/// # Returns
+/// * Pointer to `Result` struct (caller must free with `ime_free`)
+/// * `null` if engine not initialized or `ch` is not a valid codepoint
+#[no_mangle]
+pub extern "C" fn ime_char(ch: u32, modifiers: u8) -> *mut Result {
+    let Some(ch) = char::from_u32(ch) else {
+        return std::ptr::null_mut();
+    };
+    boxed(with_engine(|e| e.on_char(ch, modifiers)))
+}
+
+/// Fetch the next chunk of a long output.
+///
+/// When a result has flag `0x02` (more) set, its output did not fit in one
+/// `Result`. Call this until a result without the flag is returned; chunks
+/// only insert text (`backspace` is 0). Pending chunks are dropped on the
+/// next key.
+///
+/// # Returns
+/// * Pointer to `Result` struct (caller must free with `ime_free`),
+///   `action` 0 when nothing is pending
+/// * `null` if engine not initialized
+#[no_mangle]
+pub extern "C" fn ime_next_chunk() -> *mut Result {
+    boxed(with_engine(|e| e.next_chunk()))
+}
+
+/// Revert the last IME rewrite (tone, mark, stroke, shortcut expansion).
+///
+/// Bind to an "undo typing" hotkey: unlike the app's Ctrl+Z it only undoes
+/// what the IME changed, even after the word was committed. Text typed
+/// after the rewrite is deleted and typed again. Call `ime_clear_all` when
+/// the caret moves so stale rewrites are not replayed.
+///
+/// # Returns
+/// * Pointer to `Result` struct (caller must free with `ime_free`),
+///   `action` 3 (apply like Send) or 0 when there is nothing to undo
+/// * `null` if engine not initialized
+#[no_mangle]
+pub extern "C" fn ime_undo() -> *mut Result {
+    boxed(with_engine(|e| e.undo_last_transform()))
+}
+
+/// Re-apply the rewrite reverted by the last `ime_undo`.
+///
+/// Any key typed after the undo drops what could be redone.
+///
+/// # Returns
+/// Same as `ime_undo`.
+#[no_mangle]
+pub extern "C" fn ime_redo() -> *mut Result {
+    boxed(with_engine(|e| e.redo_last_transform()))
 }
 
 /// Set the input method.
 ///
 /// # Arguments
-/// * `method` - 0 for Telex, 1 for VNI
+/// * `method` - 0 for Telex, 1 for VNI, 2 for VIQR, 3+ for methods registered via `input::register`
 ///
 /// No-op if engine not initialized.
 #[no_mangle]
 pub extern "C" fn ime_method(method: u8) {
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        e.set_method(method);
-    }
+    with_engine(|e| e.set_method(method));
 }
 
 /// Enable or disable the engine.
@@ -134,10 +213,7 @@ pub extern "C" fn ime_method(method: u8) {
 /// No-op if engine not initialized.
 #[no_mangle]
 pub extern "C" fn ime_enabled(enabled: bool) {
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        e.set_enabled(enabled);
-    }
+    with_engine(|e| e.set_enabled(enabled));
 }
 
 /// Set whether to skip w→ư shortcut in Telex mode.
@@ -147,10 +223,7 @@ pub extern "C" fn ime_enabled(enabled: bool) {
 /// No-op if engine not initialized.
 #[no_mangle]
 pub extern "C" fn ime_skip_w_shortcut(skip: bool) {
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        e.set_skip_w_shortcut(skip);
-    }
+    with_engine(|e| e.set_skip_w_shortcut(skip));
 }
 
 /// Set whether bracket shortcuts are enabled: ] → ư, [ → ơ (Issue #159)
@@ -159,10 +232,7 @@ pub extern "C" fn ime_skip_w_shortcut(skip: bool) {
 /// No-op if engine not initialized.
 #[no_mangle]
 pub extern "C" fn ime_bracket_shortcut(enabled: bool) {
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        e.set_bracket_shortcut(enabled);
-    }
+    with_engine(|e| e.set_bracket_shortcut(enabled));
 }
 
 /// Set whether ESC key restores raw ASCII input.
@@ -172,10 +242,7 @@ pub extern "C" fn ime_bracket_shortcut(enabled: bool) {
 /// No-op if engine not initialized.
 #[no_mangle]
 pub extern "C" fn ime_esc_restore(enabled: bool) {
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        e.set_esc_restore(enabled);
-    }
+    with_engine(|e| e.set_esc_restore(enabled));
 }
 
 /// Set whether to enable free tone placement (skip validation).
@@ -186,10 +253,7 @@ pub extern "C" fn ime_esc_restore(enabled: bool) {
 /// No-op if engine not initialized.
 #[no_mangle]
 pub extern "C" fn ime_free_tone(enabled: bool) {
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        e.set_free_tone(enabled);
-    }
+    with_engine(|e| e.set_free_tone(enabled));
 }
 
 /// Set whether to use modern orthography for tone placement.
@@ -199,10 +263,7 @@ pub extern "C" fn ime_free_tone(enabled: bool) {
 /// No-op if engine not initialized.
 #[no_mangle]
 pub extern "C" fn ime_modern(modern: bool) {
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        e.set_modern_tone(modern);
-    }
+    with_engine(|e| e.set_modern_tone(modern));
 }
 
 /// Enable/disable English auto-restore (experimental feature).
@@ -213,10 +274,18 @@ pub extern "C" fn ime_modern(modern: bool) {
 /// No-op if engine not initialized.
 #[no_mangle]
 pub extern "C" fn ime_english_auto_restore(enabled: bool) {
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        e.set_english_auto_restore(enabled);
-    }
+    with_engine(|e| e.set_english_auto_restore(enabled));
+}
+
+/// Enable/disable spell checking on word commit.
+///
+/// When `enabled` is true, a transformed word that is well formed but not an
+/// attested Vietnamese syllable (e.g., "nghẻ") is restored to its keystrokes.
+/// When `enabled` is false (default), any valid structure is kept.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_spell_check(enabled: bool) {
+    with_engine(|e| e.set_spell_check(enabled));
 }
 
 /// Enable/disable auto-capitalize after sentence-ending punctuation.
@@ -227,10 +296,67 @@ pub extern "C" fn ime_english_auto_restore(enabled: bool) {
 /// No-op if engine not initialized.
 #[no_mangle]
 pub extern "C" fn ime_auto_capitalize(enabled: bool) {
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        e.set_auto_capitalize(enabled);
+    with_engine(|e| e.set_auto_capitalize(enabled));
+}
+
+/// Set the output encoding for legacy fonts.
+///
+/// `charset`: 0=Unicode (default), 1=TCVN3 (ABC), 2=VNI-Windows, 3=VISCII.
+/// Legacy charsets send one Latin-1 char per byte and count backspaces in
+/// bytes. Unknown ids are ignored.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_charset(charset: u8) {
+    let Some(charset) = charset::Charset::from_u8(charset) else {
+        return;
+    };
+    with_engine(|e| e.set_charset(charset));
+}
+
+/// Set Unicode composition of output.
+///
+/// `form`: 0=precomposed NFC (default), 1=decomposed NFD, 2=Windows-1258
+/// style (precomposed base + combining tone mark). Backspace counts follow
+/// the form. Unknown ids are ignored.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_output_form(form: u8) {
+    let Some(form) = charset::Form::from_u8(form) else {
+        return;
+    };
+    with_engine(|e| e.set_output_form(form));
+}
+
+/// Apply all engine options at once.
+///
+/// Prefer this over the individual setters so frontends stay in sync with
+/// every option the core supports.
+///
+/// # Safety
+/// `config` must point to a valid `FfiEngineConfig`, or be null (no-op).
+/// No-op if engine not initialized.
+#[no_mangle]
+pub unsafe extern "C" fn ime_apply_config(config: *const engine::config::FfiEngineConfig) {
+    if config.is_null() {
+        return;
+    }
+    let config = engine::EngineConfig::from(std::ptr::read_unaligned(config));
+    with_engine(|e| e.apply_config(&config));
+}
+
+/// Read the current engine options.
+///
+/// Returns false (and leaves `out` untouched) if `out` is null or the
+/// engine is not initialized.
+///
+/// # Safety
+/// `out` must point to writable memory for one `FfiEngineConfig`, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn ime_get_config(out: *mut engine::config::FfiEngineConfig) -> bool {
+    if out.is_null() {
+        return false;
     }
+    with_engine(|e| std::ptr::write_unaligned(out, e.config().into())).is_some()
 }
 
 /// Clear the input buffer.
@@ -240,10 +366,7 @@ pub extern "C" fn ime_auto_capitalize(enabled: bool) {
 /// No-op if engine not initialized.
 #[no_mangle]
 pub extern "C" fn ime_clear() {
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        e.clear();
-    }
+    with_engine(|e| e.clear());
 }
 
 /// Clear everything including word history.
@@ -253,10 +376,7 @@ pub extern "C" fn ime_clear() {
 /// No-op if engine not initialized.
 #[no_mangle]
 pub extern "C" fn ime_clear_all() {
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        e.clear_all();
-    }
+    with_engine(|e| e.clear_all());
 }
 
 /// Get the full composed buffer as UTF-32 codepoints.
@@ -278,17 +398,12 @@ pub unsafe extern "C" fn ime_get_buffer(out: *mut u32, max_len: i64) -> i64 {
     if out.is_null() || max_len <= 0 {
         return 0;
     }
-
-    let guard = lock_engine();
-    if let Some(ref e) = *guard {
-        let full = e.get_buffer_string();
-        let utf32: Vec<u32> = full.chars().map(|c| c as u32).collect();
-        let len = utf32.len().min(max_len as usize);
-        std::ptr::copy_nonoverlapping(utf32.as_ptr(), out, len);
-        len as i64
-    } else {
-        0
-    }
+    with_engine(|e| {
+        let utf32: Vec<u32> = e.get_buffer_string().chars().map(|c| c as u32).collect();
+        copy_out(&utf32, out, max_len);
+        utf32.len().min(max_len as usize) as i64
+    })
+    .unwrap_or(0)
 }
 
 /// Free a result pointer returned by `ime_key`.
@@ -317,36 +432,50 @@ pub unsafe extern "C" fn ime_free(r: *mut Result) {
 /// # Safety
 /// Both pointers must be valid null-terminated UTF-8 strings.
 #[no_mangle]
-pub unsafe extern "C" fn ime_add_shortcut(
-    trigger: *const std::os::raw::c_char,
-    replacement: *const std::os::raw::c_char,
-) {
-    if trigger.is_null() || replacement.is_null() {
+pub unsafe extern "C" fn ime_add_shortcut(trigger: *const c_char, replacement: *const c_char) {
+    let (Ok(trigger), Ok(replacement)) = (c_str(trigger), c_str(replacement)) else {
         return;
-    }
-
-    let trigger_str = match std::ffi::CStr::from_ptr(trigger).to_str() {
-        Ok(s) => s,
-        Err(_) => return,
     };
-    let replacement_str = match std::ffi::CStr::from_ptr(replacement).to_str() {
-        Ok(s) => s,
-        Err(_) => return,
+    with_engine(|e| add_shortcut(e, trigger, replacement, &[]));
+}
+
+/// Add a shortcut that only fires in the given contexts (see `ime_set_context`).
+///
+/// # Arguments
+/// * `trigger` - C string for trigger (e.g., "fn")
+/// * `replacement` - C string for replacement (e.g., "function")
+/// * `contexts` - comma-separated context tags (e.g., "code.exe,devenv.exe")
+///
+/// # Safety
+/// All pointers must be valid null-terminated UTF-8 strings.
+#[no_mangle]
+pub unsafe extern "C" fn ime_add_shortcut_in_context(
+    trigger: *const c_char,
+    replacement: *const c_char,
+    contexts: *const c_char,
+) {
+    let (Ok(trigger), Ok(replacement), Ok(contexts)) =
+        (c_str(trigger), c_str(replacement), c_str(contexts))
+    else {
+        return;
     };
+    with_engine(|e| add_shortcut(e, trigger, replacement, &split_tags(contexts)));
+}
 
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        // Auto-detect shortcut type:
-        // - If trigger contains only non-letter chars (like "->", "=>"), use immediate trigger
-        // - Otherwise use word boundary trigger (traditional abbreviations like "vn" → "Việt Nam")
-        let is_symbol_trigger = trigger_str.chars().all(|c| !c.is_alphabetic());
-        let shortcut = if is_symbol_trigger {
-            engine::shortcut::Shortcut::immediate(trigger_str, replacement_str)
-        } else {
-            engine::shortcut::Shortcut::new(trigger_str, replacement_str)
-        };
-        e.shortcuts_mut().add(shortcut);
-    }
+/// Set the current context tags for scoped shortcuts.
+///
+/// Typically the foreground application (e.g., "code.exe"); pass a
+/// comma-separated list for several tags, or null to clear.
+///
+/// # Safety
+/// Pointer must be null or a valid null-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_set_context(tags: *const c_char) {
+    let tags = if tags.is_null() { Ok("") } else { c_str(tags) };
+    let Ok(tags) = tags else {
+        return;
+    };
+    with_engine(|e| e.set_context(&split_tags(tags)));
 }
 
 /// Remove a shortcut from the engine.
@@ -357,29 +486,123 @@ pub unsafe extern "C" fn ime_add_shortcut(
 /// # Safety
 /// Pointer must be a valid null-terminated UTF-8 string.
 #[no_mangle]
-pub unsafe extern "C" fn ime_remove_shortcut(trigger: *const std::os::raw::c_char) {
-    if trigger.is_null() {
+pub unsafe extern "C" fn ime_remove_shortcut(trigger: *const c_char) {
+    let Ok(trigger) = c_str(trigger) else {
         return;
-    }
-
-    let trigger_str = match std::ffi::CStr::from_ptr(trigger).to_str() {
-        Ok(s) => s,
-        Err(_) => return,
     };
-
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        e.shortcuts_mut().remove(trigger_str);
-    }
+    with_engine(|e| e.shortcuts_mut().remove(trigger));
 }
 
 /// Clear all shortcuts from the engine.
 #[no_mangle]
 pub extern "C" fn ime_clear_shortcuts() {
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        e.shortcuts_mut().clear();
-    }
+    with_engine(|e| e.shortcuts_mut().clear());
+}
+
+/// Set the UTC offset of `{date}`, `{time}` and `{weekday_vi}` in shortcuts.
+///
+/// `minutes` east of UTC (420 for Vietnam); the default is 0. Offsets beyond
+/// ±14 hours are ignored.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_set_utc_offset(minutes: i32) {
+    with_engine(|e| {
+        e.shortcuts_mut().set_utc_offset(minutes);
+    });
+}
+
+// ============================================================
+// User Dictionary FFI
+// ============================================================
+
+/// Add a word to a personal list.
+///
+/// # Arguments
+/// * `list` - 0=English (restored to the typed keys on commit),
+///   1=Vietnamese (never restored)
+/// * `word` - C string, e.g. "samsung"
+///
+/// # Returns
+/// false for unknown lists, empty words, words with spaces, or when the
+/// engine is not initialized.
+///
+/// # Safety
+/// Pointer must be a valid null-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_user_word_add(list: u8, word: *const c_char) -> bool {
+    let (Ok(list), Ok(word)) = (user_list(list), c_str(word)) else {
+        return false;
+    };
+    with_engine(|e| e.user_dict_mut().add(list, word)).unwrap_or(false)
+}
+
+/// Remove a word from a personal list; returns whether it was there.
+///
+/// # Safety
+/// Pointer must be a valid null-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_user_word_remove(list: u8, word: *const c_char) -> bool {
+    let (Ok(list), Ok(word)) = (user_list(list), c_str(word)) else {
+        return false;
+    };
+    with_engine(|e| e.user_dict_mut().remove(list, word)).unwrap_or(false)
+}
+
+/// Clear a personal list (0=English, 1=Vietnamese).
+#[no_mangle]
+pub extern "C" fn ime_user_words_clear(list: u8) {
+    let Ok(list) = user_list(list) else {
+        return;
+    };
+    with_engine(|e| e.user_dict_mut().clear(list));
+}
+
+/// Get the words of a personal list as UTF-8, one per line.
+///
+/// # Returns
+/// Full length in bytes; at most `max_len` bytes are written, so call
+/// again with a larger buffer when the result exceeds `max_len`.
+///
+/// # Safety
+/// `out` must point to valid memory of at least `max_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ime_user_words(list: u8, out: *mut u8, max_len: i64) -> i64 {
+    let Ok(list) = user_list(list) else {
+        return 0;
+    };
+    with_engine(|e| {
+        let text = user_words_text(e, list);
+        copy_out(text.as_bytes(), out, max_len);
+        text.len() as i64
+    })
+    .unwrap_or(0)
+}
+
+/// Replace both personal lists with a dictionary file.
+///
+/// # Returns
+/// false if the file can't be read or parsed (lists are left unchanged).
+///
+/// # Safety
+/// Pointer must be a valid null-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_user_dict_load(path: *const c_char) -> bool {
+    let Ok(path) = c_str(path) else {
+        return false;
+    };
+    with_engine(|e| handle::load_user_dict(e, path).is_ok()).unwrap_or(false)
+}
+
+/// Save both personal lists to a dictionary file.
+///
+/// # Safety
+/// Pointer must be a valid null-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_user_dict_save(path: *const c_char) -> bool {
+    let Ok(path) = c_str(path) else {
+        return false;
+    };
+    with_engine(|e| e.user_dict().save(path).is_ok()).unwrap_or(false)
 }
 
 // ============================================================
@@ -398,18 +621,31 @@ pub extern "C" fn ime_clear_shortcuts() {
 /// # Safety
 /// Pointer must be a valid null-terminated UTF-8 string.
 #[no_mangle]
-pub unsafe extern "C" fn ime_restore_word(word: *const std::os::raw::c_char) {
-    if word.is_null() {
+pub unsafe extern "C" fn ime_restore_word(word: *const c_char) {
+    let Ok(word) = c_str(word) else {
         return;
-    }
-    let word_str = match std::ffi::CStr::from_ptr(word).to_str() {
-        Ok(s) => s,
-        Err(_) => return,
     };
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        e.restore_word(word_str);
-    }
+    with_engine(|e| e.restore_word(word));
+}
+
+/// Resynchronize with the text around the caret.
+///
+/// Call after the caret moves without typing (click, arrow keys, focus)
+/// with the surrounding text IBus/TSF report. The word ending at the caret
+/// becomes the current word, so the next mark or tone key edits it.
+///
+/// # Arguments
+/// * `text` - C string with the text around the caret
+/// * `cursor` - Caret offset in `text`, in chars
+///
+/// # Safety
+/// Pointer must be a valid null-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_set_surrounding_text(text: *const c_char, cursor: u32) {
+    let Ok(text) = c_str(text) else {
+        return;
+    };
+    with_engine(|e| e.set_surrounding_text(text, cursor as usize));
 }
 
 // ============================================================
@@ -444,6 +680,54 @@ mod tests {
         ime_clear();
     }
 
+    #[test]
+    #[serial]
+    fn test_char_ffi() {
+        ime_init();
+        ime_method(0);
+        unsafe { ime_free(ime_char('A' as u32, 0)) };
+        let r = ime_char('w' as u32, 0);
+        assert_eq!(unsafe { ((*r).backspace, (*r).chars[0]) }, (1, 'Ă' as u32));
+        unsafe { ime_free(r) };
+        assert!(ime_char(0xd800, 0).is_null());
+        ime_clear();
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_ffi() {
+        ime_init();
+        let config = engine::config::FfiEngineConfig::from(engine::EngineConfig {
+            method: 1,
+            modern_tone: false,
+            ..Default::default()
+        });
+        unsafe {
+            ime_apply_config(&config);
+            ime_apply_config(std::ptr::null());
+        }
+
+        // VNI + traditional tone placement
+        for key in [keys::H, keys::O, keys::A, keys::N2] {
+            let r = ime_key(key, false, false);
+            unsafe { ime_free(r) };
+        }
+        let mut buf = [0u32; 8];
+        let len = unsafe { ime_get_buffer(buf.as_mut_ptr(), 8) };
+        let word: String = buf[..len as usize]
+            .iter()
+            .filter_map(|&c| char::from_u32(c))
+            .collect();
+        assert_eq!(word, "hòa");
+
+        let mut out = engine::config::FfiEngineConfig::from(engine::EngineConfig::default());
+        assert!(unsafe { ime_get_config(&mut out) });
+        assert_eq!(engine::EngineConfig::from(out).method, 1);
+        assert!(!unsafe { ime_get_config(std::ptr::null_mut()) });
+
+        ime_clear();
+    }
+
     #[test]
     #[serial]
     fn test_shortcut_ffi_add_and_clear() {
@@ -479,6 +763,162 @@ mod tests {
         ime_clear();
     }
 
+    #[test]
+    #[serial]
+    fn test_utc_offset_ffi() {
+        use crate::engine::shortcut::Shortcut;
+        use crate::utils::is_system_hour;
+
+        ime_init();
+        ime_clear_shortcuts();
+        let expand = || {
+            let guard = lock_engine();
+            let e = guard.as_ref().unwrap();
+            e.shortcuts().try_match("gio", None, true).unwrap().output
+        };
+        with_engine(|e| {
+            e.shortcuts_mut()
+                .add(Shortcut::new("gio", "{date:%Y-%m-%d %H}"))
+        });
+        ime_set_utc_offset(420);
+        assert!(is_system_hour(420, expand));
+        ime_set_utc_offset(24 * 60);
+        assert!(is_system_hour(420, expand));
+        ime_set_utc_offset(0);
+        assert!(is_system_hour(0, expand));
+
+        ime_clear_shortcuts();
+    }
+
+    #[test]
+    #[serial]
+    fn test_next_chunk_ffi() {
+        ime_init();
+        ime_clear_shortcuts();
+        ime_method(0);
+        let long = "Trân trọng, ".repeat(50);
+        let trigger = CString::new("sig").unwrap();
+        let replacement = CString::new(long.clone()).unwrap();
+        unsafe { ime_add_shortcut(trigger.as_ptr(), replacement.as_ptr()) };
+
+        for key in [keys::S, keys::I, keys::G] {
+            unsafe { ime_free(ime_key(key, false, false)) };
+        }
+        let mut r = ime_key(keys::SPACE, false, false);
+        let mut text = String::new();
+        let mut chunks = 0;
+        loop {
+            let res = unsafe { &*r };
+            text.extend(
+                res.chars[..res.count as usize]
+                    .iter()
+                    .filter_map(|&c| char::from_u32(c)),
+            );
+            chunks += 1;
+            let more = res.flags & engine::FLAG_MORE != 0;
+            unsafe { ime_free(r) };
+            if !more {
+                break;
+            }
+            r = ime_next_chunk();
+        }
+        assert_eq!(text, format!("{} ", long));
+        assert_eq!(chunks, 3);
+
+        let r = ime_next_chunk();
+        assert_eq!(unsafe { (*r).action }, 0);
+        unsafe { ime_free(r) };
+        ime_clear_shortcuts();
+        ime_clear();
+    }
+
+    #[test]
+    #[serial]
+    fn test_undo_ffi() {
+        ime_init();
+        ime_method(0);
+        for key in [keys::A, keys::S] {
+            unsafe { ime_free(ime_key(key, false, false)) };
+        }
+        let r = ime_undo();
+        let res = unsafe { &*r };
+        assert_eq!(res.action, engine::Action::Undo as u8);
+        assert_eq!((res.backspace, res.count), (1, 2));
+        assert_eq!(res.chars[..2], ['a' as u32, 's' as u32]);
+        unsafe { ime_free(r) };
+
+        let r = ime_undo();
+        assert_eq!(unsafe { (*r).action }, 0);
+        unsafe { ime_free(r) };
+        let r = ime_redo();
+        assert_eq!(unsafe { ((*r).backspace, (*r).chars[0]) }, (2, 'á' as u32));
+        unsafe { ime_free(r) };
+        ime_clear_all();
+    }
+
+    #[test]
+    #[serial]
+    fn test_user_dict_ffi() {
+        ime_init();
+        let word = CString::new("Samsung").unwrap();
+        unsafe {
+            assert!(ime_user_word_add(0, word.as_ptr()));
+            assert!(!ime_user_word_add(7, word.as_ptr()));
+            assert!(!ime_user_word_add(0, std::ptr::null()));
+        }
+        let mut out = [0u8; 4];
+        let len = unsafe { ime_user_words(0, out.as_mut_ptr(), out.len() as i64) };
+        assert_eq!(len, 8);
+        assert_eq!(&out, b"sams");
+
+        let path = std::env::temp_dir().join("gonhanh_user_dict_ffi.txt");
+        let c_path = CString::new(path.to_str().unwrap()).unwrap();
+        unsafe {
+            assert!(ime_user_dict_save(c_path.as_ptr()));
+            assert!(ime_user_word_remove(0, word.as_ptr()));
+            assert!(!ime_user_word_remove(0, word.as_ptr()));
+            assert_eq!(ime_user_words(0, std::ptr::null_mut(), 0), 0);
+            assert!(ime_user_dict_load(c_path.as_ptr()));
+            assert_eq!(ime_user_words(0, std::ptr::null_mut(), 0), 8);
+        }
+        ime_user_words_clear(0);
+        assert_eq!(unsafe { ime_user_words(0, std::ptr::null_mut(), 0) }, 0);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    #[serial]
+    fn test_context_ffi() {
+        ime_init();
+        ime_clear_shortcuts();
+        ime_method(0);
+        let trigger = CString::new("fn").unwrap();
+        let replacement = CString::new("function").unwrap();
+        let contexts = CString::new("code.exe, devenv.exe").unwrap();
+        unsafe {
+            ime_add_shortcut_in_context(trigger.as_ptr(), replacement.as_ptr(), contexts.as_ptr());
+        }
+
+        let fires = || {
+            for key in [keys::F, keys::N] {
+                unsafe { ime_free(ime_key(key, false, false)) };
+            }
+            let r = ime_key(keys::SPACE, false, false);
+            let action = unsafe { (*r).action };
+            unsafe { ime_free(r) };
+            ime_clear();
+            action == 1
+        };
+        assert!(!fires());
+        let ctx = CString::new("DEVENV.EXE").unwrap();
+        unsafe { ime_set_context(ctx.as_ptr()) };
+        assert!(fires());
+        unsafe { ime_set_context(std::ptr::null()) };
+        assert!(!fires());
+
+        ime_clear_shortcuts();
+    }
+
     #[test]
     #[serial]
     fn test_shortcut_ffi_remove() {
@@ -712,6 +1152,30 @@ mod tests {
         ime_clear();
     }
 
+    #[test]
+    #[serial]
+    fn test_surrounding_text_ffi() {
+        ime_init();
+        ime_method(0); // Telex
+
+        // Caret after "Viêt": 'j' adds the dot below
+        let text = CString::new("Viêt Nam").unwrap();
+        unsafe {
+            ime_set_surrounding_text(text.as_ptr(), 4);
+            ime_set_surrounding_text(std::ptr::null(), 0);
+        }
+        let r = ime_key(keys::J, false, false);
+        assert!(!r.is_null());
+        unsafe {
+            assert_eq!((*r).action, 1);
+            assert_eq!((*r).backspace, 2);
+            assert_eq!((*r).chars[0], 'ệ' as u32);
+            ime_free(r);
+        }
+
+        ime_clear();
+    }
+
     #[test]
     #[serial]
     fn test_restore_word_ffi_null_safety() {
@@ -84,7 +84,21 @@ pub fn key_to_char_ext(key: u16, caps: bool, shift: bool) -> Option<char> {
             _ => key_to_char(key, caps),
         };
     }
-    key_to_char(key, caps)
+    // Unshifted punctuation (VIQR modifiers are recorded in raw input)
+    match key {
+        keys::MINUS => Some('-'),
+        keys::EQUAL => Some('='),
+        keys::SEMICOLON => Some(';'),
+        keys::QUOTE => Some('\''),
+        keys::COMMA => Some(','),
+        keys::DOT => Some('.'),
+        keys::SLASH => Some('/'),
+        keys::BACKSLASH => Some('\\'),
+        keys::LBRACKET => Some('['),
+        keys::RBRACKET => Some(']'),
+        keys::BACKQUOTE => Some('`'),
+        _ => key_to_char(key, caps),
+    }
 }
 
 /// Collect vowels from buffer with phonological info
@@ -146,7 +160,8 @@ mod test_utils {
     //! Used by `#[cfg(test)]` modules throughout the crate.
 
     use crate::data::keys;
-    use crate::engine::{Action, Engine};
+    use crate::engine::{Action, CompositionEvent, Engine, KeyEvent, MOD_SHIFT};
+    use crate::input::{self, CustomMethod, MethodSpec};
 
     // ============================================================
     // KEY MAPPING
@@ -344,6 +359,75 @@ mod test_utils {
         screen
     }
 
+    /// Simulate typing through `Engine::on_char`, returns screen output
+    ///
+    /// Same input format as `type_word` ('<' is Backspace).
+    pub fn type_chars(e: &mut Engine, input: &str) -> String {
+        let mut screen = String::new();
+        for c in input.chars() {
+            let c = if c == '<' { '\u{8}' } else { c };
+            let r = e.on_char(c, 0);
+            if r.action == Action::Send as u8 {
+                for _ in 0..r.backspace {
+                    screen.pop();
+                }
+                screen.extend(
+                    r.chars[..r.count as usize]
+                        .iter()
+                        .filter_map(|&ch| char::from_u32(ch)),
+                );
+                // Punctuation follows the output unless a shortcut consumed it
+                let is_break = !matches!(c, ' ' | '\u{1b}')
+                    && keys::from_char(c).is_some_and(|(k, shift)| keys::is_break_ext(k, shift));
+                if is_break && !r.key_consumed() {
+                    screen.push(c);
+                }
+            } else if c == '\u{8}' {
+                screen.pop();
+            } else if c != '\u{1b}' {
+                screen.push(c);
+            }
+        }
+        screen
+    }
+
+    /// Simulate typing in composition mode (`Engine::compose`), returns
+    /// committed text followed by the preedit
+    ///
+    /// Same input format as `type_word` ('<' is Backspace).
+    pub fn type_composing(e: &mut Engine, input: &str) -> String {
+        let mut committed = String::new();
+        let mut preedit = String::new();
+        for c in input.chars() {
+            let (key, shift) = match c {
+                '<' => (keys::DELETE, false),
+                _ => keys::from_char(c).expect("US layout char"),
+            };
+            let modifiers = if shift { MOD_SHIFT } else { 0 };
+            let r = e.compose(KeyEvent::press(key).with_modifiers(modifiers));
+            for event in r.events {
+                match event {
+                    CompositionEvent::DeleteSurrounding(n) => {
+                        for _ in 0..n {
+                            committed.pop();
+                        }
+                    }
+                    CompositionEvent::Commit(text) => committed.push_str(&text),
+                    CompositionEvent::Preedit { text, cursor } => {
+                        assert_eq!(cursor, text.chars().count());
+                        preedit = text;
+                    }
+                }
+            }
+            // The app handles keys passed through: only Backspace edits text
+            if r.pass_through && key == keys::DELETE {
+                assert!(preedit.is_empty());
+                committed.pop();
+            }
+        }
+        committed + &preedit
+    }
+
     // ============================================================
     // TEST RUNNERS
     // ============================================================
@@ -395,6 +479,16 @@ mod test_utils {
         }
     }
 
+    /// Run VIQR test cases
+    pub fn viqr(cases: &[(&str, &str)]) {
+        for (input, expected) in cases {
+            let mut e = Engine::new();
+            e.set_method(2);
+            let result = type_word(&mut e, input);
+            assert_eq!(result, *expected, "[VIQR] '{}' → '{}'", input, result);
+        }
+    }
+
     /// Run Telex test cases with traditional tone placement (hòa, thúy style)
     pub fn telex_traditional(cases: &[(&str, &str)]) {
         for (input, expected) in cases {
@@ -424,6 +518,39 @@ mod test_utils {
         }
     }
 
+    /// Create engine using a data-driven method built from `spec`
+    /// (registered as "custom-<spec name>" so built-in names stay reserved)
+    pub fn custom_engine(mut spec: MethodSpec) -> Engine {
+        spec.name = format!("custom-{}", spec.name);
+        let method = CustomMethod::from_spec(&spec).expect("valid method spec");
+        input::register(Box::new(method)).expect("method registered");
+        let mut e = Engine::new();
+        assert!(e.set_method_by_name(&spec.name));
+        e
+    }
+
+    /// Run Telex test cases through the data-driven Telex description
+    pub fn telex_custom(cases: &[(&str, &str)]) {
+        for (input, expected) in cases {
+            let mut e = custom_engine(MethodSpec::telex());
+            let result = type_word(&mut e, input);
+            assert_eq!(
+                result, *expected,
+                "[Custom Telex] '{}' → '{}'",
+                input, result
+            );
+        }
+    }
+
+    /// Run VNI test cases through the data-driven VNI description
+    pub fn vni_custom(cases: &[(&str, &str)]) {
+        for (input, expected) in cases {
+            let mut e = custom_engine(MethodSpec::vni());
+            let result = type_word(&mut e, input);
+            assert_eq!(result, *expected, "[Custom VNI] '{}' → '{}'", input, result);
+        }
+    }
+
     /// Simulate typing with extended parameters (supports raw mode prefix)
     /// Input format: use special prefixes to trigger shift+key:
     /// - "@" triggers Shift+2
@@ -516,6 +643,27 @@ mod test_utils {
         }
         screen
     }
+
+    /// Whether `expand` returns the system hour at `offset_minutes` from UTC
+    ///
+    /// `expand` must format "%Y-%m-%d %H"; the hour may roll over while it
+    /// runs, so either side of the call is accepted.
+    pub fn is_system_hour(offset_minutes: i32, expand: impl FnOnce() -> String) -> bool {
+        use crate::engine::shortcut::template::{expand as expand_at, DateTime, FixedClock};
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let hour = || {
+            let secs = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs() as i64;
+            let now = DateTime::from_unix(secs + i64::from(offset_minutes) * 60);
+            expand_at("{date:%Y-%m-%d %H}", &FixedClock(now), None).before
+        };
+        let before = hour();
+        let text = expand();
+        text == before || text == hour()
+    }
 }
 
 // Re-export test utilities for use in other test modules
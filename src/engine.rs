@@ -1,11 +1,44 @@
-use gonhanh_core::engine::{Engine as CoreEngine, Result as CoreResult};
+use gonhanh_core::engine::shortcut::template::{Clipboard, Clock, DateTime};
+use gonhanh_core::engine::shortcut::ShortcutTable;
+use gonhanh_core::engine::{Engine as CoreEngine, KeyEvent, Output};
 use lazy_static::lazy_static;
 use parking_lot::Mutex;
+use std::path::PathBuf;
 use std::sync::Arc;
 use crate::settings::Settings;
 
-// Public alias for Result to be used by hook
-pub use gonhanh_core::engine::Result as ImeResult;
+/// Local time for shortcut templates ({date}, {time}, {weekday_vi})
+struct LocalClock;
+
+impl Clock for LocalClock {
+    fn now(&self) -> DateTime {
+        let st = unsafe { windows::Win32::System::SystemInformation::GetLocalTime() };
+        DateTime {
+            year: st.wYear as i32,
+            month: st.wMonth as u8,
+            day: st.wDay as u8,
+            hour: st.wHour as u8,
+            minute: st.wMinute as u8,
+            second: st.wSecond as u8,
+            weekday: st.wDayOfWeek as u8,
+        }
+    }
+}
+
+/// Windows clipboard for the {clipboard} placeholder
+struct SystemClipboard;
+
+impl Clipboard for SystemClipboard {
+    fn text(&self) -> Option<String> {
+        crate::hook::get_clipboard_text()
+    }
+}
+
+/// Give a shortcut table the Windows clock and clipboard
+fn attach_sources(table: &mut ShortcutTable) {
+    table.set_clock(Box::new(LocalClock));
+    table.set_clipboard(Some(Box::new(SystemClipboard)));
+}
 
 pub struct EngineWrapper {
     core: CoreEngine,
@@ -16,39 +49,55 @@ impl EngineWrapper {
     pub fn new() -> Self {
         let settings = Settings::load();
         let mut core = CoreEngine::new();
-        
-        // Apply settings to core
-        core.set_method(settings.method as u8);
-        core.set_enabled(settings.enabled);
-        core.set_modern_tone(settings.modern_tone);
-        core.set_auto_capitalize(settings.auto_capitalize);
-        core.set_skip_w_shortcut(!settings.w_as_u_at_start);
-        core.set_bracket_shortcut(settings.bracket_as_uo);
-        core.set_english_auto_restore(settings.auto_restore_english);
-
-        Self { core, settings }
+        core.apply_config(&settings.engine_config());
+
+        attach_sources(core.shortcuts_mut());
+
+        let mut wrapper = Self { core, settings };
+        wrapper.reload_shortcuts();
+        wrapper
+    }
+
+    /// Load shortcuts.json into the core (keeps the current table if missing or invalid)
+    pub fn reload_shortcuts(&mut self) {
+        let Some(path) = Settings::shortcuts_path() else { return };
+        if let Ok(mut table) = ShortcutTable::load(&path) {
+            attach_sources(&mut table);
+            *self.core.shortcuts_mut() = table;
+        }
+    }
+
+    /// Prepare shortcuts.json for editing and return its path.
+    /// Picks up edits made since the last call, then writes the table back
+    /// (creating the file on first use).
+    pub fn shortcuts_file(&mut self) -> Option<PathBuf> {
+        let path = Settings::shortcuts_path()?;
+        self.reload_shortcuts();
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        self.core.shortcuts().save(&path).ok()?;
+        Some(path)
+    }
+
+    /// Run a key event through the core (Ctrl/Alt combos clear the word)
+    pub fn process_key(&mut self, event: KeyEvent) -> Output {
+        self.core.on_event(event)
     }
 
-    pub fn process_key(&mut self, keycode: u16, _shift: bool, capslock: bool) -> CoreResult {
-        // Core handles the logic. We just pass the mapped key.
-        // If keycode mapping failed (e.g. keycode == 0), caller shouldn't call this or we handle it.
-        // We assume valid keycode here.
-        self.core.on_key(keycode, capslock, false) 
-        // Note: 'ctrl' param is false because we handle shortcuts locally or pass them?
-        // Core takes (key, caps, ctrl).
-        // If we want core to handle Ctrl shortcuts/bypass, we should pass correct Ctrl state.
-        // For now pass false (assuming no Ctrl combos handled by IME core logic).
+    /// Scope shortcuts to the foreground process (e.g. "code.exe")
+    pub fn set_context(&mut self, process: &str) {
+        let process = process.to_lowercase();
+        if process.is_empty() {
+            self.core.set_context(&[]);
+        } else {
+            self.core.set_context(&[process.as_str()]);
+        }
     }
 
     pub fn update_settings(&mut self, new_settings: Settings) {
-        self.settings = new_settings.clone();
-        self.core.set_method(new_settings.method as u8);
-        self.core.set_enabled(new_settings.enabled);
-        self.core.set_modern_tone(new_settings.modern_tone);
-        self.core.set_auto_capitalize(new_settings.auto_capitalize);
-        self.core.set_skip_w_shortcut(!new_settings.w_as_u_at_start);
-        self.core.set_bracket_shortcut(new_settings.bracket_as_uo);
-        self.core.set_english_auto_restore(new_settings.auto_restore_english);
+        self.core.apply_config(&new_settings.engine_config());
+        self.settings = new_settings;
         self.settings.save();
     }
 
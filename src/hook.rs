@@ -1,6 +1,7 @@
-use crate::engine::{ImeResult, ENGINE};
+use crate::engine::ENGINE;
 use crate::key_map::map_vk_to_core;
-use gonhanh_core::engine::Action;
+use gonhanh_core::data::keys;
+use gonhanh_core::engine::{KeyEvent, Output, MOD_ALT, MOD_CAPS_LOCK, MOD_CTRL, MOD_SHIFT};
 
 use std::mem::size_of;
 use std::thread;
@@ -11,7 +12,7 @@ use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_INFORMATION,
 use windows::Win32::System::ProcessStatus::GetModuleBaseNameW;
 use windows::Win32::UI::Input::KeyboardAndMouse::{
     GetKeyState, SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP,
-    KEYEVENTF_UNICODE, VIRTUAL_KEY, VK_BACK, VK_CAPITAL, VK_SHIFT,
+    KEYEVENTF_UNICODE, VIRTUAL_KEY, VK_BACK, VK_CAPITAL, VK_LEFT, VK_SHIFT,
 };
 use windows::Win32::UI::WindowsAndMessaging::{
     CallNextHookEx, GetMessageW, SetWindowsHookExW, HHOOK, KBDLLHOOKSTRUCT,
@@ -19,13 +20,15 @@ use windows::Win32::UI::WindowsAndMessaging::{
     GetForegroundWindow, GetWindowThreadProcessId,
     GetGUIThreadInfo, GUITHREADINFO,
 };
-use windows::Win32::System::DataExchange::{OpenClipboard, EmptyClipboard, SetClipboardData, CloseClipboard};
+use windows::Win32::System::DataExchange::{OpenClipboard, EmptyClipboard, GetClipboardData, SetClipboardData, CloseClipboard};
 use windows::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
-use windows::Win32::Foundation::{HANDLE};
+use windows::Win32::Foundation::{HANDLE, HGLOBAL};
 
 
 static mut HOOK_HANDLE: HHOOK = HHOOK(0);
 static HOOK_INSTALLED: AtomicBool = AtomicBool::new(false);
+// Foreground window the shortcut context was taken from
+static mut LAST_FOREGROUND: isize = 0;
 
 
 fn set_clipboard_text(text: &str) {
@@ -52,6 +55,41 @@ fn set_clipboard_text(text: &str) {
     }
 }
 
+/// Read clipboard text (for the `{clipboard}` shortcut placeholder)
+pub fn get_clipboard_text() -> Option<String> {
+    unsafe {
+        OpenClipboard(HWND(0)).ok()?;
+        let mut text = None;
+        // CF_UNICODETEXT = 13
+        if let Ok(h_data) = GetClipboardData(13) {
+            let h_mem = HGLOBAL(h_data.0 as *mut std::ffi::c_void);
+            let p_mem = GlobalLock(h_mem) as *const u16;
+            if !p_mem.is_null() {
+                let mut len = 0;
+                while *p_mem.add(len) != 0 {
+                    len += 1;
+                }
+                text = Some(String::from_utf16_lossy(std::slice::from_raw_parts(p_mem, len)));
+                let _ = GlobalUnlock(h_mem);
+            }
+        }
+        let _ = CloseClipboard();
+        text
+    }
+}
+
+/// Move the caret left (shortcut `{cursor}` placeholder)
+unsafe fn send_cursor_left(count: usize) {
+    let mut inputs: Vec<INPUT> = Vec::new();
+    for _ in 0..count {
+        inputs.push(create_key_input(VK_LEFT, false));
+        inputs.push(create_key_input(VK_LEFT, true));
+    }
+    if !inputs.is_empty() {
+        SendInput(&inputs, size_of::<INPUT>() as i32);
+    }
+}
+
 fn log_to_file(msg: &str) {
     if !ENGINE.lock().get_settings().debug_enabled {
         return;
@@ -158,26 +196,41 @@ unsafe extern "system" fn keyboard_proc(code: i32, wparam: WPARAM, lparam: LPARA
         // Only process if enabled and KeyDown
         if is_keydown {
              let alt_down = (flags.0 & 0x20) != 0;
-             // If Ctrl or Alt is held, skip engine processing to avoid shortcut conflicts (like Ctrl+A)
-             if ctrl_down || alt_down {
-                 return CallNextHookEx(HOOK_HANDLE, code, wparam, lparam);
+
+             // Scoped shortcuts: context is the foreground process, looked up on focus change
+             let foreground = GetForegroundWindow();
+             let process = if foreground.0 != LAST_FOREGROUND {
+                 LAST_FOREGROUND = foreground.0;
+                 Some(get_foreground_process_name().unwrap_or_default())
+             } else {
+                 None
+             };
+
+             let mut modifiers = 0;
+             for (down, bit) in [(shift_down, MOD_SHIFT), (ctrl_down, MOD_CTRL), (alt_down, MOD_ALT), (caps_on, MOD_CAPS_LOCK)] {
+                 if down {
+                     modifiers |= bit;
+                 }
              }
+             let event = KeyEvent::press(core_key)
+                 .with_modifiers(modifiers)
+                 .at((*pkb).time as u64);
 
              let mut engine = ENGINE.lock();
-             // Important: Pass modifiers to engine if needed, or handle locally.
-             // If Ctrl is down, we generally bypass processing in engine (it returns None), 
-             // but we still call it to clear state.
-             
-             let result = engine.process_key(core_key, shift_down, caps_on);
-             
-             match result.action {
-                 x if x == Action::None as u8 => {}
-                 x if x == Action::Send as u8 || x == Action::Restore as u8 => {
-                      drop(engine);
-                      send_replacement(&result);
-                      return LRESULT(1);
+             if let Some(process) = process {
+                 engine.set_context(&process);
+             }
+             // Ctrl/Alt combos (like Ctrl+A) pass through; the core drops its word
+             let output = engine.process_key(event);
+             drop(engine);
+
+             if let Output::Replace { delete, mut insert, consume_key, cursor_left } = output {
+                 // Punctuation ending a restored word is typed after the edit
+                 if !consume_key {
+                     insert.extend(keys::to_char(core_key, shift_down).filter(|c| !c.is_control()));
                  }
-                 _ => {}
+                 send_replacement(delete, &insert, cursor_left);
+                 return LRESULT(1);
              }
         }
     }
@@ -185,7 +238,7 @@ unsafe extern "system" fn keyboard_proc(code: i32, wparam: WPARAM, lparam: LPARA
     CallNextHookEx(HOOK_HANDLE, code, wparam, lparam)
 }
 
-unsafe fn send_replacement(res: &ImeResult) {
+unsafe fn send_replacement(delete: usize, text: &str, cursor_left: usize) {
     // Check if we are targeting Warp
     let is_warp = get_foreground_process_name()
         .map(|n| n.to_lowercase() == "warp.exe")
@@ -220,7 +273,7 @@ unsafe fn send_replacement(res: &ImeResult) {
         
         // 1. Send Backspaces
         let mut inputs: Vec<INPUT> = Vec::new();
-        for _ in 0..res.backspace {
+        for _ in 0..delete {
             inputs.push(create_key_input(VK_BACK, false));
             inputs.push(create_key_input(VK_BACK, true));
         }
@@ -229,15 +282,8 @@ unsafe fn send_replacement(res: &ImeResult) {
         }
 
         // 2. Insert text via Clipboard
-        let mut text = String::new();
-        for i in 0..res.count as usize {
-            if let Some(ch) = char::from_u32(res.chars[i]) {
-                text.push(ch);
-            }
-        }
-        
         if !text.is_empty() {
-            set_clipboard_text(&text);
+            set_clipboard_text(text);
             
             // Send Ctrl + V
             let mut paste_inputs: Vec<INPUT> = Vec::new();
@@ -252,6 +298,7 @@ unsafe fn send_replacement(res: &ImeResult) {
             
             SendInput(&paste_inputs, size_of::<INPUT>() as i32);
         }
+        send_cursor_left(cursor_left);
         return;
     } 
 
@@ -259,23 +306,23 @@ unsafe fn send_replacement(res: &ImeResult) {
     let mut inputs: Vec<INPUT> = Vec::new();
 
     // 1. Backspace
-    for _ in 0..res.backspace {
+    for _ in 0..delete {
         inputs.push(create_key_input(VK_BACK, false)); // Down
         inputs.push(create_key_input(VK_BACK, true));  // Up
     }
 
-    // 2. Text
-    for i in 0..res.count as usize {
-        let c = res.chars[i];
-        if c != 0 {
-            inputs.push(create_unicode_input(c, false));
-            inputs.push(create_unicode_input(c, true));
-        }
+    // 2. Text (UTF-16 units, so chars outside the BMP go as surrogate pairs)
+    for unit in text.encode_utf16() {
+        inputs.push(create_unicode_input(unit, false));
+        inputs.push(create_unicode_input(unit, true));
     }
 
     if !inputs.is_empty() {
         SendInput(&inputs, size_of::<INPUT>() as i32);
     }
+
+    // 3. Caret placement
+    send_cursor_left(cursor_left);
 }
 
 
@@ -325,7 +372,7 @@ fn create_key_input(vk: VIRTUAL_KEY, up: bool) -> INPUT {
     }
 }
 
-fn create_unicode_input(c: u32, up: bool) -> INPUT {
+fn create_unicode_input(unit: u16, up: bool) -> INPUT {
     let mut flags = KEYEVENTF_UNICODE;
     if up {
         flags |= KEYEVENTF_KEYUP;
@@ -336,7 +383,7 @@ fn create_unicode_input(c: u32, up: bool) -> INPUT {
         Anonymous: INPUT_0 {
             ki: KEYBDINPUT {
                 wVk: VIRTUAL_KEY(0),
-                wScan: c as u16, // Assuming BMP
+                wScan: unit,
                 dwFlags: flags,
                 time: 0,
                 dwExtraInfo: 0,
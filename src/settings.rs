@@ -1,3 +1,4 @@
+use gonhanh_core::engine::EngineConfig;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{PathBuf};
@@ -6,6 +7,7 @@ use std::path::{PathBuf};
 pub enum InputMethod {
     Telex = 0,
     Vni = 1,
+    Viqr = 2,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -56,6 +58,21 @@ impl Default for Settings {
 }
 
 impl Settings {
+    /// Core engine options for these settings
+    pub fn engine_config(&self) -> EngineConfig {
+        EngineConfig {
+            method: self.method as u8,
+            enabled: self.enabled,
+            modern_tone: self.modern_tone,
+            auto_capitalize: self.auto_capitalize,
+            skip_w_shortcut: !self.w_as_u_at_start,
+            bracket_shortcut: self.bracket_as_uo,
+            english_auto_restore: self.auto_restore_english,
+            spell_check: self.spell_check,
+            ..Default::default()
+        }
+    }
+
     pub fn load() -> Self {
         if let Some(path) = Self::get_config_path() {
             if path.exists() {
@@ -80,6 +97,12 @@ impl Settings {
         }
     }
 
+    /// Shortcut table file (gõ tắt), stored next to settings.json
+    pub fn shortcuts_path() -> Option<PathBuf> {
+        directories::ProjectDirs::from("org", "gonhanh", "GoNhanh")
+            .map(|dirs| dirs.config_dir().join("shortcuts.json"))
+    }
+
     fn get_config_path() -> Option<PathBuf> {
         if let Some(dirs) = directories::ProjectDirs::from("org", "gonhanh", "GoNhanh") {
             return Some(dirs.config_dir().join("settings.json"));
@@ -209,7 +209,23 @@ unsafe extern "system" fn dialog_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lpar
                   }
               }
              
-             if id == IDC_BTN_TABLE || id == IDC_BTN_SHORTCUT {
+             if id == IDC_BTN_TABLE {
+                 // Open shortcuts.json in the default editor; edits apply on the next click or restart
+                 let path = ENGINE.lock().shortcuts_file();
+                 match path {
+                     Some(path) => {
+                         use windows::Win32::UI::Shell::ShellExecuteW;
+                         use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+                         let file = encode_wide(&path.to_string_lossy());
+                         ShellExecuteW(hwnd, PCWSTR(encode_wide("open").as_ptr()), PCWSTR(file.as_ptr()), PCWSTR::null(), PCWSTR::null(), SW_SHOWNORMAL);
+                     }
+                     None => {
+                         MessageBoxW(hwnd, PCWSTR(encode_wide("Không thể lưu bảng gõ tắt").as_ptr()), PCWSTR(encode_wide("Thông báo").as_ptr()), MB_OK);
+                     }
+                 }
+             }
+
+             if id == IDC_BTN_SHORTCUT {
                  MessageBoxW(hwnd, PCWSTR(encode_wide("Tính năng đang phát triển").as_ptr()), PCWSTR(encode_wide("Thông báo").as_ptr()), MB_OK);
              }
             
@@ -312,13 +328,16 @@ unsafe fn init_controls(hwnd: HWND) {
     // Combo initialization
     let telex = encode_wide("Telex");
     let vni = encode_wide("VNI");
+    let viqr = encode_wide("VIQR");
     
     SendDlgItemMessageW(hwnd, IDC_COMBO_METHOD, CB_ADDSTRING, WPARAM(0), LPARAM(telex.as_ptr() as isize));
     SendDlgItemMessageW(hwnd, IDC_COMBO_METHOD, CB_ADDSTRING, WPARAM(0), LPARAM(vni.as_ptr() as isize));
+    SendDlgItemMessageW(hwnd, IDC_COMBO_METHOD, CB_ADDSTRING, WPARAM(0), LPARAM(viqr.as_ptr() as isize));
     
     let sel = match settings.method {
         InputMethod::Telex => 0,
         InputMethod::Vni => 1,
+        InputMethod::Viqr => 2,
     };
     SendDlgItemMessageW(hwnd, IDC_COMBO_METHOD, CB_SETCURSEL, WPARAM(sel), LPARAM(0));
 }
@@ -336,7 +355,11 @@ unsafe fn save_settings_from_ui(hwnd: HWND) {
     let auto_cap = is_dlg_button_checked(hwnd, IDC_CHK_AUTO_CAP);
     
     let sel = SendDlgItemMessageW(hwnd, IDC_COMBO_METHOD, CB_GETCURSEL, WPARAM(0), LPARAM(0));
-    let method = if sel.0 == 1 { InputMethod::Vni } else { InputMethod::Telex };
+    let method = match sel.0 {
+        1 => InputMethod::Vni,
+        2 => InputMethod::Viqr,
+        _ => InputMethod::Telex,
+    };
     
     let mut current = ENGINE.lock().get_settings();
     current.enabled = enabled;